@@ -0,0 +1,98 @@
+/*!
+	Memory allocators for the buffers of [Dynamic](crate::Dynamic) matrices, to allocate them in arenas or with a custom strategy
+
+	[Allocator] is a stable shim of the unstable `core::alloc::Allocator` trait with the same signatures, so that implementations can easily switch to it once stabilized. [Global] uses the global allocator and is the default for [Dynamic](crate::Dynamic), and any `&Al` is also an allocator, which allows matrices to borrow an arena like a [Workspace](crate::workspace::Workspace). Operations give matrices in the [Global] allocator
+*/
+
+use core::{
+	alloc::Layout,
+	fmt,
+	ptr::{self, NonNull},
+	};
+
+
+/// the allocator could not satisfy a request
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct AllocError;
+impl fmt::Display for AllocError {
+	fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result  {f.write_str("memory allocation failed")}
+}
+impl std::error::Error for AllocError {}
+
+/**
+	memory allocator, same as the unstable `core::alloc::Allocator`
+
+	# Safety
+	allocated blocks must stay valid until deallocated, and moving or cloning the allocator must not invalidate them
+*/
+pub unsafe trait Allocator {
+	/// allocate a block fitting the layout, its content is uninitialized
+	fn allocate(&self, layout: Layout) -> Result<NonNull<[u8]>, AllocError>;
+	/**
+		deallocate a block
+
+		# Safety
+		`ptr` must have been allocated by this allocator with the same layout
+	*/
+	unsafe fn deallocate(&self, ptr: NonNull<u8>, layout: Layout);
+}
+unsafe impl<Al: Allocator + ?Sized> Allocator for &Al {
+	fn allocate(&self, layout: Layout) -> Result<NonNull<[u8]>, AllocError>  {(**self).allocate(layout)}
+	unsafe fn deallocate(&self, ptr: NonNull<u8>, layout: Layout)  {(**self).deallocate(ptr, layout)}
+}
+
+/// the global memory allocator, as used by [Vec]
+#[derive(Copy, Clone, Debug, Default)]
+pub struct Global;
+unsafe impl Allocator for Global {
+	fn allocate(&self, layout: Layout) -> Result<NonNull<[u8]>, AllocError> {
+		// the global allocator doesn't allow zero-sized blocks
+		let ptr = if layout.size() == 0  {ptr::without_provenance_mut(layout.align())}
+			// safety: the layout size is not zero
+			else {unsafe {std::alloc::alloc(layout)}};
+		NonNull::new(ptr)
+			.map(|ptr|  NonNull::slice_from_raw_parts(ptr, layout.size()))
+			.ok_or(AllocError)
+	}
+	unsafe fn deallocate(&self, ptr: NonNull<u8>, layout: Layout) {
+		if layout.size() != 0  {std::alloc::dealloc(ptr.as_ptr(), layout)}
+	}
+}
+
+
+
+#[test]
+fn test_allocator() {
+	use crate::prelude::*;
+	use crate::matrices::*;
+	use core::cell::Cell;
+
+	// allocations go to the given allocator
+	struct Counting(Cell<usize>);
+	unsafe impl Allocator for Counting {
+		fn allocate(&self, layout: Layout) -> Result<NonNull<[u8]>, AllocError> {
+			self.0.set(self.0.get() + layout.size());
+			Global.allocate(layout)
+		}
+		unsafe fn deallocate(&self, ptr: NonNull<u8>, layout: Layout) {
+			self.0.set(self.0.get() - layout.size());
+			Global.deallocate(ptr, layout)
+		}
+	}
+	let counting = Counting(Cell::new(0));
+	let names = DMatrix::<String, Dyn, Dyn, &Counting>::field_in([3, 2], ALIGNMENT, true, &counting, |[i,j]|  format!("{}{}", i, j));
+	// 24 bytes strings span a multiple of 64 bytes every 8 rows
+	assert_eq!(names.strides(), [1, 8]);
+	assert_eq!(counting.0.get(), 16 * size_of::<String>());
+	assert_eq!(names[[2,1]], "21");
+	let copy = names.clone();
+	assert_eq!(counting.0.get(), 32 * size_of::<String>());
+	drop((names, copy));
+	assert_eq!(counting.0.get(), 0);
+
+	// products are in the global allocator
+	let ones = DMatrix::<f64, Dyn, Dyn, &Counting>::field_in([3, 3], ALIGNMENT, false, &counting, |_|  1.);
+	let product: DMatrix<f64> = &ones * &ones;
+	assert_eq!(product[[2,2]], 3.);
+	assert_eq!(counting.0.get(), 9 * size_of::<f64>());
+}
@@ -0,0 +1,464 @@
+/*!
+	Banded and tridiagonal matrices, storing only their diagonals
+
+	- [Banded] stores `lower` sub-diagonals and `upper` super-diagonals in the LAPACK band layout, its [BandedLu] factorization solves square systems in `O(n lower (lower+upper))`
+	- [Tridiagonal] stores its three diagonals in separate vectors and solves systems in `O(n)` with the Thomas algorithm
+
+	Both multiply with any dense [Matrix] using the common operators or [MulTo], and are [LinearOperator]s.
+*/
+
+use crate::prelude::*;
+use crate::matrix::*;
+use crate::matrices::*;
+use crate::operators::*;
+use crate::krylov::LinearOperator;
+use crate::sparse::FactorizationError;
+use crate::permutation::PermutationMatrix;
+use crate::workspace::{self, Workspace, ScratchMatrix, MulWithWorkspace, workspace_size};
+
+use core::ops::Mul;
+use num_traits::Float;
+
+
+/**
+	matrix with nonzero elements only in a band around its diagonal
+
+	Element `[i,j]` is stored in the column `j` of a dense `(lower+upper+1, columns)` array, at row `upper + i - j`. This is the band layout of LAPACK
+*/
+#[derive(Clone, Debug, PartialEq)]
+pub struct Banded<T> {
+	shape: [usize; 2],
+	lower: usize,
+	upper: usize,
+	data: Vec<T>,
+}
+impl<T: Element> Banded<T> {
+	/// number of rows in the band storage
+	fn height(&self) -> usize  {self.lower + self.upper + 1}
+	/// position of the given index in the band storage, if in the band
+	fn position(&self, [i, j]: [usize; 2]) -> Option<usize> {
+		if i < self.shape[0] && j < self.shape[1] && i + self.upper >= j && i <= j + self.lower
+			{Some(self.upper + i - j + j * self.height())}
+		else {None}
+	}
+	/// shape of the matrix
+	pub fn shape(&self) -> [usize; 2]  {self.shape}
+	/// number of sub-diagonals and super-diagonals
+	pub fn bandwidths(&self) -> [usize; 2]  {[self.lower, self.upper]}
+	/// true if the given index is in the band
+	pub fn contains(&self, index: [usize; 2]) -> bool  {self.position(index).is_some()}
+	/// band storage, elements outside the matrix are not meaningful
+	pub fn band(&self) -> &[T]  {&self.data}
+	/// mutable element at the given index, if in the band
+	pub fn get_mut(&mut self, index: [usize; 2]) -> Option<&mut T> {
+		let position = self.position(index)?;
+		Some(&mut self.data[position])
+	}
+}
+impl<T: Scalar> Banded<T> {
+	/// banded matrix of zeros with the given bandwidths
+	pub fn zeros(shape: [usize; 2], lower: usize, upper: usize) -> Self  {Self {
+		shape,
+		lower,
+		upper,
+		data: vec![T::zero(); (lower+upper+1) * shape[1]],
+	}}
+	/// build from a function of the index, it is only called for indices in the band
+	pub fn field<F>(shape: [usize; 2], lower: usize, upper: usize, mut field: F) -> Self
+	where F: FnMut([usize; 2]) -> T
+	{
+		let mut new = Self::zeros(shape, lower, upper);
+		for j in 0 .. shape[1] {
+			for i in j.saturating_sub(upper) .. shape[0].min(j + lower + 1) {
+				*new.get_mut([i,j]).unwrap() = field([i,j]);
+			}
+		}
+		new
+	}
+	/// copy the band of the given dense matrix, elements outside the band are ignored
+	pub fn from_dense<A: Array<Element=T>>(matrix: &Matrix<A>, lower: usize, upper: usize) -> Self {
+		Self::field(matrix.shape(), lower, upper, |index|  matrix[index].clone())
+	}
+	/// element at the given index, zero outside the band
+	pub fn get(&self, index: [usize; 2]) -> T {
+		match self.position(index) {
+			Some(position) => self.data[position].clone(),
+			None => T::zero(),
+		}
+	}
+}
+impl<T: Scalar> Banded<T> {
+	/// copy into a full dense matrix
+	pub fn to_dense(&self) -> DMatrix<T> {
+		DMatrix::<T>::field(self.shape, |index|  self.get(index))
+	}
+}
+impl<T: Scalar + Float> Banded<T> {
+	/// LU factorization with partial pivoting of this square matrix
+	pub fn lu(&self) -> Result<BandedLu<T>, FactorizationError> {
+		BandedLu::new(self)
+	}
+}
+
+/**
+	LU factorization `P A = L U` with partial pivoting of a square banded matrix
+
+	Pivoting widens the upper bandwidth of `U` to `lower+upper`, so the factors need `2 lower + upper + 1` diagonals like LAPACK `gbtrf`
+*/
+#[derive(Clone, Debug)]
+pub struct BandedLu<T> {
+	/// factors in band layout with `lower` sub-diagonals of multipliers and `lower+upper` super-diagonals of `U`
+	factors: Banded<T>,
+	/// row swapped with each row during the elimination
+	pivots: Vec<usize>,
+}
+impl<T: Scalar + Float> BandedLu<T> {
+	/// factorize the given square matrix
+	pub fn new(matrix: &Banded<T>) -> Result<Self, FactorizationError> {
+		if matrix.shape[0] != matrix.shape[1]
+			{return Err(FactorizationError::NotSquare)}
+		let mut new = Self {
+			factors: Banded::zeros(matrix.shape, matrix.lower, matrix.lower + matrix.upper),
+			pivots: vec![0; matrix.shape[0]],
+		};
+		new.refactorize(matrix)?;
+		Ok(new)
+	}
+	/**
+		factorize a new matrix with the same shape and bandwidths, reusing the factors memory.
+
+		On failure, the factorization is left in an unspecified state until a successful refactorization
+	*/
+	pub fn refactorize(&mut self, matrix: &Banded<T>) -> Result<(), FactorizationError> {
+		let a = &mut self.factors;
+		if matrix.shape != a.shape || matrix.lower != a.lower || matrix.lower + matrix.upper != a.upper
+			{return Err(FactorizationError::PatternMismatch)}
+		let size = matrix.shape[0];
+		let (lower, upper) = (a.lower, a.upper);
+		for j in 0 .. size {
+			for i in j.saturating_sub(upper) ..= (size-1).min(j + lower) {
+				*a.get_mut([i,j]).unwrap() = matrix.get([i,j]);
+			}
+		}
+		for j in 0 .. size {
+			let last = (size-1).min(j + lower);
+			let right = (size-1).min(j + upper);
+			let pivot = (j ..= last)
+				.map(|i|  (i, a.get([i,j]).abs()))
+				.fold((j, -T::one()), |best, candidate|  if candidate.1 > best.1 {candidate} else {best})
+				.0;
+			if a.get([pivot,j]).is_zero()
+				{return Err(FactorizationError::Singular(j))}
+			self.pivots[j] = pivot;
+			if pivot != j {
+				for c in j ..= right {
+					let (x, y) = (a.get([j,c]), a.get([pivot,c]));
+					*a.get_mut([j,c]).unwrap() = y;
+					*a.get_mut([pivot,c]).unwrap() = x;
+				}
+			}
+			let diagonal = a.get([j,j]);
+			for i in j+1 ..= last {
+				let multiplier = a.get([i,j]) / diagonal;
+				*a.get_mut([i,j]).unwrap() = multiplier;
+				for c in j+1 ..= right {
+					let value = a.get([i,c]) - multiplier * a.get([j,c]);
+					*a.get_mut([i,c]).unwrap() = value;
+				}
+			}
+		}
+		Ok(())
+	}
+	/// size of the factorized matrix
+	pub fn size(&self) -> usize  {self.pivots.len()}
+	/// row permutation `P` applied by the pivoting, such that `P A = L U`
+	pub fn permutation(&self) -> PermutationMatrix  {PermutationMatrix::from_swaps(self.size(), &self.pivots)}
+	/// solve `A x = b` for each column of `b`, writing `x` in place of `b`
+	pub fn solve_inplace<B: ArrayMut<Element=T>>(&self, b: &mut Matrix<B>) {
+		let size = self.size();
+		assert_eq!(b.rows(), size);
+		let a = &self.factors;
+		for c in 0 .. b.columns() {
+			// L y = P b, applying row swaps in the elimination order
+			for j in 0 .. size {
+				let pivot = self.pivots[j];
+				if pivot != j {
+					let (x, y) = (b[[j,c]], b[[pivot,c]]);
+					b[[j,c]] = y;
+					b[[pivot,c]] = x;
+				}
+				for i in j+1 ..= (size-1).min(j + a.lower) {
+					b[[i,c]] = b[[i,c]] - a.get([i,j]) * b[[j,c]];
+				}
+			}
+			// U x = y
+			for i in (0 .. size).rev() {
+				let value = (i+1 .. size.min(i + a.upper + 1))
+					.fold(b[[i,c]], |value, k|  value - a.get([i,k]) * b[[k,c]]);
+				b[[i,c]] = value / a.get([i,i]);
+			}
+		}
+	}
+	/// solve `A x = b` for each column of `b`
+	pub fn solve<B: Array<Element=T>>(&self, b: &Matrix<B>) -> Matrix<Dynamic<T, Dyn, B::C>> {
+		let mut x = Matrix::from_field(b.shape(), |index|  b[index]);
+		self.solve_inplace(&mut x);
+		x
+	}
+	/// bytes of workspace drawn by [Self::solve_with_workspace] for a right-hand side of the given shape
+	pub fn workspace_size(&self, shape: [usize; 2]) -> usize  {workspace_size::<T>(shape)}
+	/// same as [Self::solve] but drawing the result from the workspace
+	pub fn solve_with_workspace<'w, B: Array<Element=T>>(&self, b: &Matrix<B>, workspace: &'w Workspace) -> ScratchMatrix<'w, T, Dyn, B::C> {
+		let mut x = workspace.field(b.shape(), |index|  b[index]);
+		self.solve_inplace(&mut x);
+		x
+	}
+}
+
+
+/// square matrix with nonzero elements only on its diagonal, sub-diagonal and super-diagonal
+#[derive(Clone, Debug, PartialEq)]
+pub struct Tridiagonal<T> {
+	lower: Vec<T>,
+	diagonal: Vec<T>,
+	upper: Vec<T>,
+}
+impl<T: Element> Tridiagonal<T> {
+	/// build from its diagonals, the sub-diagonal and super-diagonal must be one element shorter than the diagonal
+	pub fn try_from_diagonals(lower: Vec<T>, diagonal: Vec<T>, upper: Vec<T>) -> Option<Self> {
+		let size = diagonal.len();
+		if lower.len() + 1 != size.max(1) || upper.len() + 1 != size.max(1)  {return None}
+		Some(Self {lower, diagonal, upper})
+	}
+	/// number of rows and columns
+	pub fn size(&self) -> usize  {self.diagonal.len()}
+	/// shape of the matrix
+	pub fn shape(&self) -> [usize; 2]  {[self.size(), self.size()]}
+	/// sub-diagonal, element `i` is at index `[i+1,i]`
+	pub fn lower(&self) -> &[T]  {&self.lower}
+	/// diagonal
+	pub fn diagonal(&self) -> &[T]  {&self.diagonal}
+	/// super-diagonal, element `i` is at index `[i,i+1]`
+	pub fn upper(&self) -> &[T]  {&self.upper}
+	/// sub-diagonal, mutable
+	pub fn lower_mut(&mut self) -> &mut [T]  {&mut self.lower}
+	/// diagonal, mutable
+	pub fn diagonal_mut(&mut self) -> &mut [T]  {&mut self.diagonal}
+	/// super-diagonal, mutable
+	pub fn upper_mut(&mut self) -> &mut [T]  {&mut self.upper}
+}
+impl<T: Scalar> Tridiagonal<T> {
+	/// tridiagonal matrix of zeros
+	pub fn zeros(size: usize) -> Self  {Self {
+		lower: vec![T::zero(); size.saturating_sub(1)],
+		diagonal: vec![T::zero(); size],
+		upper: vec![T::zero(); size.saturating_sub(1)],
+	}}
+	/// copy the three central diagonals of the given square matrix
+	pub fn from_dense<A: Array<Element=T>>(matrix: &Matrix<A>) -> Self {
+		assert_eq!(matrix.rows(), matrix.columns());
+		let size = matrix.rows();
+		Self {
+			lower: (1 .. size).map(|i|  matrix[[i,i-1]].clone()).collect(),
+			diagonal: (0 .. size).map(|i|  matrix[[i,i]].clone()).collect(),
+			upper: (1 .. size).map(|i|  matrix[[i-1,i]].clone()).collect(),
+		}
+	}
+	/// element at the given index, zero outside the three diagonals
+	pub fn get(&self, [i, j]: [usize; 2]) -> T {
+		assert!(i < self.size() && j < self.size());
+		if i == j  {self.diagonal[i].clone()}
+		else if i == j+1  {self.lower[j].clone()}
+		else if j == i+1  {self.upper[i].clone()}
+		else {T::zero()}
+	}
+	/// convert to the general band storage
+	pub fn to_banded(&self) -> Banded<T> {
+		Banded::field(self.shape(), 1, 1, |index|  self.get(index))
+	}
+	/**
+		solve `A x = b` for each column of `b` with the Thomas algorithm, writing `x` in place of `b`.
+
+		There is no pivoting, so it is only stable for diagonally dominant or symmetric positive definite matrices. `b` is left unchanged on failure
+	*/
+	pub fn solve_inplace<B: ArrayMut<Element=T>>(&self, b: &mut Matrix<B>) -> Result<(), FactorizationError> {
+		let mut coefficients = vec![T::zero(); 2 * self.size()];
+		self.solve_coefficients(b, &mut coefficients)
+	}
+	/// same as [Self::solve_inplace] but drawing the temporaries from the workspace
+	pub fn solve_inplace_with_workspace<B: ArrayMut<Element=T>>(&self, b: &mut Matrix<B>, workspace: &Workspace) -> Result<(), FactorizationError> {
+		let mut coefficients = workspace.zeros::<T, Dyn, Stat<1>>([2 * self.size(), 1]);
+		self.solve_coefficients(b, coefficients.as_slice_mut().unwrap())
+	}
+	/// bytes of workspace drawn by [Self::solve_with_workspace] for a right-hand side of the given shape
+	pub fn workspace_size(&self, shape: [usize; 2]) -> usize {
+		workspace_size::<T>([2 * self.size(), 1]) + workspace_size::<T>(shape)
+	}
+	/// same as [Self::solve] but drawing the temporaries and result from the workspace
+	pub fn solve_with_workspace<'w, B: Array<Element=T>>(&self, b: &Matrix<B>, workspace: &'w Workspace) -> Result<ScratchMatrix<'w, T, Dyn, B::C>, FactorizationError> {
+		let mut x = workspace.field(b.shape(), |index|  b[index].clone());
+		self.solve_inplace_with_workspace(&mut x, workspace)?;
+		Ok(x)
+	}
+	/// Thomas algorithm, using a buffer of `2 size` elements for the pivots and ratios of the forward elimination
+	fn solve_coefficients<B: ArrayMut<Element=T>>(&self, b: &mut Matrix<B>, coefficients: &mut [T]) -> Result<(), FactorizationError> {
+		let size = self.size();
+		assert_eq!(b.rows(), size);
+		if size == 0  {return Ok(())}
+		// forward elimination coefficients do not depend on b
+		let (pivots, ratios) = coefficients.split_at_mut(size);
+		for i in 0 .. size {
+			let pivot = match i {
+				0 => self.diagonal[0].clone(),
+				_ => self.diagonal[i].clone() - self.lower[i-1].clone() * ratios[i-1].clone(),
+			};
+			if pivot.is_zero()
+				{return Err(FactorizationError::Singular(i))}
+			if i+1 < size  {ratios[i] = self.upper[i].clone() / pivot.clone()}
+			pivots[i] = pivot;
+		}
+		for c in 0 .. b.columns() {
+			b[[0,c]] = b[[0,c]].clone() / pivots[0].clone();
+			for i in 1 .. size {
+				b[[i,c]] = (b[[i,c]].clone() - self.lower[i-1].clone() * b[[i-1,c]].clone()) / pivots[i].clone();
+			}
+			for i in (0 .. size-1).rev() {
+				b[[i,c]] = b[[i,c]].clone() - ratios[i].clone() * b[[i+1,c]].clone();
+			}
+		}
+		Ok(())
+	}
+}
+impl<T: Scalar> Tridiagonal<T> {
+	/// copy into a full dense matrix
+	pub fn to_dense(&self) -> DMatrix<T> {
+		DMatrix::<T>::field(self.shape(), |index|  self.get(index))
+	}
+	/// solve `A x = b` for each column of `b` with the Thomas algorithm
+	pub fn solve<B: Array<Element=T>>(&self, b: &Matrix<B>) -> Result<Matrix<Dynamic<T, Dyn, B::C>>, FactorizationError> {
+		let mut x = Matrix::from_field(b.shape(), |index|  b[index].clone());
+		self.solve_inplace(&mut x)?;
+		Ok(x)
+	}
+}
+
+
+impl<T,R,O>
+	MulTo<Matrix<R>, Matrix<O>> for Banded<T>
+where
+	T: Scalar,
+	R: Array<Element=T, C=O::C>,
+	O: ArrayMut<Element=T>,
+{
+	/// banded-dense product without dynamic allocation, only reading the band
+	fn mul_to<'o>(&self, right: &Matrix<R>, out: &'o mut Matrix<O>) -> &'o mut Matrix<O> {
+		assert_eq!(self.shape[1], right.rows());
+		assert_eq!(out.shape(), [self.shape[0], right.columns()]);
+		out.set_field(|[i, c]|  {
+			(i.saturating_sub(self.lower) .. self.shape[1].min(i + self.upper + 1))
+				.fold(T::zero(), |sum, k|  sum + self.data[self.position([i,k]).unwrap()].clone() * right[[k,c]].clone())
+		})
+	}
+}
+impl<T,R,O>
+	MulTo<Matrix<R>, Matrix<O>> for Tridiagonal<T>
+where
+	T: Scalar,
+	R: Array<Element=T, C=O::C>,
+	O: ArrayMut<Element=T>,
+{
+	/// tridiagonal-dense product without dynamic allocation
+	fn mul_to<'o>(&self, right: &Matrix<R>, out: &'o mut Matrix<O>) -> &'o mut Matrix<O> {
+		let size = self.size();
+		assert_eq!(size, right.rows());
+		assert_eq!(out.shape(), [size, right.columns()]);
+		out.set_field(|[i, c]|  {
+			let mut value = self.diagonal[i].clone() * right[[i,c]].clone();
+			if i > 0  {value = value + self.lower[i-1].clone() * right[[i-1,c]].clone()}
+			if i+1 < size  {value = value + self.upper[i].clone() * right[[i+1,c]].clone()}
+			value
+		})
+	}
+}
+macro_rules! banded_mul {
+	($name:ident) => {
+		impl<T,R>
+			Mul<&Matrix<R>> for &$name<T>
+		where
+			T: Scalar,
+			R: Array<Element=T>,
+		{
+			type Output = Matrix<Dynamic<T, Dyn, R::C>>;
+			fn mul(self, right: &Matrix<R>) -> Self::Output {
+				// initialized elements for the product to overwrite
+				let mut new = Matrix::from_field([self.shape()[0], right.columns()], |_|  T::zero());
+				self.mul_to(right, &mut new);
+				new
+			}
+		}
+		impl<'w,T,R>
+			MulWithWorkspace<'w, Matrix<R>> for $name<T>
+		where
+			T: Scalar,
+			R: Array<Element=T>,
+		{
+			type Output = ScratchMatrix<'w, T, Dyn, R::C>;
+			fn mul_with_workspace(&self, right: &Matrix<R>, workspace: &'w Workspace) -> Self::Output {
+				workspace::product(self, right, [self.shape()[0], right.columns()], workspace)
+			}
+		}
+		impl<T: Scalar> LinearOperator<T> for $name<T> {
+			fn shape(&self) -> [usize; 2]  {$name::shape(self)}
+			fn apply(&self, x: &VectorView<'_, T>, y: &mut VectorViewMut<'_, T>)  {self.mul_to(x, y);}
+		}
+	};
+}
+banded_mul!(Banded);
+banded_mul!(Tridiagonal);
+
+
+
+#[test]
+fn test_banded() {
+	let dense = DMatrix::<f64>::field([6, 6], |[i,j]|
+		if i == j {4. + i as f64}
+		else if i+2 >= j && j+1 >= i {(i + 2*j) as f64 * 0.5 - 1.}
+		else {0.});
+	let banded = Banded::from_dense(&dense, 1, 2);
+	assert_eq!(banded.bandwidths(), [1, 2]);
+	assert_eq!(banded.to_dense().as_slice(), dense.as_slice());
+	assert_eq!(banded.get([5,0]), 0.);
+	assert!(banded.contains([0,2]) && !banded.contains([0,3]));
+
+	let x = DMatrix::<f64>::field([6, 2], |[i,j]|  (i + 6*j) as f64 - 2.);
+	assert_eq!((&banded * &x).as_slice(), (&dense * &x).as_slice());
+
+	let b = &dense * &x;
+	let close = |a: &DMatrix<f64>, b: &DMatrix<f64>|  a.as_slice().unwrap().iter()
+		.zip(b.as_slice().unwrap())
+		.all(|(a, b)|  (a - b).abs() < 1e-9);
+	let lu = banded.lu().unwrap();
+	assert!(close(&lu.solve(&b), &x));
+	// zero diagonal needing pivoting
+	let mut pivoting = banded.clone();
+	*pivoting.get_mut([0,0]).unwrap() = 0.;
+	let dense = pivoting.to_dense();
+	let b = &dense * &x;
+	let lu = pivoting.lu().unwrap();
+	assert!(close(&lu.solve(&b), &x));
+	assert_ne!(lu.permutation().indices()[0], 0);
+	assert_eq!(Banded::<f64>::zeros([3,3], 1, 1).lu().err(), Some(FactorizationError::Singular(0)));
+
+	// 1D poisson problem
+	let size = 8;
+	let tridiagonal = Tridiagonal::try_from_diagonals(vec![-1.; size-1], vec![2.; size], vec![-1.; size-1]).unwrap();
+	assert!(Tridiagonal::try_from_diagonals(vec![-1.; size], vec![2.; size], vec![-1.; size-1]).is_none());
+	let dense = tridiagonal.to_dense();
+	assert_eq!(Tridiagonal::from_dense(&dense), tridiagonal);
+	assert_eq!(tridiagonal.to_banded().to_dense().as_slice(), dense.as_slice());
+	let x = DMatrix::<f64>::field([size, 2], |[i,j]|  (i*i + j) as f64);
+	let b = &tridiagonal * &x;
+	assert_eq!(b.as_slice(), (&dense * &x).as_slice());
+	assert!(close(&tridiagonal.solve(&b).unwrap(), &x));
+	assert_eq!(Tridiagonal::<f64>::zeros(size).solve(&b).err(), Some(FactorizationError::Singular(0)));
+}
@@ -0,0 +1,375 @@
+/*!
+	Batches of many small [Static] matrices, stored together to vectorize operations across the batch
+
+	Per-matrix operations on small matrices are too short to use SIMD units. In a [Batch], the operations loop over the matrices in their innermost loop, so with the [Layout::StructOfArrays] layout each step of a product or an elimination is a contiguous loop over the whole batch.
+	The [Layout::ArrayOfStructs] layout matches a slice of [Static] matrices, for interoperability with code storing matrices one by one.
+*/
+
+use crate::prelude::*;
+use crate::matrix::*;
+use crate::matrices::*;
+use crate::operators::*;
+
+use core::{
+	marker::PhantomData,
+	ops::Mul,
+	};
+use num_traits::Float;
+
+
+/// memory layout of a [Batch]
+#[derive(Copy, Clone, Debug, Default, Eq, PartialEq, Hash)]
+pub enum Layout {
+	/// element `[i,j]` of every matrix are contiguous, so operations vectorize across the batch
+	#[default]
+	StructOfArrays,
+	/// each matrix is contiguous in column-major order, as in a slice of [Static] matrices
+	ArrayOfStructs,
+}
+
+/**
+	many matrices of the same static shape, stored in a single buffer with the chosen [Layout]
+
+	`A` is the [Static] array of each matrix. Products, inverses and determinants are computed for all the matrices at once, and each matrix can still be accessed as a strided [View]
+*/
+#[derive(Clone)]
+pub struct Batch<A: Array> {
+	layout: Layout,
+	len: usize,
+	data: Vec<A::Element>,
+	array: PhantomData<A>,
+}
+
+/// position of the matrices elements in a buffer
+#[derive(Copy, Clone)]
+struct Lanes {
+	rows: usize,
+	/// step between successive elements of a matrix
+	element: usize,
+	/// step between successive matrices
+	item: usize,
+}
+impl Lanes {
+	/// lanes of a single column-major matrix, repeated for every item
+	fn single(rows: usize) -> Self  {Self {rows, element: 1, item: 0}}
+	/// offset of element `[i,j]` of the first matrix
+	fn at(&self, [i, j]: [usize; 2]) -> usize  {(i + self.rows*j) * self.element}
+}
+
+impl<T: Element, const R: usize, const C: usize> Batch<Static<T,R,C>> {
+	/// batch of `len` matrices given by the closure for each index in the batch
+	pub fn from_fn<F>(len: usize, layout: Layout, mut f: F) -> Self
+	where F: FnMut(usize) -> Matrix<Static<T,R,C>>
+	{
+		let mut data = Vec::<T>::with_capacity(len * R * C);
+		for k in 0 .. len {
+			data.extend(f(k).0.data.into_iter().flatten());
+		}
+		Self::from_lanes(Layout::ArrayOfStructs, len, data).with_layout(layout)
+	}
+	/// batch with the given layout and elements, or `None` if the vector has not exactly the length for `len` matrices
+	pub fn try_from_vec(len: usize, layout: Layout, data: Vec<T>) -> Option<Self> {
+		if data.len() != len * R * C  {return None}
+		Some(Self::from_lanes(layout, len, data))
+	}
+	fn from_lanes(layout: Layout, len: usize, data: Vec<T>) -> Self {
+		Self {layout, len, data, array: PhantomData}
+	}
+	fn lanes(&self) -> Lanes {
+		match self.layout {
+			Layout::StructOfArrays => Lanes {rows: R, element: self.len, item: 1},
+			Layout::ArrayOfStructs => Lanes {rows: R, element: 1, item: R*C},
+		}
+	}
+
+	/// number of matrices in the batch
+	pub fn len(&self) -> usize  {self.len}
+	/// true if there is no matrix in the batch
+	pub fn is_empty(&self) -> bool  {self.len == 0}
+	/// memory layout of the batch
+	pub fn layout(&self) -> Layout  {self.layout}
+	/// all the batch elements, in the batch layout
+	pub fn as_slice(&self) -> &[T]  {&self.data}
+	/// all the batch elements, in the batch layout, mutable
+	pub fn as_mut_slice(&mut self) -> &mut [T]  {&mut self.data}
+	/// retreive the batch elements, in the batch layout
+	pub fn into_vec(self) -> Vec<T>  {self.data}
+	/// element `[i,j]` of all the matrices, only contiguous with [Layout::StructOfArrays]
+	pub fn lane(&self, index: [usize; 2]) -> Option<&[T]> {
+		assert!(index[0] < R && index[1] < C);
+		let start = self.lanes().at(index);
+		match self.layout {
+			Layout::StructOfArrays => Some(&self.data[start .. start + self.len]),
+			Layout::ArrayOfStructs => None,
+		}
+	}
+
+	/// copy of the `k`th matrix
+	pub fn get(&self, k: usize) -> Matrix<Static<T,R,C>> {
+		let view = self.view(k);
+		Matrix(Static {data: core::array::from_fn(|j|  core::array::from_fn(|i|  view[[i,j]].clone()))})
+	}
+	/// overwrite the `k`th matrix
+	pub fn set<B: Array<Element=T>>(&mut self, k: usize, matrix: &Matrix<B>) {
+		assert_eq!(matrix.shape(), [R, C]);
+		self.view_mut(k).set_field(|index|  matrix[index].clone());
+	}
+	/// view on the `k`th matrix
+	pub fn view(&self, k: usize) -> Matrix<View<'_, T, Stat<R>, Stat<C>>> {
+		assert!(k < self.len);
+		let lanes = self.lanes();
+		Matrix(View {
+			shape: (Stat{}, Stat{}),
+			strides: (lanes.element as isize, (lanes.element * R) as isize),
+			data: unsafe {self.data.as_ptr().add(k * lanes.item)},
+			lifetime: PhantomData,
+		})
+	}
+	/// same as [Self::view] but mutable
+	pub fn view_mut(&mut self, k: usize) -> Matrix<ViewMut<'_, T, Stat<R>, Stat<C>>> {
+		assert!(k < self.len);
+		let lanes = self.lanes();
+		Matrix(ViewMut {
+			shape: (Stat{}, Stat{}),
+			strides: (lanes.element as isize, (lanes.element * R) as isize),
+			data: unsafe {self.data.as_mut_ptr().add(k * lanes.item)},
+			lifetime: PhantomData,
+		})
+	}
+	/// iterate over copies of the matrices
+	pub fn iter(&self) -> impl Iterator<Item=Matrix<Static<T,R,C>>> + '_ {
+		(0 .. self.len).map(|k|  self.get(k))
+	}
+	/// copy of this batch in the given layout
+	pub fn with_layout(self, layout: Layout) -> Self {
+		if layout == self.layout  {return self}
+		let (lanes, len) = (self.lanes(), self.len);
+		let data = match layout {
+			Layout::StructOfArrays => (0 .. R*C)
+				.flat_map(|e|  (0 .. len).map(move |k|  e*lanes.element + k*lanes.item))
+				.map(|i|  self.data[i].clone())
+				.collect(),
+			Layout::ArrayOfStructs => (0 .. len)
+				.flat_map(|k|  (0 .. R*C).map(move |e|  e*lanes.element + k*lanes.item))
+				.map(|i|  self.data[i].clone())
+				.collect(),
+		};
+		Self::from_lanes(layout, len, data)
+	}
+}
+impl<T: Scalar, const R: usize, const C: usize> Batch<Static<T,R,C>> {
+	/// batch of `len` zero matrices
+	pub fn zeros(len: usize, layout: Layout) -> Self {
+		Self::from_lanes(layout, len, vec![T::zero(); len * R * C])
+	}
+}
+impl<T: Element, const R: usize, const C: usize>
+	FromIterator<Matrix<Static<T,R,C>>> for Batch<Static<T,R,C>>
+{
+	/// batch in the default layout
+	fn from_iter<I: IntoIterator<Item=Matrix<Static<T,R,C>>>>(iter: I) -> Self {
+		let matrices: Vec<_> = iter.into_iter().collect();
+		let mut matrices = matrices.into_iter();
+		Self::from_fn(matrices.len(), Layout::default(), |_|  matrices.next().unwrap())
+	}
+}
+
+impl<T: Scalar + Float, const N: usize> Batch<Static<T,N,N>> {
+	/// determinant of every matrix
+	pub fn determinant(&self) -> Vec<T> {
+		let mut work = self.clone().with_layout(Layout::StructOfArrays).data;
+		let mut determinant = vec![T::one(); self.len];
+		eliminate::<T,N>(self.len, &mut work, None, &mut determinant);
+		determinant
+	}
+	/// inverse of every matrix, or the index of the first singular matrix
+	pub fn inverse(&self) -> Result<Self, usize> {
+		let len = self.len;
+		let mut work = self.clone().with_layout(Layout::StructOfArrays).data;
+		let mut inverse = Self::zeros(len, Layout::StructOfArrays);
+		for i in 0 .. N {
+			let start = (i + N*i) * len;
+			inverse.data[start .. start + len].fill(T::one());
+		}
+		let mut determinant = vec![T::one(); len];
+		eliminate::<T,N>(len, &mut work, Some(&mut inverse.data), &mut determinant);
+		if let Some(k) = determinant.iter().position(|d|  d.is_zero())
+			{return Err(k)}
+		Ok(inverse.with_layout(self.layout))
+	}
+}
+
+/**
+	Gauss-Jordan elimination with partial pivoting of a batch of square matrices in the struct of arrays layout
+
+	Rows are swapped per matrix, while the row updates loop over the whole batch. The determinants are multiplied by the pivots, and the right hand side is turned into `A^-1 rhs` if given
+*/
+fn eliminate<T: Scalar + Float, const N: usize>(len: usize, work: &mut [T], mut rhs: Option<&mut [T]>, determinant: &mut [T]) {
+	let at = |[i, j]: [usize; 2]|  (i + N*j) * len;
+	let columns = rhs.as_ref().map_or(0, |rhs|  rhs.len() / (N * len));
+	let mut factors = vec![T::zero(); len];
+	let mut inverses = vec![T::zero(); len];
+	for p in 0 .. N {
+		for k in 0 .. len {
+			let pivot = (p .. N).max_by(|&a, &b|
+					work[at([a,p]) + k].abs().partial_cmp(&work[at([b,p]) + k].abs())
+					.unwrap_or(core::cmp::Ordering::Equal))
+				.unwrap();
+			if pivot != p {
+				for j in 0 .. N  {work.swap(at([p,j]) + k, at([pivot,j]) + k)}
+				if let Some(rhs) = rhs.as_mut() {
+					for j in 0 .. columns  {rhs.swap(at([p,j]) + k, at([pivot,j]) + k)}
+				}
+				determinant[k] = -determinant[k];
+			}
+			let diagonal = work[at([p,p]) + k];
+			determinant[k] = determinant[k] * diagonal;
+			inverses[k] = if diagonal.is_zero() {T::zero()} else {T::one() / diagonal};
+		}
+		// without right hand side, only the rows below the pivot are needed for the determinant
+		let rows = if rhs.is_some() {0} else {p+1};
+		for i in (rows .. N).filter(|&i|  i != p) {
+			for k in 0 .. len  {factors[k] = work[at([i,p]) + k] * inverses[k]}
+			for j in p .. N {
+				let (target, source) = (at([i,j]), at([p,j]));
+				for k in 0 .. len  {work[target + k] = work[target + k] - factors[k] * work[source + k]}
+			}
+			if let Some(rhs) = rhs.as_mut() {
+				for j in 0 .. columns {
+					let (target, source) = (at([i,j]), at([p,j]));
+					for k in 0 .. len  {rhs[target + k] = rhs[target + k] - factors[k] * rhs[source + k]}
+				}
+			}
+		}
+	}
+	if let Some(rhs) = rhs {
+		for i in 0 .. N {
+			let diagonal = at([i,i]);
+			for k in 0 .. len  {inverses[k] = T::one() / work[diagonal + k]}
+			for j in 0 .. columns {
+				let target = at([i,j]);
+				for k in 0 .. len  {rhs[target + k] = rhs[target + k] * inverses[k]}
+			}
+		}
+	}
+}
+
+/// product of every matrix of `left` with every matrix of `right`, looping over the batch innermost
+fn product<T: Scalar>(
+	[rows, inner, columns]: [usize; 3],
+	len: usize,
+	(left, ll): (&[T], Lanes),
+	(right, rl): (&[T], Lanes),
+	(out, ol): (&mut [T], Lanes),
+) {
+	for j in 0 .. columns {
+		for i in 0 .. rows {
+			let o = ol.at([i,j]);
+			for k in 0 .. len  {out[o + k*ol.item] = T::zero()}
+			for d in 0 .. inner {
+				let (l, r) = (ll.at([i,d]), rl.at([d,j]));
+				for k in 0 .. len {
+					out[o + k*ol.item] = out[o + k*ol.item].clone()
+						+ left[l + k*ll.item].clone() * right[r + k*rl.item].clone();
+				}
+			}
+		}
+	}
+}
+
+
+impl<T: Scalar, const R: usize, const K: usize, const C: usize>
+	MulTo<Batch<Static<T,K,C>>, Batch<Static<T,R,C>>> for Batch<Static<T,R,K>>
+{
+	/// product of each matrix of this batch with the matrix at the same index in the right batch
+	fn mul_to<'o>(&self, right: &Batch<Static<T,K,C>>, out: &'o mut Batch<Static<T,R,C>>) -> &'o mut Batch<Static<T,R,C>> {
+		assert_eq!(self.len, right.len);
+		assert_eq!(self.len, out.len);
+		let lanes = out.lanes();
+		product([R, K, C], self.len, (&self.data, self.lanes()), (&right.data, right.lanes()), (&mut out.data, lanes));
+		out
+	}
+}
+impl<T: Scalar, const R: usize, const K: usize, const C: usize>
+	MulTo<Batch<Static<T,K,C>>, Batch<Static<T,R,C>>> for Matrix<Static<T,R,K>>
+{
+	/// apply this matrix to every matrix of the batch, typically transforming a batch of vectors
+	fn mul_to<'o>(&self, right: &Batch<Static<T,K,C>>, out: &'o mut Batch<Static<T,R,C>>) -> &'o mut Batch<Static<T,R,C>> {
+		assert_eq!(right.len, out.len);
+		let lanes = out.lanes();
+		let left = self.as_slice().unwrap();
+		product([R, K, C], right.len, (left, Lanes::single(R)), (&right.data, right.lanes()), (&mut out.data, lanes));
+		out
+	}
+}
+impl<T: Scalar, const R: usize, const K: usize, const C: usize>
+	Mul<&Batch<Static<T,K,C>>> for &Batch<Static<T,R,K>>
+{
+	type Output = Batch<Static<T,R,C>>;
+	/// product of the matrices at the same index, in the layout of this batch
+	fn mul(self, right: &Batch<Static<T,K,C>>) -> Self::Output {
+		let mut new = Batch::zeros(self.len, self.layout);
+		self.mul_to(right, &mut new);
+		new
+	}
+}
+impl<T: Scalar, const R: usize, const K: usize, const C: usize>
+	Mul<&Batch<Static<T,K,C>>> for &Matrix<Static<T,R,K>>
+{
+	type Output = Batch<Static<T,R,C>>;
+	/// apply this matrix to every matrix of the batch, in the layout of the batch
+	fn mul(self, right: &Batch<Static<T,K,C>>) -> Self::Output {
+		let mut new = Batch::zeros(right.len, right.layout);
+		self.mul_to(right, &mut new);
+		new
+	}
+}
+
+
+
+#[test]
+fn test_batch() {
+	use crate::glm::*;
+
+	let transforms = |k: usize|  DMat4::field(|[i,j]|
+		if i == j {2. + k as f64}
+		else {((i + 2*j + k) % 5) as f64 - 2.});
+	let vectors = |k: usize|  DVec4::from([k as f64, 1., -2., 0.5]);
+	let close = |a: &[f64], b: &[f64]|  a.iter().zip(b).all(|(a, b)|  (a - b).abs() < 1e-9);
+
+	for layout in [Layout::StructOfArrays, Layout::ArrayOfStructs] {
+		let matrices = Batch::from_fn(7, layout, transforms);
+		let points = Batch::from_fn(7, layout, vectors);
+		assert_eq!(matrices.len(), 7);
+		assert_eq!(matrices.view(3)[[1,2]], transforms(3)[[1,2]]);
+		assert_eq!(matrices.lane([0,0]).is_some(), layout == Layout::StructOfArrays);
+
+		// products against per-matrix products
+		let transformed = &matrices * &points;
+		let composed = &matrices * &matrices;
+		for k in 0 .. 7 {
+			assert_eq!(transformed.get(k).as_slice(), (&transforms(k) * &vectors(k)).as_slice());
+			assert_eq!(composed.get(k).as_slice(), (&transforms(k) * &transforms(k)).as_slice());
+		}
+		let transform = transforms(1);
+		let moved = &transform * &points;
+		assert_eq!(moved.layout(), layout);
+		assert_eq!(moved.get(5).as_slice(), (&transform * &vectors(5)).as_slice());
+
+		// inverse and determinant
+		let inverse = matrices.inverse().unwrap();
+		let identity = &inverse * &matrices;
+		for (k, determinant) in matrices.determinant().into_iter().enumerate() {
+			assert!(close(identity.get(k).as_slice().unwrap(), DMat4::identity().as_slice().unwrap()));
+			assert!((determinant * inverse.determinant()[k] - 1.).abs() < 1e-9);
+		}
+	}
+	let diagonal = Batch::<Static<f64,2,2>>::from_iter([
+		DMat2::from([[2., 0.], [0., 3.]]),
+		DMat2::from([[0., 1.], [1., 0.]]),
+		DMat2::from([[1., 2.], [2., 4.]]),
+		]);
+	assert_eq!(diagonal.determinant(), [6., -1., 0.]);
+	assert_eq!(diagonal.inverse().err(), Some(2));
+	assert_eq!(diagonal.clone().with_layout(Layout::ArrayOfStructs).as_slice(), [2., 0., 0., 3.,  0., 1., 1., 0.,  1., 2., 2., 4.]);
+}
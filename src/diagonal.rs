@@ -0,0 +1,201 @@
+/*!
+	Diagonal matrices, storing only their diagonal as a vector
+
+	Products with dense matrices scale their rows or columns in `O(n m)` without building the dense diagonal matrix
+*/
+
+use crate::prelude::*;
+use crate::matrix::*;
+use crate::matrices::*;
+use crate::operators::*;
+use crate::workspace::{self, Workspace, ScratchMatrix, MulWithWorkspace};
+
+use core::ops::Mul;
+use num_traits::{Zero, One};
+
+
+/// square matrix with nonzero elements only on its diagonal, stored as a column vector
+#[derive(Clone)]
+pub struct DiagonalMatrix<A: Array> {
+	diagonal: Matrix<A>,
+}
+impl<A: Array> DiagonalMatrix<A> {
+	/// diagonal matrix with the elements of the given column vector on its diagonal
+	pub fn new(diagonal: Matrix<A>) -> Self {
+		assert_eq!(diagonal.columns(), 1, "diagonal must be a column vector");
+		Self {diagonal}
+	}
+	/// number of rows and columns
+	pub fn size(&self) -> usize  {self.diagonal.rows()}
+	/// shape of the matrix
+	pub fn shape(&self) -> [usize; 2]  {[self.size(), self.size()]}
+	/// the diagonal as a column vector
+	pub fn diagonal(&self) -> &Matrix<A>  {&self.diagonal}
+	/// the diagonal as a column vector, mutable
+	pub fn diagonal_mut(&mut self) -> &mut Matrix<A>  {&mut self.diagonal}
+	/// retreive the diagonal vector
+	pub fn into_inner(self) -> Matrix<A>  {self.diagonal}
+	/// the transpose of a diagonal matrix is itself, this is a view of the same diagonal
+	pub fn transpose(&self) -> DiagonalMatrix<View<'_, A::Element, A::R, A::C>> {
+		DiagonalMatrix {diagonal: self.diagonal.view()}
+	}
+}
+impl<A: Array> DiagonalMatrix<A>
+where A::Element: Scalar
+{
+	/// element at the given index, zero outside the diagonal
+	pub fn get(&self, [i, j]: [usize; 2]) -> A::Element {
+		assert!(i < self.size() && j < self.size());
+		if i == j  {self.diagonal[[i,0]].clone()}
+		else {A::Element::zero()}
+	}
+	/// product of the diagonal elements
+	pub fn determinant(&self) -> A::Element {
+		(0 .. self.size()).fold(A::Element::one(), |product, i|  product * self.diagonal[[i,0]].clone())
+	}
+	/// copy into a full dense matrix
+	pub fn to_dense(&self) -> Matrix<Dynamic<A::Element, A::R, A::R>> {
+		Matrix::from_field(self.shape(), |index|  self.get(index))
+	}
+	/// multiply the rows of the given matrix in place, computing `self * matrix`
+	pub fn scale_rows<B: ArrayMut<Element=A::Element>>(&self, matrix: &mut Matrix<B>) {
+		assert_eq!(matrix.rows(), self.size());
+		matrix.update_field(|[i,_], element|  *element = self.diagonal[[i,0]].clone() * element.clone());
+	}
+	/// multiply the columns of the given matrix in place, computing `matrix * self`
+	pub fn scale_columns<B: ArrayMut<Element=A::Element>>(&self, matrix: &mut Matrix<B>) {
+		assert_eq!(matrix.columns(), self.size());
+		matrix.update_field(|[_,j], element|  *element = element.clone() * self.diagonal[[j,0]].clone());
+	}
+}
+impl<A: ArrayMut> DiagonalMatrix<A>
+where A::Element: Scalar
+{
+	/// invert in place, return `false` and leave the matrix unchanged if an element is zero
+	pub fn invert(&mut self) -> bool {
+		if (0 .. self.size()).any(|i|  self.diagonal[[i,0]].is_zero())  {return false}
+		self.diagonal.update_field(|_, element|  *element = A::Element::one() / element.clone());
+		true
+	}
+}
+impl<A, R: Dim, C: Dim> DiagonalMatrix<A>
+where
+	A: Array<R=R, C=C> + Compatible<R, C>,
+	A::Element: Scalar,
+{
+	/// inverse diagonal matrix, or `None` if an element is zero
+	pub fn inverse(&self) -> Option<DiagonalMatrix<A::Owned>> {
+		let mut new = DiagonalMatrix {diagonal: self.diagonal.owned()};
+		if new.invert() {Some(new)} else {None}
+	}
+}
+
+
+impl<A,R,O>
+	MulTo<Matrix<R>, Matrix<O>> for DiagonalMatrix<A>
+where
+	A: Array<Element=O::Element>,
+	R: Array<Element=O::Element>,
+	O: ArrayMut,
+	O::Element: Scalar,
+{
+	/// scale the rows of the right matrix
+	fn mul_to<'o>(&self, right: &Matrix<R>, out: &'o mut Matrix<O>) -> &'o mut Matrix<O> {
+		assert_eq!(right.rows(), self.size());
+		assert_eq!(out.shape(), right.shape());
+		out.set_field(|[i, j]|  self.diagonal[[i,0]].clone() * right[[i,j]].clone())
+	}
+}
+impl<L,A,O>
+	MulTo<DiagonalMatrix<A>, Matrix<O>> for Matrix<L>
+where
+	L: Array<Element=O::Element>,
+	A: Array<Element=O::Element>,
+	O: ArrayMut,
+	O::Element: Scalar,
+{
+	/// scale the columns of the left matrix
+	fn mul_to<'o>(&self, right: &DiagonalMatrix<A>, out: &'o mut Matrix<O>) -> &'o mut Matrix<O> {
+		assert_eq!(self.columns(), right.size());
+		assert_eq!(out.shape(), self.shape());
+		out.set_field(|[i, j]|  self[[i,j]].clone() * right.diagonal[[j,0]].clone())
+	}
+}
+impl<A,R,RR,RC>
+	Mul<&Matrix<R>> for &DiagonalMatrix<A>
+where
+	A: Array<Element=R::Element>,
+	R: Array<R=RR, C=RC> + Compatible<RR, RC>,
+	R::Element: Scalar,
+{
+	type Output = Matrix<R::Owned>;
+	/// scale the rows of the right matrix
+	fn mul(self, right: &Matrix<R>) -> Self::Output {
+		assert_eq!(right.rows(), self.size());
+		Matrix::from_field(right.shape(), |[i, j]|  self.diagonal[[i,0]].clone() * right[[i,j]].clone())
+	}
+}
+impl<L,A,LR,LC>
+	Mul<&DiagonalMatrix<A>> for &Matrix<L>
+where
+	L: Array<R=LR, C=LC> + Compatible<LR, LC>,
+	A: Array<Element=L::Element>,
+	L::Element: Scalar,
+{
+	type Output = Matrix<L::Owned>;
+	/// scale the columns of the left matrix
+	fn mul(self, right: &DiagonalMatrix<A>) -> Self::Output {
+		assert_eq!(self.columns(), right.size());
+		Matrix::from_field(self.shape(), |[i, j]|  self[[i,j]].clone() * right.diagonal[[j,0]].clone())
+	}
+}
+impl<'w,A,R>
+	MulWithWorkspace<'w, Matrix<R>> for DiagonalMatrix<A>
+where
+	A: Array<Element=R::Element>,
+	R: Array,
+	R::Element: Scalar,
+{
+	type Output = ScratchMatrix<'w, R::Element, R::R, R::C>;
+	/// scale the rows of the right matrix
+	fn mul_with_workspace(&self, right: &Matrix<R>, workspace: &'w Workspace) -> Self::Output {
+		workspace::product(self, right, right.shape(), workspace)
+	}
+}
+impl<'w,L,A>
+	MulWithWorkspace<'w, DiagonalMatrix<A>> for Matrix<L>
+where
+	L: Array,
+	A: Array<Element=L::Element>,
+	L::Element: Scalar,
+{
+	type Output = ScratchMatrix<'w, L::Element, L::R, L::C>;
+	/// scale the columns of the left matrix
+	fn mul_with_workspace(&self, right: &DiagonalMatrix<A>, workspace: &'w Workspace) -> Self::Output {
+		workspace::product(self, right, self.shape(), workspace)
+	}
+}
+
+
+
+#[test]
+fn test_diagonal() {
+	let diagonal = DiagonalMatrix::new(SVector::<f64,3>::from([1., 2., 4.]));
+	assert_eq!(diagonal.determinant(), 8.);
+	assert_eq!(diagonal.to_dense().as_slice(), Some(&[1.,0.,0.,  0.,2.,0.,  0.,0.,4.][..]));
+
+	let matrix = SMatrix::<f64,3,3>::field(|[i,j]|  (i + 3*j) as f64);
+	let dense = diagonal.to_dense();
+	assert_eq!((&diagonal * &matrix).as_slice(), (&dense * &matrix).as_slice());
+	assert_eq!((&matrix * &diagonal).as_slice(), (&matrix * &dense).as_slice());
+	let mut scaled = matrix.clone();
+	diagonal.scale_rows(&mut scaled);
+	assert_eq!(scaled.as_slice(), (&dense * &matrix).as_slice());
+	let mut scaled = matrix.clone();
+	diagonal.transpose().scale_columns(&mut scaled.view_mut());
+	assert_eq!(scaled.as_slice(), (&matrix * &dense).as_slice());
+
+	let inverse = diagonal.inverse().unwrap();
+	assert_eq!(inverse.diagonal().as_slice(), Some(&[1., 0.5, 0.25][..]));
+	assert!(DiagonalMatrix::new(SVector::<f64,2>::from([1., 0.])).inverse().is_none());
+}
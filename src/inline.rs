@@ -0,0 +1,170 @@
+/*!
+	Owned storage with a runtime shape but a compile-time capacity, allocated inline without heap
+
+	[Inline] holds up to `CAP` elements in an array, like [Static](crate::Static) does, but its dimensions can be [Dyn] as for [Dynamic](crate::Dynamic). This suits variable sized matrices with a known bound, for instance jacobians of a robot with at most 6 joints. Creating a matrix bigger than the capacity panics, operations giving an [Inline] matrix of the same capacity
+
+	The capacity must then fit every result, not only the operands: with `CAP = 72` a 6x12 jacobian `J` and its transpose stored inline fit, but their product `Jᵀ J` has 144 elements and panics. Either choose `CAP` as the biggest result, here `max(R·C, C·C)`, or write bigger results into a matrix of their own capacity with [MulTo](crate::operators::MulTo) and the other `*To` operations. Operations on views, like `transpose()`, give heap allocated results instead
+*/
+
+use crate::prelude::*;
+use crate::matrix::*;
+
+use core::iter::zip;
+use core::mem::MaybeUninit;
+
+
+/// matrix of runtime shape with inline storage of `CAP` elements, operators results have the same capacity so it must fit them as well
+pub type IMatrix<T, const CAP: usize, R=Dyn, C=Dyn> = Matrix<Inline<T,CAP,R,C>>;
+
+
+/// column-major owned array of at most `CAP` elements allocated inline, sizing can be dynamic or static
+pub struct Inline<T: Element, const CAP: usize, R: Dim=Dyn, C: Dim=Dyn> {
+	shape: (R, C),
+	/// only the elements in the shape are initialized
+	data: [MaybeUninit<T>; CAP],
+}
+impl<T: Element, const CAP: usize, R: Dim, C: Dim> Inline<T,CAP,R,C> {
+	/// number of initialized elements
+	fn size(&self) -> usize  {checked_size::<CAP>(self.shape.0.value(), self.shape.1.value()).expect("inline shape exceeds the capacity")}
+}
+/// number of elements in the shape, or `None` if it exceeds the capacity
+fn checked_size<const CAP: usize>(rows: usize, columns: usize) -> Option<usize> {
+	rows.checked_mul(columns).filter(|&size|  size <= CAP)
+}
+impl<T: Element, const CAP: usize, R: Dim, C: Dim>
+	Clone for Inline<T,CAP,R,C>
+{
+	fn clone(&self) -> Self {
+		let mut data = [const {MaybeUninit::uninit()}; CAP];
+		for (dst, src) in zip(&mut data, &self.data[.. self.size()]) {
+			// safety: elements in the shape are initialized
+			dst.write(unsafe {src.assume_init_ref()}.clone());
+		}
+		Self {shape: self.shape, data}
+	}
+}
+impl<T: Element, const CAP: usize, R: Dim, C: Dim>
+	Drop for Inline<T,CAP,R,C>
+{
+	fn drop(&mut self) {
+		let size = self.size();
+		for element in &mut self.data[.. size] {
+			// safety: elements in the shape are initialized
+			unsafe {element.assume_init_drop()}
+		}
+	}
+}
+impl<T: Element, const CAP: usize, R: Dim, C: Dim>
+	Array for Inline<T,CAP,R,C>
+{
+	type Element = T;
+	type R = R;
+	type C = C;
+	fn shape(&self) -> [usize; 2]    {[self.shape.0.value(), self.shape.1.value()]}
+	fn strides(&self) -> [isize; 2]  {[1, self.shape.0.value() as isize]}
+	fn as_ptr(&self) -> *const T        {self.data.as_ptr() as _}
+}
+impl<T: Element, const CAP: usize, R: Dim, C: Dim>
+	ArrayMut for Inline<T,CAP,R,C>
+{
+	fn as_mut_ptr(&mut self) -> *mut T  {self.data.as_mut_ptr() as _}
+}
+impl<T: Element, const CAP: usize, R: Dim, C: Dim>
+	ArrayOwned for Inline<T,CAP,R,C>
+{
+	type Uninit = InlineUninit<T,CAP,R,C>;
+	/// panics if the shape exceeds the capacity
+	fn uninit(shape: (R, C)) -> Self::Uninit {
+		let [rows, columns] = [shape.0.value(), shape.1.value()];
+		assert!(checked_size::<CAP>(rows, columns).is_some(), "{}x{} elements exceed the inline capacity of {}", rows, columns, CAP);
+		InlineUninit {
+			shape,
+			data: [const {MaybeUninit::uninit()}; CAP],
+		}
+	}
+}
+/// uninitialized counterpart of [Inline], as returned by [ArrayOwned::uninit]
+pub struct InlineUninit<T, const CAP: usize, R: Dim=Dyn, C: Dim=Dyn> {
+	shape: (R, C),
+	data: [MaybeUninit<T>; CAP],
+}
+impl<T: Element, const CAP: usize, R: Dim, C: Dim>
+	ArrayUninit<Inline<T,CAP,R,C>> for InlineUninit<T,CAP,R,C>
+{
+	fn strides(&self) -> [isize; 2]  {[1, self.shape.0.value() as isize]}
+	fn as_mut_ptr(&mut self) -> *mut MaybeUninit<T>  {self.data.as_mut_ptr()}
+	unsafe fn assume_init(self) -> Inline<T,CAP,R,C>  {Inline {shape: self.shape, data: self.data}}
+}
+impl<T: Element, const CAP: usize, R1: Dim, C1: Dim, R2: Dim, C2: Dim>
+	Compatible<R2,C2> for Inline<T,CAP,R1,C1>
+{
+	type Owned = Inline<T,CAP,R2,C2>;
+}
+impl<T: Element, const CAP: usize, R: Dim, C: Dim>
+	Matrix<Inline<T,CAP,R,C>>
+{
+	/// maximum number of elements this matrix can hold
+	pub fn capacity(&self) -> usize  {CAP}
+	/// copy the given column-major elements, or `None` if the shape doesn't fit the dimensionality, the capacity or the slice
+	pub fn try_from_slice(shape: [usize; 2], src: &[T]) -> Option<Self> {
+		let size = checked_size::<CAP>(shape[0], shape[1])?;
+		if size > src.len()  {return None}
+		R::check(shape[0])?;
+		C::check(shape[1])?;
+		Some(Matrix::from_field(shape, |[i,j]|  src[i + j*shape[0]].clone()))
+	}
+}
+/// constructors, as for [Dynamic](crate::Dynamic)
+impl<T: Scalar, const CAP: usize>
+	Matrix<Inline<T, CAP, Dyn, Dyn>>
+{
+	pub fn empty(shape: [usize; 2]) -> Self where T: Default  {Matrix::new(shape)}
+	pub fn zeros(shape: [usize; 2]) -> Self         {Matrix::from_field(shape, |_|  T::zero())}
+	pub fn ones(shape: [usize; 2]) -> Self          {Matrix::from_field(shape, |_|  T::one())}
+	pub fn identity(shape: [usize; 2]) -> Self      {Matrix::from_field(shape, |[i,j]|  if i==j {T::one()} else {T::zero()})}
+	pub fn full(shape: [usize; 2], value: T) -> Self  {Matrix::from_field(shape, |_|  value.clone())}
+	pub fn field<F>(shape: [usize; 2], field: F) -> Self
+	where F: FnMut([usize; 2]) -> T
+		{Matrix::from_field(shape, field)}
+}
+
+
+
+#[test]
+fn test_inline() {
+	use crate::matrices::*;
+
+	// a 6x12 jacobian bound, used with smaller runtime shapes
+	let jacobian = IMatrix::<f64, 72>::field([3, 5], |[i,j]|  (i + 3*j) as f64);
+	assert_eq!(jacobian.shape(), [3, 5]);
+	assert_eq!(jacobian.strides(), [1, 3]);
+	assert_eq!(jacobian.capacity(), 72);
+	assert_eq!(jacobian[[2,4]], 14.);
+	// products stay inline
+	let gram: IMatrix<f64, 72> = &jacobian * &jacobian.transpose();
+	let dense = DMatrix::<f64>::field([3, 5], |[i,j]|  (i + 3*j) as f64);
+	assert_eq!(gram.as_slice(), (&dense * &dense.transpose()).as_slice());
+	// results bigger than the operands need their own capacity
+	use crate::operators::MulTo;
+	let full = IMatrix::<f64, 72>::field([6, 12], |[i,j]|  (i + 6*j) as f64);
+	let transposed = IMatrix::<f64, 72>::field([12, 6], |[i,j]|  full[[j,i]]);
+	assert!(std::panic::catch_unwind(||  &transposed * &full).is_err());
+	let mut normal = IMatrix::<f64, 144>::zeros([12, 12]);
+	transposed.mul_to(&full, &mut normal);
+	let dense_full = DMatrix::<f64>::field([6, 12], |[i,j]|  (i + 6*j) as f64);
+	assert_eq!(normal.as_slice(), (&dense_full.transpose() * &dense_full).as_slice());
+	let copy = jacobian.clone();
+	assert_eq!(copy.as_slice(), dense.as_slice());
+	// elements beyond the shape are never initialized nor dropped
+	let names = IMatrix::<String, 8, Dyn, Stat<1>>::try_from_slice([2, 1], &["a".to_owned(), "b".to_owned()]).unwrap();
+	assert_eq!(names.clone()[[1,0]], "b");
+
+	let vector = IMatrix::<f32, 4, Dyn, Stat<1>>::try_from_slice([3, 1], &[1., 2., 3.]).unwrap();
+	assert_eq!(vector[[2,0]], 3.);
+	assert!(IMatrix::<f32, 4, Dyn, Stat<1>>::try_from_slice([5, 1], &[0.; 5]).is_none());
+	assert!(std::panic::catch_unwind(||  IMatrix::<f32, 4>::zeros([3, 2])).is_err());
+	// shapes whose number of elements overflows
+	let half = 1 << (usize::BITS - 1);
+	assert!(IMatrix::<f32, 4>::try_from_slice([half, 2], &[]).is_none());
+	assert!(std::panic::catch_unwind(||  IMatrix::<f32, 4>::zeros([half, 2])).is_err());
+}
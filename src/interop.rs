@@ -0,0 +1,16 @@
+/*!
+	Bridges with other array crates, each enabled by the feature of the same name
+
+	Foreign arrays implement [Array](crate::Array) so they can be wrapped in a [Matrix](crate::Matrix) without copy, and any matrix can be viewed as a foreign array.
+*/
+
+#[cfg(feature = "ndarray")]
+pub mod ndarray;
+#[cfg(feature = "nalgebra")]
+pub mod nalgebra;
+#[cfg(feature = "mint")]
+pub mod mint;
+#[cfg(feature = "bytemuck")]
+pub mod bytemuck;
+#[cfg(feature = "glam")]
+pub mod glam;
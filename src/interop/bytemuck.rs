@@ -0,0 +1,52 @@
+/*!
+	Safe byte casting of static matrices with [bytemuck](https://docs.rs/bytemuck), enabled by the `bytemuck` feature
+
+	[Static] arrays of [Pod] elements are themselves [Pod], so matrices like [Mat4](crate::glm::Mat4) can be uploaded to GPU buffers as bytes in column-major order without `unsafe` in user code.
+*/
+
+use crate::prelude::*;
+use crate::matrix::*;
+use crate::matrices::*;
+
+use ::bytemuck::{Pod, Zeroable};
+
+
+// safety: Static is a transparent wrapper around an array of its elements, with no padding
+unsafe impl<T: Element + Zeroable, const R: usize, const C: usize> Zeroable for Static<T,R,C> {}
+unsafe impl<T: Element + Pod, const R: usize, const C: usize> Pod for Static<T,R,C> {}
+
+impl<T: Element + Pod, const R: usize, const C: usize> Matrix<Static<T,R,C>> {
+	/// bytes of the matrix elements, in column-major order
+	pub fn as_bytes(&self) -> &[u8]  {::bytemuck::bytes_of(&self.0)}
+	/// same as [Self::as_bytes] but mutable
+	pub fn as_bytes_mut(&mut self) -> &mut [u8]  {::bytemuck::bytes_of_mut(&mut self.0)}
+	/// matrix read from bytes in column-major order, or `None` if the number of bytes is not the matrix size. The bytes need no alignment
+	pub fn try_from_bytes(bytes: &[u8]) -> Option<Self> {
+		::bytemuck::try_pod_read_unaligned(bytes).ok().map(Matrix)
+	}
+	/// bytes of a slice of matrices, one after the other
+	pub fn slice_as_bytes(matrices: &[Self]) -> &[u8] {
+		// safety: Matrix is a transparent wrapper around its array
+		let arrays = unsafe {core::slice::from_raw_parts(matrices.as_ptr() as *const Static<T,R,C>, matrices.len())};
+		::bytemuck::cast_slice(arrays)
+	}
+}
+
+
+
+#[test]
+fn test_bytemuck() {
+	use crate::glm::*;
+
+	let mut matrix = Mat2::from([[1., 2.], [3., 4.]]);
+	assert_eq!(matrix.as_bytes().len(), 16);
+	assert_eq!(&matrix.as_bytes()[4 .. 8], 2f32.to_ne_bytes());
+	matrix.as_bytes_mut()[.. 4].copy_from_slice(&5f32.to_ne_bytes());
+	assert_eq!(matrix[[0,0]], 5.);
+	let bytes = FMat2::slice_as_bytes(&[matrix.clone(), FMat2::identity()]).to_vec();
+	assert_eq!(bytes.len(), 32);
+	assert_eq!(FMat2::try_from_bytes(&bytes[16 ..]).unwrap().as_slice(), FMat2::identity().as_slice());
+	assert!(FMat2::try_from_bytes(&bytes[1 ..]).is_none());
+	let zero: Static<f32,4,4> = ::bytemuck::Zeroable::zeroed();
+	assert_eq!(Matrix(zero).as_slice(), Some(&[0.; 16][..]));
+}
@@ -0,0 +1,60 @@
+/*!
+	Conversions between the [glm](crate::glm) vectors and matrices and [glam](https://docs.rs/glam) types, enabled by the `glam` feature
+
+	Both store matrices in column-major order so conversions only move the columns arrays.
+*/
+
+use crate::matrix::*;
+use crate::matrices::*;
+
+
+macro_rules! glam_vector {
+	($glam:ident, $element:ty, $size:literal) => {
+		impl From<::glam::$glam> for Matrix<Static<$element, $size, 1>> {
+			fn from(src: ::glam::$glam) -> Self  {Matrix(Static {data: [src.to_array()]})}
+		}
+		impl From<Matrix<Static<$element, $size, 1>>> for ::glam::$glam {
+			fn from(src: Matrix<Static<$element, $size, 1>>) -> Self  {::glam::$glam::from_array(src.0.data[0])}
+		}
+	}
+}
+glam_vector!(Vec2, f32, 2);
+glam_vector!(Vec3, f32, 3);
+glam_vector!(Vec4, f32, 4);
+glam_vector!(DVec2, f64, 2);
+glam_vector!(DVec3, f64, 3);
+glam_vector!(DVec4, f64, 4);
+
+macro_rules! glam_matrix {
+	($glam:ident, $element:ty, $size:literal) => {
+		impl From<::glam::$glam> for Matrix<Static<$element, $size, $size>> {
+			fn from(src: ::glam::$glam) -> Self  {Matrix(Static {data: src.to_cols_array_2d()})}
+		}
+		impl From<Matrix<Static<$element, $size, $size>>> for ::glam::$glam {
+			fn from(src: Matrix<Static<$element, $size, $size>>) -> Self  {::glam::$glam::from_cols_array_2d(&src.0.data)}
+		}
+	}
+}
+glam_matrix!(Mat2, f32, 2);
+glam_matrix!(Mat3, f32, 3);
+glam_matrix!(Mat4, f32, 4);
+glam_matrix!(DMat2, f64, 2);
+glam_matrix!(DMat3, f64, 3);
+glam_matrix!(DMat4, f64, 4);
+
+
+
+#[test]
+fn test_glam() {
+	use crate::glm::*;
+
+	let translation = ::glam::Mat4::from_translation(::glam::Vec3::new(1., 2., 3.));
+	let matrix = Mat4::from(translation);
+	assert_eq!(matrix[[0,3]], 1.);
+	assert_eq!(matrix[[2,3]], 3.);
+	let point = Vec4::from(::glam::Vec4::new(1., 1., 1., 1.));
+	let moved = &matrix * &point;
+	assert_eq!(::glam::Vec4::from(moved), translation * ::glam::Vec4::ONE);
+	assert_eq!(::glam::Mat4::from(matrix), translation);
+	assert_eq!(::glam::DVec2::from(DVec2::from([1., 2.])), ::glam::DVec2::new(1., 2.));
+}
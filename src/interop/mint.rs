@@ -0,0 +1,67 @@
+/*!
+	Conversions between the [glm](crate::glm) vectors and matrices and [mint](https://docs.rs/mint) types, enabled by the `mint` feature
+
+	mint is the common vocabulary of graphics crates, so this gives conversions with all the crates supporting it. Column matrices convert by moving their columns, row matrices are transposed.
+*/
+
+use crate::prelude::*;
+use crate::matrix::*;
+use crate::matrices::*;
+
+
+macro_rules! mint_vector {
+	($size:literal, $vector:ident) => {
+		impl<T: Element> From<::mint::$vector<T>> for Matrix<Static<T, $size, 1>> {
+			fn from(src: ::mint::$vector<T>) -> Self  {Matrix(Static {data: [src.into()]})}
+		}
+		impl<T: Element> From<Matrix<Static<T, $size, 1>>> for ::mint::$vector<T> {
+			fn from(src: Matrix<Static<T, $size, 1>>) -> Self {
+				let [column] = src.0.data;
+				column.into()
+			}
+		}
+	}
+}
+mint_vector!(2, Vector2);
+mint_vector!(3, Vector3);
+mint_vector!(4, Vector4);
+
+macro_rules! mint_matrix {
+	($size:literal, $column:ident, $row:ident) => {
+		impl<T: Element> From<::mint::$column<T>> for Matrix<Static<T, $size, $size>> {
+			fn from(src: ::mint::$column<T>) -> Self  {Matrix(Static {data: src.into()})}
+		}
+		impl<T: Element> From<Matrix<Static<T, $size, $size>>> for ::mint::$column<T> {
+			fn from(src: Matrix<Static<T, $size, $size>>) -> Self  {src.0.data.into()}
+		}
+		impl<T: Element> From<::mint::$row<T>> for Matrix<Static<T, $size, $size>> {
+			fn from(src: ::mint::$row<T>) -> Self  {::mint::$column::from(src).into()}
+		}
+		impl<T: Element> From<Matrix<Static<T, $size, $size>>> for ::mint::$row<T> {
+			fn from(src: Matrix<Static<T, $size, $size>>) -> Self  {::mint::$column::from(src).into()}
+		}
+	}
+}
+mint_matrix!(2, ColumnMatrix2, RowMatrix2);
+mint_matrix!(3, ColumnMatrix3, RowMatrix3);
+mint_matrix!(4, ColumnMatrix4, RowMatrix4);
+
+
+
+#[test]
+fn test_mint() {
+	use crate::glm::*;
+
+	let vector = Vec3::from(::mint::Vector3 {x: 1., y: 2., z: 3.});
+	assert_eq!(vector.as_slice(), Some(&[1., 2., 3.][..]));
+	let back: ::mint::Vector3<f32> = vector.into();
+	assert_eq!(back, ::mint::Vector3 {x: 1., y: 2., z: 3.});
+
+	let matrix = Mat2::from([[1., 2.], [3., 4.]]);
+	let columns: ::mint::ColumnMatrix2<f32> = matrix.clone().into();
+	assert_eq!(columns.x, ::mint::Vector2 {x: 1., y: 2.});
+	let rows: ::mint::RowMatrix2<f32> = matrix.clone().into();
+	assert_eq!(rows.x, ::mint::Vector2 {x: 1., y: 3.});
+	assert_eq!(Mat2::from(rows).as_slice(), matrix.as_slice());
+	assert_eq!(Mat2::from(columns).as_slice(), matrix.as_slice());
+}
@@ -0,0 +1,247 @@
+/*!
+	Zero-copy interoperability with [nalgebra](https://docs.rs/nalgebra), enabled by the `nalgebra` feature
+
+	Any nalgebra matrix whose storage exposes its memory implements [Array] and can be wrapped in a [Matrix] without copy, nalgebra dimensions `Const<N>` and `Dyn` being mapped to [Stat] and [Dyn] by [FlexDim]. Conversely any matrix can be viewed as a nalgebra matrix view with [Matrix::as_nalgebra_view].
+
+	Owned matrices are converted both ways with [From] by moving their buffer, since both libraries store them in column-major order:
+
+	- [Static] matrices and nalgebra matrices with `ArrayStorage`
+	- [Dynamic] matrices and nalgebra matrices with `VecStorage`
+*/
+
+use crate::prelude::*;
+use crate::matrix::*;
+use crate::matrices::*;
+
+use ::nalgebra::{
+	self as na,
+	Const, ArrayStorage, VecStorage, ViewStorage, ViewStorageMut,
+	RawStorage, RawStorageMut,
+	};
+use core::mem::MaybeUninit;
+
+
+/// nalgebra dimension with an equivalent [Dim]
+pub trait FlexDim: na::Dim {
+	/// equivalent dimension in this crate
+	type Flex: Dim;
+}
+impl<const N: usize> FlexDim for Const<N>  {type Flex = Stat<N>;}
+impl FlexDim for na::Dyn  {type Flex = Dyn;}
+
+/// [Dim] with an equivalent nalgebra dimension
+pub trait NalgebraDim: Dim {
+	/// equivalent dimension in nalgebra
+	type Nalgebra: FlexDim<Flex=Self>;
+}
+impl<const N: usize> NalgebraDim for Stat<N>  {type Nalgebra = Const<N>;}
+impl NalgebraDim for Dyn  {type Nalgebra = na::Dyn;}
+
+/// nalgebra view on a matrix of this crate, as returned by [Matrix::as_nalgebra_view]
+pub type NalgebraView<'t, T, R, C> = na::Matrix<T, <R as NalgebraDim>::Nalgebra, <C as NalgebraDim>::Nalgebra,
+	ViewStorage<'t, T, <R as NalgebraDim>::Nalgebra, <C as NalgebraDim>::Nalgebra, na::Dyn, na::Dyn>>;
+/// mutable nalgebra view on a matrix of this crate, as returned by [Matrix::as_nalgebra_view_mut]
+pub type NalgebraViewMut<'t, T, R, C> = na::Matrix<T, <R as NalgebraDim>::Nalgebra, <C as NalgebraDim>::Nalgebra,
+	ViewStorageMut<'t, T, <R as NalgebraDim>::Nalgebra, <C as NalgebraDim>::Nalgebra, na::Dyn, na::Dyn>>;
+
+
+impl<T, R, C, S> Array for na::Matrix<T, R, C, S>
+where
+	T: Element,
+	R: FlexDim,
+	C: FlexDim,
+	S: RawStorage<T, R, C>,
+{
+	type Element = T;
+	type R = R::Flex;
+	type C = C::Flex;
+	fn shape(&self) -> [usize; 2] {
+		let (rows, columns) = self.data.shape();
+		[na::Dim::value(&rows), na::Dim::value(&columns)]
+	}
+	fn strides(&self) -> [isize; 2] {
+		let (rows, columns) = self.data.strides();
+		[na::Dim::value(&rows) as isize, na::Dim::value(&columns) as isize]
+	}
+	fn as_ptr(&self) -> *const T  {self.data.ptr()}
+}
+impl<T, R, C, S> ArrayMut for na::Matrix<T, R, C, S>
+where
+	T: Element,
+	R: FlexDim,
+	C: FlexDim,
+	S: RawStorageMut<T, R, C>,
+{
+	fn as_mut_ptr(&mut self) -> *mut T  {self.data.ptr_mut()}
+}
+impl<T: Element, const R: usize, const C: usize>
+	ArrayOwned for na::Matrix<T, Const<R>, Const<C>, ArrayStorage<T, R, C>>
+{
+	type Uninit = MaybeUninit<[[T; R]; C]>;
+	fn uninit(_: (Stat<R>, Stat<C>)) -> Self::Uninit  {MaybeUninit::uninit()}
+}
+impl<T: Element, const R: usize, const C: usize>
+	ArrayUninit<na::Matrix<T, Const<R>, Const<C>, ArrayStorage<T, R, C>>> for MaybeUninit<[[T; R]; C]>
+{
+	fn strides(&self) -> [isize; 2]  {[1, R as isize]}
+	fn as_mut_ptr(&mut self) -> *mut MaybeUninit<T>  {self.as_mut_ptr() as _}
+	unsafe fn assume_init(self) -> na::Matrix<T, Const<R>, Const<C>, ArrayStorage<T, R, C>> {
+		na::Matrix::from_data(ArrayStorage(MaybeUninit::assume_init(self)))
+	}
+}
+impl<T, R, C> ArrayOwned for na::Matrix<T, R, C, VecStorage<T, R, C>>
+where
+	T: Element,
+	R: FlexDim,
+	C: FlexDim,
+	VecStorage<T, R, C>: RawStorageMut<T, R, C>,
+{
+	type Uninit = DynamicUninit<T, R::Flex, C::Flex>;
+	/// not aligned beyond the elements, so that the buffer is moved to the nalgebra vector without copy
+	fn uninit(shape: (R::Flex, C::Flex)) -> Self::Uninit  {DynamicUninit::unaligned(shape)}
+}
+impl<T, R, C> ArrayUninit<na::Matrix<T, R, C, VecStorage<T, R, C>>> for DynamicUninit<T, R::Flex, C::Flex>
+where
+	T: Element,
+	R: FlexDim,
+	C: FlexDim,
+	VecStorage<T, R, C>: RawStorageMut<T, R, C>,
+{
+	fn strides(&self) -> [isize; 2]  {[1, self.leading() as isize]}
+	fn as_mut_ptr(&mut self) -> *mut MaybeUninit<T>  {DynamicUninit::as_mut_ptr(self)}
+	unsafe fn assume_init(self) -> na::Matrix<T, R, C, VecStorage<T, R, C>> {
+		let (rows, columns) = self.shape;
+		let data = ArrayUninit::<Dynamic<T, R::Flex, C::Flex>>::assume_init(self).into_vec();
+		na::Matrix::from_data(VecStorage::new(na::Dim::from_usize(rows.value()), na::Dim::from_usize(columns.value()), data))
+	}
+}
+impl<T, R, C, S, R2: Dim, C2: Dim> Compatible<R2, C2> for na::Matrix<T, R, C, S>
+where
+	T: Element,
+	R: FlexDim,
+	C: FlexDim,
+	S: RawStorage<T, R, C>,
+{
+	type Owned = Dynamic<T, R2, C2>;
+}
+
+
+impl<T: Element, const R: usize, const C: usize>
+	From<na::SMatrix<T, R, C>> for Matrix<Static<T, R, C>>
+{
+	fn from(src: na::SMatrix<T, R, C>) -> Self  {Matrix(Static {data: src.data.0})}
+}
+impl<T: Element, const R: usize, const C: usize>
+	From<Matrix<Static<T, R, C>>> for na::SMatrix<T, R, C>
+{
+	fn from(src: Matrix<Static<T, R, C>>) -> Self  {na::Matrix::from_data(ArrayStorage(src.0.data))}
+}
+impl<T, R, C> From<na::Matrix<T, R, C, VecStorage<T, R, C>>> for Matrix<Dynamic<T, R::Flex, C::Flex>>
+where
+	T: Element,
+	R: FlexDim,
+	C: FlexDim,
+	VecStorage<T, R, C>: RawStorage<T, R, C>,
+{
+	fn from(src: na::Matrix<T, R, C, VecStorage<T, R, C>>) -> Self {
+		let (rows, columns) = src.shape();
+		Matrix(Dynamic::from_vec((R::Flex::check(rows).unwrap(), C::Flex::check(columns).unwrap()), src.data.into()))
+	}
+}
+impl<T, R, C> From<Matrix<Dynamic<T, R, C>>> for na::Matrix<T, R::Nalgebra, C::Nalgebra, VecStorage<T, R::Nalgebra, C::Nalgebra>>
+where
+	T: Element,
+	R: NalgebraDim,
+	C: NalgebraDim,
+	VecStorage<T, R::Nalgebra, C::Nalgebra>: RawStorage<T, R::Nalgebra, C::Nalgebra>,
+{
+	/// the buffer is moved without copy if it is not padded nor aligned beyond the elements, as after a conversion from nalgebra
+	fn from(src: Matrix<Dynamic<T, R, C>>) -> Self {
+		let (rows, columns) = src.0.shape;
+		na::Matrix::from_data(VecStorage::new(
+			na::Dim::from_usize(rows.value()),
+			na::Dim::from_usize(columns.value()),
+			src.into_vec(),
+			))
+	}
+}
+
+impl<A: Array> Matrix<A>
+where
+	A::R: NalgebraDim,
+	A::C: NalgebraDim,
+{
+	/**
+		view on this matrix as a nalgebra matrix, sharing the same memory
+		
+		panics if the matrix has negative strides, which nalgebra does not support
+	*/
+	pub fn as_nalgebra_view(&self) -> NalgebraView<'_, A::Element, A::R, A::C> {
+		let [rows, columns] = self.shape();
+		let strides = nalgebra_strides(self.strides());
+		// safety: the array trait implementor ensures the memory at these strides can be read during the borrow of the matrix
+		na::Matrix::from_data(unsafe {ViewStorage::from_raw_parts(
+			self.as_ptr(),
+			(na::Dim::from_usize(rows), na::Dim::from_usize(columns)),
+			(na::Dyn(strides[0]), na::Dyn(strides[1])),
+			)})
+	}
+}
+impl<A: ArrayMut> Matrix<A>
+where
+	A::R: NalgebraDim,
+	A::C: NalgebraDim,
+{
+	/// same as [Self::as_nalgebra_view] but mutable
+	pub fn as_nalgebra_view_mut(&mut self) -> NalgebraViewMut<'_, A::Element, A::R, A::C> {
+		let [rows, columns] = self.shape();
+		let strides = nalgebra_strides(self.strides());
+		// safety: the array trait implementor ensures the memory at these strides can be written, and the matrix is mutably borrowed
+		na::Matrix::from_data(unsafe {ViewStorageMut::from_raw_parts(
+			self.as_mut_ptr(),
+			(na::Dim::from_usize(rows), na::Dim::from_usize(columns)),
+			(na::Dyn(strides[0]), na::Dyn(strides[1])),
+			)})
+	}
+}
+
+/// strides accepted by nalgebra views
+fn nalgebra_strides(strides: [isize; 2]) -> [usize; 2] {
+	strides.map(|s|  usize::try_from(s).expect("negative strides are not supported by nalgebra"))
+}
+
+
+#[test]
+fn test_nalgebra() {
+	let na_static = na::Matrix3x2::new(
+		1., 2.,
+		3., 4.,
+		5., 6.);
+	// wrapping a nalgebra matrix without copy
+	let wrapped = Matrix(na_static);
+	assert_eq!(wrapped.shape(), [3, 2]);
+	assert_eq!(wrapped[[2,1]], 6.);
+	let view = Matrix(na_static.rows(1, 2));
+	assert_eq!(view.strides(), [1, 3]);
+	assert_eq!(view[[0,1]], 4.);
+
+	// owned conversions keep the column-major buffer
+	let flex = SMatrix::<f64,3,2>::from(na_static);
+	assert_eq!(flex.as_slice(), Some(&[1., 3., 5., 2., 4., 6.][..]));
+	assert_eq!(na::Matrix3x2::from(flex.clone()), na_static);
+	let na_dynamic = na::DMatrix::from_fn(3, 4, |i, j|  (i + 3*j) as f64);
+	let flex_dynamic = DMatrix::<f64>::from(na_dynamic.clone());
+	assert_eq!(flex_dynamic.as_slice(), na_dynamic.as_slice().into());
+	assert_eq!(na::DMatrix::from(flex_dynamic.clone()), na_dynamic);
+
+	// operations mixing both libraries
+	let product = &flex_dynamic.transpose() * &Matrix(na_dynamic.clone());
+	assert_eq!(product.as_nalgebra_view(), na_dynamic.transpose() * &na_dynamic);
+	assert_eq!(flex.transpose().as_nalgebra_view(), na_static.transpose());
+	let mut owned = Matrix::<na::DMatrix<f64>>::new([2, 2]);
+	owned.set_identity();
+	assert_eq!(owned.0, na::DMatrix::identity(2, 2));
+	let mut dense = DMatrix::<f64>::zeros([2, 3]);
+	dense.as_nalgebra_view_mut().fill(2.);
+	assert_eq!(dense.as_slice(), Some(&[2.; 6][..]));
+}
@@ -0,0 +1,149 @@
+/*!
+	Zero-copy interoperability with [ndarray](https://docs.rs/ndarray), enabled by the `ndarray` feature
+
+	Any 2-dimensional [ArrayBase] (owned [Array2], views [ArrayView2] and [ArrayViewMut2], shared arrays...) implements [Array] and can be wrapped in a [Matrix] without copy. Raw views are not matrices, as nothing ensures their memory is still alive. Conversely any matrix can be viewed as an ndarray view with [Matrix::to_ndarray_view].
+
+	Negative strides (for instance after reversing an axis) are supported both ways, as in [Matrix::flip_rows].
+*/
+
+use crate::prelude::*;
+use crate::matrix::*;
+use crate::matrices::*;
+
+use ::ndarray::{
+	ArrayBase, Array2, ArrayView2, ArrayViewMut2, Ix2, Axis,
+	Data, DataMut, RawData, OwnedRepr, ShapeBuilder,
+	};
+use core::mem::MaybeUninit;
+
+
+impl<S> Array for ArrayBase<S, Ix2>
+where
+	S: Data,
+	S::Elem: Element,
+{
+	type Element = S::Elem;
+	type R = Dyn;
+	type C = Dyn;
+	fn shape(&self) -> [usize; 2] {
+		let shape = ArrayBase::shape(self);
+		[shape[0], shape[1]]
+	}
+	fn strides(&self) -> [isize; 2] {
+		let strides = ArrayBase::strides(self);
+		[strides[0], strides[1]]
+	}
+	fn as_ptr(&self) -> *const S::Elem  {ArrayBase::as_ptr(self)}
+}
+impl<S> ArrayMut for ArrayBase<S, Ix2>
+where
+	S: DataMut,
+	S::Elem: Element,
+{
+	fn as_mut_ptr(&mut self) -> *mut S::Elem  {ArrayBase::as_mut_ptr(self)}
+}
+impl<T: Element> ArrayOwned for Array2<T> {
+	type Uninit = Array2<MaybeUninit<T>>;
+	/// column-major array, as other owned arrays
+	fn uninit(shape: (Dyn, Dyn)) -> Self::Uninit {
+		Array2::uninit((shape.0.value(), shape.1.value()).f())
+	}
+}
+impl<T: Element> ArrayUninit<Array2<T>> for Array2<MaybeUninit<T>> {
+	fn strides(&self) -> [isize; 2] {
+		let strides = ArrayBase::strides(self);
+		[strides[0], strides[1]]
+	}
+	fn as_mut_ptr(&mut self) -> *mut MaybeUninit<T>  {ArrayBase::as_mut_ptr(self)}
+	unsafe fn assume_init(self) -> Array2<T>  {ArrayBase::assume_init(self)}
+}
+impl<S, R2: Dim, C2: Dim> Compatible<R2, C2> for ArrayBase<S, Ix2>
+where
+	S: Data,
+	S::Elem: Element,
+{
+	type Owned = Dynamic<S::Elem, R2, C2>;
+}
+
+impl<T: Element> Matrix<Array2<T>> {
+	/// retreive the ndarray
+	pub fn into_ndarray(self) -> Array2<T>  {self.0}
+}
+impl<A: Array> Matrix<A> {
+	/// view on this matrix as an ndarray, sharing the same memory
+	pub fn to_ndarray_view(&self) -> ArrayView2<'_, A::Element> {
+		let [rows, columns] = self.shape();
+		let strides = self.strides();
+		// ndarray views are built from their lowest address with positive strides, then reversed
+		let start = lowest(self.shape(), strides);
+		let [row_step, column_step] = strides.map(isize::unsigned_abs);
+		// safety: the array trait implementor ensures the memory at these strides can be read during the borrow of the matrix
+		let mut view = unsafe {ArrayView2::from_shape_ptr((rows, columns).strides((row_step, column_step)), self.as_ptr().sub(start))};
+		reverse_negative(&mut view, strides);
+		view
+	}
+}
+impl<A: ArrayMut> Matrix<A> {
+	/// same as [Self::to_ndarray_view] but mutable
+	pub fn to_ndarray_view_mut(&mut self) -> ArrayViewMut2<'_, A::Element> {
+		let [rows, columns] = self.shape();
+		let strides = self.strides();
+		let start = lowest(self.shape(), strides);
+		let [row_step, column_step] = strides.map(isize::unsigned_abs);
+		// safety: the array trait implementor ensures the memory at these strides can be written, and the matrix is mutably borrowed
+		let mut view = unsafe {ArrayViewMut2::from_shape_ptr((rows, columns).strides((row_step, column_step)), self.as_mut_ptr().sub(start))};
+		reverse_negative(&mut view, strides);
+		view
+	}
+}
+
+/// reverse the axes of an ndarray where the given strides are negative
+fn reverse_negative<S: RawData>(array: &mut ArrayBase<S, Ix2>, strides: [isize; 2]) {
+	for (axis, stride) in strides.into_iter().enumerate() {
+		if stride < 0  {array.invert_axis(Axis(axis))}
+	}
+}
+
+/// matrix based on an owned ndarray
+pub type NdMatrix<T> = Matrix<ArrayBase<OwnedRepr<T>, Ix2>>;
+
+
+
+#[test]
+fn test_ndarray() {
+	use ::ndarray::{array, s};
+
+	let array = array![[1., 2., 3.], [4., 5., 6.]];
+	let matrix = Matrix(array.view());
+	assert_eq!(matrix.shape(), [2, 3]);
+	assert_eq!(matrix[[1,0]], 4.);
+	// row-major ndarray and column-major matrices mix in operations
+	let other = DMatrix::<f64>::field([3, 2], |[i,j]|  (i + 3*j) as f64);
+	let product = &matrix * &other;
+	assert_eq!(product.to_ndarray_view(), array.dot(&other.to_ndarray_view()));
+	assert_eq!(matrix.transpose().to_ndarray_view(), array.t());
+
+	// owned arrays and mutable views
+	let mut owned = Matrix(array.clone());
+	owned[[0,2]] = 0.;
+	let mut zeroed = array.clone();
+	Matrix(zeroed.view_mut()).set_zero();
+	assert!(zeroed.iter().all(|&v|  v == 0.));
+	let copy = NdMatrix::<f64>::from(&owned);
+	assert_eq!(copy.into_ndarray(), array![[1., 2., 0.], [4., 5., 6.]]);
+	let mut dense = DMatrix::<f64>::zeros([2, 2]);
+	dense.to_ndarray_view_mut().fill(1.);
+	assert_eq!(dense.as_slice(), Some(&[1.; 4][..]));
+	// shared arrays are copied on write
+	let shared = array.to_shared();
+	let mut written = Matrix(shared.clone());
+	written[[0,0]] = 9.;
+	assert_eq!((shared[[0,0]], written[[0,0]]), (1., 9.));
+
+	// negative strides
+	let reversed = Matrix(array.slice(s![.., ..;-1]));
+	assert_eq!(reversed.strides(), [3, -1]);
+	assert_eq!(reversed[[1,0]], 6.);
+	assert_eq!(matrix.flip_columns().to_ndarray_view(), array.slice(s![.., ..;-1]));
+	assert_eq!(matrix.rot90().to_ndarray_view(), array.t().slice(s![..;-1, ..]));
+}
@@ -2,37 +2,108 @@
     Provides the matrix iterators. All column major of course
 */
 
-use core::ops::Mul;
+use core::ops::{Mul, Range};
+use core::iter::FusedIterator;
+use core::marker::PhantomData;
+use super::prelude::*;
 use super::matrix::*;
+use super::matrices::*;
 
 
 /// iterator generating column-major indices in a matrix
+#[derive(Clone)]
 pub struct IndexIter {
     shape: [usize; 2],
-    position: [usize; 2],
+    /// next index yielded from the front
+    front: [usize; 2],
+    /// next index yielded from the back
+    back: [usize; 2],
+    remaining: usize,
 }
 impl Iterator for IndexIter  {
     type Item = [usize; 2];
     fn next(&mut self) -> Option<Self::Item> {
-        if self.position[1] >= self.shape[1]
+        if self.remaining == 0
             {return None}
-        let position = self.position.clone();
-        self.position[0] += 1;
-        if self.position[0] >= self.shape[0] {
-            self.position[0] = 0;
-            self.position[1] += 1;
+        self.remaining -= 1;
+        let position = self.front;
+        self.front[0] += 1;
+        if self.front[0] >= self.shape[0] {
+            self.front[0] = 0;
+            self.front[1] += 1;
         }
         Some(position)
     }
+    fn nth(&mut self, n: usize) -> Option<Self::Item> {
+        if n >= self.remaining {
+            self.remaining = 0;
+            return None
+        }
+        self.remaining -= n;
+        self.front = self.unflat(self.flat(self.front) + n);
+        self.next()
+    }
+    fn size_hint(&self) -> (usize, Option<usize>)  {(self.remaining, Some(self.remaining))}
+    fn fold<B, F>(self, init: B, mut f: F) -> B
+    where F: FnMut(B, Self::Item) -> B
+    {
+        // nested loops are much easier to optimize than the state machine of `next`
+        let mut accumulator = init;
+        let mut remaining = self.remaining;
+        let [mut start, mut j] = self.front;
+        while remaining != 0 {
+            let end = (start + remaining).min(self.shape[0]);
+            for i in start .. end {
+                accumulator = f(accumulator, [i,j]);
+            }
+            remaining -= end - start;
+            start = 0;
+            j += 1;
+        }
+        accumulator
+    }
+}
+impl DoubleEndedIterator for IndexIter {
+    fn next_back(&mut self) -> Option<Self::Item> {
+        if self.remaining == 0
+            {return None}
+        self.remaining -= 1;
+        let position = self.back;
+        if self.remaining != 0 {
+            if self.back[0] == 0 {
+                self.back[0] = self.shape[0];
+                self.back[1] -= 1;
+            }
+            self.back[0] -= 1;
+        }
+        Some(position)
+    }
+    fn nth_back(&mut self, n: usize) -> Option<Self::Item> {
+        if n >= self.remaining {
+            self.remaining = 0;
+            return None
+        }
+        self.remaining -= n;
+        self.back = self.unflat(self.flat(self.back) - n);
+        self.next_back()
+    }
 }
 impl ExactSizeIterator for IndexIter {
-    fn len(&self) -> usize  {self.shape.iter().cloned().reduce(Mul::mul).unwrap()}
+    fn len(&self) -> usize  {self.remaining}
 }
+impl FusedIterator for IndexIter {}
 impl IndexIter {
-    pub fn new(shape: [usize; 2]) -> Self  {Self{
-        shape,
-        position: [0; 2],
-    }}
+    pub fn new(shape: [usize; 2]) -> Self  {
+        let size = shape.iter().cloned().reduce(Mul::mul).unwrap();
+        Self{
+            shape,
+            front: [0; 2],
+            back: if size == 0 {[0; 2]} else {[shape[0]-1, shape[1]-1]},
+            remaining: size,
+        }
+    }
+    fn flat(&self, index: [usize; 2]) -> usize  {index[0] + index[1]*self.shape[0]}
+    fn unflat(&self, flat: usize) -> [usize; 2]  {[flat % self.shape[0], flat / self.shape[0]]}
 }
 impl<A:Array> Matrix<A> {
     pub fn index(&self) -> IndexIter {IndexIter::new(self.shape())}
@@ -41,16 +112,49 @@ impl<A:Array> Matrix<A> {
 
 /// immutable column-major iterator into a matrix with elements indices
 pub struct IndexedIter<'t, A: Array> {
-    matrix: &'t Matrix<A>,
+    data: *const A::Element,
+    strides: [isize; 2],
     index: IndexIter,
+    lifetime: PhantomData<&'t A::Element>,
+}
+impl<'t,A:Array> IndexedIter<'t,A> {
+    fn new(matrix: &'t Matrix<A>) -> Self  {Self{
+        data: matrix.as_ptr(),
+        strides: matrix.strides(),
+        index: matrix.index(),
+        lifetime: PhantomData,
+    }}
+    fn get(data: *const A::Element, strides: [isize; 2], index: [usize; 2]) -> ([usize; 2], &'t A::Element) {
+        // safety: the index iterator only produces indices in the matrix shape
+        (index, unsafe {&* data.offset(offset(index, strides))})
+    }
 }
 impl<'t,A:Array> 
     Iterator for IndexedIter<'t,A> 
 {
     type Item = ([usize; 2], &'t A::Element);
     fn next(&mut self) -> Option<Self::Item> {
-        let index = self.index.next()?;
-        Some((index, & self.matrix[index]))
+        self.index.next().map(|i|  Self::get(self.data, self.strides, i))
+    }
+    fn nth(&mut self, n: usize) -> Option<Self::Item> {
+        self.index.nth(n).map(|i|  Self::get(self.data, self.strides, i))
+    }
+    fn size_hint(&self) -> (usize, Option<usize>)  {self.index.size_hint()}
+    fn fold<B, F>(self, init: B, mut f: F) -> B
+    where F: FnMut(B, Self::Item) -> B
+    {
+        let (data, strides) = (self.data, self.strides);
+        self.index.fold(init, |accumulator, i|  f(accumulator, Self::get(data, strides, i)))
+    }
+}
+impl<A:Array>
+    DoubleEndedIterator for IndexedIter<'_,A>
+{
+    fn next_back(&mut self) -> Option<Self::Item> {
+        self.index.next_back().map(|i|  Self::get(self.data, self.strides, i))
+    }
+    fn nth_back(&mut self, n: usize) -> Option<Self::Item> {
+        self.index.nth_back(n).map(|i|  Self::get(self.data, self.strides, i))
     }
 }
 impl<A:Array>
@@ -58,6 +162,7 @@ impl<A:Array>
 {
     fn len(&self) -> usize  {self.index.len()}
 }
+impl<A:Array> FusedIterator for IndexedIter<'_,A> {}
 impl<'t,A:Array> IndexedIter<'t,A> {
     pub fn values(self) -> MatrixIter<'t,A>  {MatrixIter(self)}
 }
@@ -72,22 +177,39 @@ impl<'t,A:Array>
     fn next(&mut self) -> Option<Self::Item> {
         self.0.next().map(|item|  item.1)
     }
+    fn nth(&mut self, n: usize) -> Option<Self::Item> {
+        self.0.nth(n).map(|item|  item.1)
+    }
+    fn size_hint(&self) -> (usize, Option<usize>)  {self.0.size_hint()}
+    fn fold<B, F>(self, init: B, mut f: F) -> B
+    where F: FnMut(B, Self::Item) -> B
+    {
+        self.0.fold(init, |accumulator, item|  f(accumulator, item.1))
+    }
+}
+impl<A:Array>
+    DoubleEndedIterator for MatrixIter<'_,A>
+{
+    fn next_back(&mut self) -> Option<Self::Item> {
+        self.0.next_back().map(|item|  item.1)
+    }
+    fn nth_back(&mut self, n: usize) -> Option<Self::Item> {
+        self.0.nth_back(n).map(|item|  item.1)
+    }
 }
 impl<A:Array>
     ExactSizeIterator for MatrixIter<'_,A>
 {
     fn len(&self) -> usize  {self.0.len()}
 }
+impl<A:Array> FusedIterator for MatrixIter<'_,A> {}
 impl<'t,A:Array> 
     IntoIterator for &'t Matrix<A> 
 {
     type Item = &'t A::Element;
     type IntoIter = MatrixIter<'t,A>;
     fn into_iter(self) -> Self::IntoIter {
-        MatrixIter(IndexedIter {
-            index: self.index(),
-            matrix: self,
-        })
+        MatrixIter(IndexedIter::new(self))
     }
 }
 impl<'t,A:Array> MatrixIter<'t,A> {
@@ -101,20 +223,50 @@ impl<A:Array>  Matrix<A> {
 
 /// mutable column-major iterator into a matrix with elements indices
 pub struct IndexedIterMut<'t, A: ArrayMut> {
-    matrix: &'t mut Matrix<A>,
+    data: *mut A::Element,
+    strides: [isize; 2],
     index: IndexIter,
+    lifetime: PhantomData<&'t mut A::Element>,
+}
+impl<'t,A:ArrayMut> IndexedIterMut<'t,A> {
+    fn new(matrix: &'t mut Matrix<A>) -> Self  {Self{
+        strides: matrix.strides(),
+        index: matrix.index(),
+        data: matrix.as_mut_ptr(),
+        lifetime: PhantomData,
+    }}
+    fn get(data: *mut A::Element, strides: [isize; 2], index: [usize; 2]) -> ([usize; 2], &'t mut A::Element) {
+        // the mutable refernce may outive the iterator but not the matrix reference
+        // safety: elements are indexed once only and are in the matrix shape, so one only mutable reference to each location can be created for the lifetime of the matrix reference
+        (index, unsafe {&mut* data.offset(offset(index, strides))})
+    }
 }
 impl<'t,A:ArrayMut> 
     Iterator for IndexedIterMut<'t,A> 
 {
     type Item = ([usize; 2], &'t mut A::Element);
     fn next(&mut self) -> Option<Self::Item> {
-        let index = self.index.next()?;
-        Some((index, unsafe {
-            // the mutable refernce may outive the iterator but not the matrix reference
-            // safety: elements are indexed once only, so one only mutable reference to each location can be created for the lifetime of the matrix reference
-            core::mem::transmute::<&mut A::Element, &mut A::Element>(&mut self.matrix[index])
-            }))
+        self.index.next().map(|i|  Self::get(self.data, self.strides, i))
+    }
+    fn nth(&mut self, n: usize) -> Option<Self::Item> {
+        self.index.nth(n).map(|i|  Self::get(self.data, self.strides, i))
+    }
+    fn size_hint(&self) -> (usize, Option<usize>)  {self.index.size_hint()}
+    fn fold<B, F>(self, init: B, mut f: F) -> B
+    where F: FnMut(B, Self::Item) -> B
+    {
+        let (data, strides) = (self.data, self.strides);
+        self.index.fold(init, |accumulator, i|  f(accumulator, Self::get(data, strides, i)))
+    }
+}
+impl<A:ArrayMut>
+    DoubleEndedIterator for IndexedIterMut<'_,A>
+{
+    fn next_back(&mut self) -> Option<Self::Item> {
+        self.index.next_back().map(|i|  Self::get(self.data, self.strides, i))
+    }
+    fn nth_back(&mut self, n: usize) -> Option<Self::Item> {
+        self.index.nth_back(n).map(|i|  Self::get(self.data, self.strides, i))
     }
 }
 impl<A:ArrayMut>
@@ -122,6 +274,7 @@ impl<A:ArrayMut>
 {
     fn len(&self) -> usize  {self.index.len()}
 }
+impl<A:ArrayMut> FusedIterator for IndexedIterMut<'_,A> {}
 impl<'t,A:ArrayMut> IndexedIterMut<'t,A> {
     pub fn values(self) -> MatrixIterMut<'t,A>  {MatrixIterMut(self)}
 }
@@ -136,22 +289,39 @@ impl<'t,A:ArrayMut>
     fn next(&mut self) -> Option<Self::Item> {
         self.0.next().map(|item|  item.1)
     }
+    fn nth(&mut self, n: usize) -> Option<Self::Item> {
+        self.0.nth(n).map(|item|  item.1)
+    }
+    fn size_hint(&self) -> (usize, Option<usize>)  {self.0.size_hint()}
+    fn fold<B, F>(self, init: B, mut f: F) -> B
+    where F: FnMut(B, Self::Item) -> B
+    {
+        self.0.fold(init, |accumulator, item|  f(accumulator, item.1))
+    }
+}
+impl<A:ArrayMut>
+    DoubleEndedIterator for MatrixIterMut<'_,A>
+{
+    fn next_back(&mut self) -> Option<Self::Item> {
+        self.0.next_back().map(|item|  item.1)
+    }
+    fn nth_back(&mut self, n: usize) -> Option<Self::Item> {
+        self.0.nth_back(n).map(|item|  item.1)
+    }
 }
 impl<A:ArrayMut>
     ExactSizeIterator for MatrixIterMut<'_,A>
 {
     fn len(&self) -> usize  {self.0.len()}
 }
+impl<A:ArrayMut> FusedIterator for MatrixIterMut<'_,A> {}
 impl<'t,A:ArrayMut> 
     IntoIterator for &'t mut Matrix<A> 
 {
     type Item = &'t mut A::Element;
     type IntoIter = MatrixIterMut<'t,A>;
     fn into_iter(self) -> Self::IntoIter {
-        MatrixIterMut(IndexedIterMut {
-            index: self.index(),
-            matrix: self,
-        })
+        MatrixIterMut(IndexedIterMut::new(self))
     }
 }
 impl<'t,A:ArrayMut> MatrixIterMut<'t,A> {
@@ -162,6 +332,364 @@ impl<A:ArrayMut>  Matrix<A> {
 }
 
 
+/// double-ended iterator over the rows of a matrix, yielding each row as a vector view
+pub struct RowIter<'t, A: Array> {
+    matrix: &'t Matrix<A>,
+    range: Range<usize>,
+}
+impl<'t,A:Array>
+    Iterator for RowIter<'t,A>
+{
+    type Item = VectorView<'t, A::Element, A::C>;
+    fn next(&mut self) -> Option<Self::Item> {
+        let matrix = self.matrix;
+        self.range.next().map(|i|  matrix.row(i))
+    }
+    fn nth(&mut self, n: usize) -> Option<Self::Item> {
+        let matrix = self.matrix;
+        self.range.nth(n).map(|i|  matrix.row(i))
+    }
+    fn size_hint(&self) -> (usize, Option<usize>)  {self.range.size_hint()}
+}
+impl<A:Array>
+    DoubleEndedIterator for RowIter<'_,A>
+{
+    fn next_back(&mut self) -> Option<Self::Item> {
+        let matrix = self.matrix;
+        self.range.next_back().map(|i|  matrix.row(i))
+    }
+}
+impl<A:Array>
+    ExactSizeIterator for RowIter<'_,A>
+{
+    fn len(&self) -> usize  {self.range.len()}
+}
+
+
+/// double-ended iterator over the columns of a matrix, yielding each column as a vector view
+pub struct ColumnIter<'t, A: Array> {
+    matrix: &'t Matrix<A>,
+    range: Range<usize>,
+}
+impl<'t,A:Array>
+    Iterator for ColumnIter<'t,A>
+{
+    type Item = VectorView<'t, A::Element, A::R>;
+    fn next(&mut self) -> Option<Self::Item> {
+        let matrix = self.matrix;
+        self.range.next().map(|j|  matrix.column(j))
+    }
+    fn nth(&mut self, n: usize) -> Option<Self::Item> {
+        let matrix = self.matrix;
+        self.range.nth(n).map(|j|  matrix.column(j))
+    }
+    fn size_hint(&self) -> (usize, Option<usize>)  {self.range.size_hint()}
+}
+impl<A:Array>
+    DoubleEndedIterator for ColumnIter<'_,A>
+{
+    fn next_back(&mut self) -> Option<Self::Item> {
+        let matrix = self.matrix;
+        self.range.next_back().map(|j|  matrix.column(j))
+    }
+}
+impl<A:Array>
+    ExactSizeIterator for ColumnIter<'_,A>
+{
+    fn len(&self) -> usize  {self.range.len()}
+}
+
+
+/// same as [RowIter] but yielding mutable views
+pub struct RowIterMut<'t, A: ArrayMut> {
+    data: *mut A::Element,
+    dim: (A::R, A::C),
+    strides: [isize; 2],
+    range: Range<usize>,
+    lifetime: PhantomData<&'t mut A::Element>,
+}
+impl<'t,A:ArrayMut> RowIterMut<'t,A> {
+    fn get(&self, i: usize) -> VectorViewMut<'t, A::Element, A::C> {
+        Matrix(ViewMut {
+            shape: (self.dim.1, Stat{}),
+            strides: (self.strides[1], self.strides[0]),
+            // safety: the index comes from the iterator range, so it is in the matrix bounds
+            data: unsafe {self.data.offset(i as isize * self.strides[0])},
+            lifetime: PhantomData,
+        })
+    }
+}
+impl<'t,A:ArrayMut>
+    Iterator for RowIterMut<'t,A>
+{
+    type Item = VectorViewMut<'t, A::Element, A::C>;
+    // rows are yielded once only, so one only mutable view to each row can be created for the lifetime of the matrix reference
+    fn next(&mut self) -> Option<Self::Item> {
+        self.range.next().map(|i|  self.get(i))
+    }
+    fn nth(&mut self, n: usize) -> Option<Self::Item> {
+        self.range.nth(n).map(|i|  self.get(i))
+    }
+    fn size_hint(&self) -> (usize, Option<usize>)  {self.range.size_hint()}
+}
+impl<A:ArrayMut>
+    DoubleEndedIterator for RowIterMut<'_,A>
+{
+    fn next_back(&mut self) -> Option<Self::Item> {
+        self.range.next_back().map(|i|  self.get(i))
+    }
+}
+impl<A:ArrayMut>
+    ExactSizeIterator for RowIterMut<'_,A>
+{
+    fn len(&self) -> usize  {self.range.len()}
+}
+
+
+/// same as [ColumnIter] but yielding mutable views
+pub struct ColumnIterMut<'t, A: ArrayMut> {
+    data: *mut A::Element,
+    dim: (A::R, A::C),
+    strides: [isize; 2],
+    range: Range<usize>,
+    lifetime: PhantomData<&'t mut A::Element>,
+}
+impl<'t,A:ArrayMut> ColumnIterMut<'t,A> {
+    fn get(&self, j: usize) -> VectorViewMut<'t, A::Element, A::R> {
+        Matrix(ViewMut {
+            shape: (self.dim.0, Stat{}),
+            strides: (self.strides[0], self.strides[1]),
+            // safety: the index comes from the iterator range, so it is in the matrix bounds
+            data: unsafe {self.data.offset(j as isize * self.strides[1])},
+            lifetime: PhantomData,
+        })
+    }
+}
+impl<'t,A:ArrayMut>
+    Iterator for ColumnIterMut<'t,A>
+{
+    type Item = VectorViewMut<'t, A::Element, A::R>;
+    // columns are yielded once only, so one only mutable view to each column can be created for the lifetime of the matrix reference
+    fn next(&mut self) -> Option<Self::Item> {
+        self.range.next().map(|j|  self.get(j))
+    }
+    fn nth(&mut self, n: usize) -> Option<Self::Item> {
+        self.range.nth(n).map(|j|  self.get(j))
+    }
+    fn size_hint(&self) -> (usize, Option<usize>)  {self.range.size_hint()}
+}
+impl<A:ArrayMut>
+    DoubleEndedIterator for ColumnIterMut<'_,A>
+{
+    fn next_back(&mut self) -> Option<Self::Item> {
+        self.range.next_back().map(|j|  self.get(j))
+    }
+}
+impl<A:ArrayMut>
+    ExactSizeIterator for ColumnIterMut<'_,A>
+{
+    fn len(&self) -> usize  {self.range.len()}
+}
+
+
+/// iterator over overlapping groups of `size` consecutive columns, like [slice::windows]
+pub struct ColumnWindows<'t, A: Array> {
+    matrix: &'t Matrix<A>,
+    size: usize,
+    range: Range<usize>,
+}
+impl<'t,A:Array> ColumnWindows<'t,A> {
+    fn get(&self, start: usize) -> MatrixView<'t, A::Element, A::R, Dyn> {
+        let strides = self.matrix.strides();
+        Matrix(View {
+            shape: (self.matrix.dimensionality().0, Dyn(self.size)),
+            strides: (strides[0], strides[1]),
+            data: unsafe {self.matrix.as_ptr().offset(start as isize * strides[1])},
+            lifetime: PhantomData,
+        })
+    }
+}
+impl<'t,A:Array>
+    Iterator for ColumnWindows<'t,A>
+{
+    type Item = MatrixView<'t, A::Element, A::R, Dyn>;
+    fn next(&mut self) -> Option<Self::Item> {
+        self.range.next().map(|j|  self.get(j))
+    }
+    fn nth(&mut self, n: usize) -> Option<Self::Item> {
+        self.range.nth(n).map(|j|  self.get(j))
+    }
+    fn size_hint(&self) -> (usize, Option<usize>)  {self.range.size_hint()}
+}
+impl<A:Array>
+    DoubleEndedIterator for ColumnWindows<'_,A>
+{
+    fn next_back(&mut self) -> Option<Self::Item> {
+        self.range.next_back().map(|j|  self.get(j))
+    }
+}
+impl<A:Array>
+    ExactSizeIterator for ColumnWindows<'_,A>
+{
+    fn len(&self) -> usize  {self.range.len()}
+}
+
+
+/// iterator over non-overlapping groups of `size` consecutive columns, like [slice::chunks]. The last chunk may have less columns
+pub struct ColumnChunks<'t, A: Array> {
+    matrix: &'t Matrix<A>,
+    size: usize,
+    range: Range<usize>,
+}
+impl<'t,A:Array> ColumnChunks<'t,A> {
+    fn get(&self, chunk: usize) -> MatrixView<'t, A::Element, A::R, Dyn> {
+        let strides = self.matrix.strides();
+        let start = chunk*self.size;
+        Matrix(View {
+            shape: (self.matrix.dimensionality().0, Dyn(self.size.min(self.matrix.columns() - start))),
+            strides: (strides[0], strides[1]),
+            data: unsafe {self.matrix.as_ptr().offset(start as isize * strides[1])},
+            lifetime: PhantomData,
+        })
+    }
+}
+impl<'t,A:Array>
+    Iterator for ColumnChunks<'t,A>
+{
+    type Item = MatrixView<'t, A::Element, A::R, Dyn>;
+    fn next(&mut self) -> Option<Self::Item> {
+        self.range.next().map(|k|  self.get(k))
+    }
+    fn nth(&mut self, n: usize) -> Option<Self::Item> {
+        self.range.nth(n).map(|k|  self.get(k))
+    }
+    fn size_hint(&self) -> (usize, Option<usize>)  {self.range.size_hint()}
+}
+impl<A:Array>
+    DoubleEndedIterator for ColumnChunks<'_,A>
+{
+    fn next_back(&mut self) -> Option<Self::Item> {
+        self.range.next_back().map(|k|  self.get(k))
+    }
+}
+impl<A:Array>
+    ExactSizeIterator for ColumnChunks<'_,A>
+{
+    fn len(&self) -> usize  {self.range.len()}
+}
+
+
+/// same as [ColumnChunks] but yielding mutable views
+pub struct ColumnChunksMut<'t, A: ArrayMut> {
+    data: *mut A::Element,
+    dim: (A::R, A::C),
+    strides: [isize; 2],
+    size: usize,
+    range: Range<usize>,
+    lifetime: PhantomData<&'t mut A::Element>,
+}
+impl<'t,A:ArrayMut> ColumnChunksMut<'t,A> {
+    fn get(&self, chunk: usize) -> Matrix<ViewMut<'t, A::Element, A::R, Dyn>> {
+        let start = chunk*self.size;
+        Matrix(ViewMut {
+            shape: (self.dim.0, Dyn(self.size.min(self.dim.1.value() - start))),
+            strides: (self.strides[0], self.strides[1]),
+            // safety: the chunk comes from the iterator range, so it is in the matrix bounds
+            data: unsafe {self.data.offset(start as isize * self.strides[1])},
+            lifetime: PhantomData,
+        })
+    }
+}
+impl<'t,A:ArrayMut>
+    Iterator for ColumnChunksMut<'t,A>
+{
+    type Item = Matrix<ViewMut<'t, A::Element, A::R, Dyn>>;
+    // chunks do not overlap and are yielded once only, so one only mutable view to each column can be created for the lifetime of the matrix reference
+    fn next(&mut self) -> Option<Self::Item> {
+        self.range.next().map(|k|  self.get(k))
+    }
+    fn nth(&mut self, n: usize) -> Option<Self::Item> {
+        self.range.nth(n).map(|k|  self.get(k))
+    }
+    fn size_hint(&self) -> (usize, Option<usize>)  {self.range.size_hint()}
+}
+impl<A:ArrayMut>
+    DoubleEndedIterator for ColumnChunksMut<'_,A>
+{
+    fn next_back(&mut self) -> Option<Self::Item> {
+        self.range.next_back().map(|k|  self.get(k))
+    }
+}
+impl<A:ArrayMut>
+    ExactSizeIterator for ColumnChunksMut<'_,A>
+{
+    fn len(&self) -> usize  {self.range.len()}
+}
+
+
+impl<A:Array>  Matrix<A> {
+    /// iterate over the matrix rows, each row being a vector view
+    pub fn row_iter(&self) -> RowIter<'_,A>  {RowIter {
+        matrix: self,
+        range: 0 .. self.rows(),
+    }}
+    /// iterate over the matrix columns, each column being a vector view
+    pub fn column_iter(&self) -> ColumnIter<'_,A>  {ColumnIter {
+        matrix: self,
+        range: 0 .. self.columns(),
+    }}
+    /// iterate over all overlapping groups of `size` consecutive columns. `size` must be non-zero
+    pub fn column_windows(&self, size: usize) -> ColumnWindows<'_,A>  {
+        assert!(size != 0);
+        ColumnWindows {
+            matrix: self,
+            size,
+            range: 0 .. (self.columns() + 1).saturating_sub(size),
+        }
+    }
+    /// iterate over non-overlapping groups of `size` consecutive columns. `size` must be non-zero
+    pub fn column_chunks(&self, size: usize) -> ColumnChunks<'_,A>  {
+        assert!(size != 0);
+        ColumnChunks {
+            matrix: self,
+            size,
+            range: 0 .. self.columns().div_ceil(size),
+        }
+    }
+}
+impl<A:ArrayMut>  Matrix<A> {
+    /// same as [Self::row_iter] but mutable
+    pub fn row_iter_mut(&mut self) -> RowIterMut<'_,A>  {RowIterMut {
+        dim: self.dimensionality(),
+        strides: self.strides(),
+        range: 0 .. self.rows(),
+        data: self.as_mut_ptr(),
+        lifetime: PhantomData,
+    }}
+    /// same as [Self::column_iter] but mutable
+    pub fn column_iter_mut(&mut self) -> ColumnIterMut<'_,A>  {ColumnIterMut {
+        dim: self.dimensionality(),
+        strides: self.strides(),
+        range: 0 .. self.columns(),
+        data: self.as_mut_ptr(),
+        lifetime: PhantomData,
+    }}
+    /// same as [Self::column_chunks] but mutable
+    pub fn column_chunks_mut(&mut self, size: usize) -> ColumnChunksMut<'_,A>  {
+        assert!(size != 0);
+        ColumnChunksMut {
+            dim: self.dimensionality(),
+            strides: self.strides(),
+            size,
+            range: 0 .. self.columns().div_ceil(size),
+            data: self.as_mut_ptr(),
+            lifetime: PhantomData,
+        }
+    }
+}
+
+
+
 
 
 #[test]
@@ -177,6 +705,21 @@ fn test_iterators() {
         *v = 2;
     }
     assert!(m.iter().all(|&v| v == 2));
+    
+    let m = SMatrix::from([[1,2,3,4], [5,6,7,8], [9,10,11,12]]);
+    let t = m.transpose();
+    assert_eq!(t.iter().len(), 12);
+    assert_eq!(t.iter().cloned().collect::<Vec<_>>(), [1,5,9, 2,6,10, 3,7,11, 4,8,12]);
+    assert_eq!(t.iter().rev().cloned().collect::<Vec<_>>(), [12,8,4, 11,7,3, 10,6,2, 9,5,1]);
+    assert_eq!(t.iter().sum::<i32>(), 78);
+    let mut it = t.iter();
+    assert_eq!(it.nth(4), Some(&6));
+    assert_eq!(it.nth_back(2), Some(&4));
+    assert_eq!(it.len(), 4);
+    assert_eq!(it.index().map(|(i,_)| i).collect::<Vec<_>>(), [[2,1], [0,2], [1,2], [2,2]]);
+    let mut it = t.iter();
+    assert_eq!(it.nth(12), None);
+    assert_eq!(it.next(), None);
 }
 #[test]
 fn test_constructors() {
@@ -199,6 +742,51 @@ fn test_constructors() {
         *v = i as _;
     }
     for i in 0 .. 3 {
-        assert_eq!(b[[i,i]], i as _);
+        assert_eq!(b[[i,i]], i as u32);
+    }
+}
+#[test]
+fn test_lanes() {
+    use crate::matrices::*;
+    
+    // point cloud of 4 points in 3D
+    let mut m = SMatrix::from([[1,2,3], [4,5,6], [7,8,9], [10,11,12]]);
+    assert_eq!(m.column_iter().len(), 4);
+    assert_eq!(m.row_iter().len(), 3);
+    for (j,column) in m.column_iter().enumerate() {
+        assert_eq!(column.shape(), [3,1]);
+        for i in 0 .. 3 {
+            assert_eq!(column[[i,0]], m[[i,j]]);
+        }
+    }
+    for (i,row) in m.row_iter().enumerate().rev() {
+        assert_eq!(row.shape(), [4,1]);
+        for j in 0 .. 4 {
+            assert_eq!(row[[j,0]], m[[i,j]]);
+        }
+    }
+    assert_eq!(m.column_iter().next_back().unwrap().iter().cloned().collect::<Vec<_>>(), [10,11,12]);
+    assert_eq!(m.row_iter().nth(1).unwrap().iter().cloned().collect::<Vec<_>>(), [2,5,8,11]);
+    
+    for mut column in m.column_iter_mut() {
+        column[[0,0]] = 0;
+    }
+    for mut row in m.row_iter_mut().rev() {
+        row[[3,0]] += 1;
+    }
+    assert_eq!(m.iter().cloned().collect::<Vec<_>>(), [0,2,3, 0,5,6, 0,8,9, 1,12,13]);
+    
+    assert_eq!(m.column_windows(3).len(), 2);
+    assert_eq!(m.column_windows(5).len(), 0);
+    for (j,window) in m.column_windows(3).enumerate() {
+        assert_eq!(window.shape(), [3,3]);
+        assert_eq!(window[[1,0]], m[[1,j]]);
+    }
+    let chunks = m.column_chunks(3).map(|c| c.shape()).collect::<Vec<_>>();
+    assert_eq!(chunks, [[3,3], [3,1]]);
+    assert_eq!(m.column_chunks(3).next_back().unwrap()[[2,0]], 13);
+    for (k,mut chunk) in m.column_chunks_mut(2).enumerate() {
+        chunk.set_full(k);
     }
+    assert_eq!(m.iter().cloned().collect::<Vec<_>>(), [0,0,0, 0,0,0, 1,1,1, 1,1,1]);
 }
@@ -0,0 +1,719 @@
+/*!
+	Krylov iterative solvers for linear systems `A x = b`
+
+	Iterative solvers only need to apply the matrix to vectors, so they are generic over [LinearOperator] which is implemented for every dense [Matrix], the sparse matrices and closures, allowing to solve matrix-free systems.
+
+	- [cg] conjugate gradient, for symmetric positive definite systems
+	- [minres] minimal residual, for symmetric indefinite systems
+	- [bicgstab] stabilized bi-conjugate gradient, for unsymmetric systems
+	- [gmres] restarted generalized minimal residual, for unsymmetric systems
+
+	All solvers start from the initial guess in `x` and accept a [Preconditioner] approximating `A⁻¹`, see [preconditioners]. The stopping criterion is given by [Tolerance] and the solver returns the [Convergence] reached.
+
+	Each solver has a `_with_workspace` variant like [cg_with_workspace] drawing its vectors from a [Workspace] instead of allocating them, the memory drawn is given by a function like [cg_workspace_size]
+*/
+
+use crate::prelude::*;
+use crate::matrix::*;
+use crate::matrices::*;
+use crate::operators::*;
+use crate::sparse::{Csc, Csr};
+use crate::workspace::{Workspace, workspace_size};
+
+use core::{
+	iter::zip,
+	marker::PhantomData,
+	ops::DerefMut,
+	};
+use num_traits::Float;
+
+pub mod preconditioners;
+pub use self::preconditioners::{Preconditioner, Identity, Jacobi, IncompleteCholesky, Ilu0};
+
+
+/// linear map `x -> A x` from vectors to vectors, only knowing how to apply it is enough for iterative solvers
+pub trait LinearOperator<T: Element> {
+	/// shape of the operator as a matrix
+	fn shape(&self) -> [usize; 2];
+	/// compute `y = A x`
+	fn apply(&self, x: &VectorView<'_, T>, y: &mut VectorViewMut<'_, T>);
+}
+
+impl<A> LinearOperator<A::Element> for Matrix<A>
+where
+	A: Array,
+	A::Element: Scalar,
+{
+	fn shape(&self) -> [usize; 2]  {Matrix::shape(self)}
+	fn apply(&self, x: &VectorView<'_, A::Element>, y: &mut VectorViewMut<'_, A::Element>) {
+		// the product needs the output to have the same row dimension type as the matrix
+		let mut out = Matrix(ViewMut {
+			shape: (A::R::check(y.rows()).expect("output size doesn't match the operator"), Stat::<1> {}),
+			strides: (y.strides()[0], y.strides()[1]),
+			data: y.as_mut_ptr(),
+			lifetime: PhantomData,
+		});
+		self.mul_to(x, &mut out);
+	}
+}
+impl<T: Scalar> LinearOperator<T> for Csc<T> {
+	fn shape(&self) -> [usize; 2]  {Csc::shape(self)}
+	fn apply(&self, x: &VectorView<'_, T>, y: &mut VectorViewMut<'_, T>)  {self.mul_to(x, y);}
+}
+impl<T: Scalar> LinearOperator<T> for Csr<T> {
+	fn shape(&self) -> [usize; 2]  {Csr::shape(self)}
+	fn apply(&self, x: &VectorView<'_, T>, y: &mut VectorViewMut<'_, T>)  {self.mul_to(x, y);}
+}
+
+/// linear operator defined by a closure, see [from_fn]
+#[derive(Copy, Clone, Debug)]
+pub struct FnOperator<F> {
+	shape: [usize; 2],
+	function: F,
+}
+/// linear operator of the given shape, applied by a closure computing `y = A x`
+pub fn from_fn<T, F>(shape: [usize; 2], function: F) -> FnOperator<F>
+where
+	T: Element,
+	F: Fn(&VectorView<'_, T>, &mut VectorViewMut<'_, T>),
+{
+	FnOperator {shape, function}
+}
+impl<T, F> LinearOperator<T> for FnOperator<F>
+where
+	T: Element,
+	F: Fn(&VectorView<'_, T>, &mut VectorViewMut<'_, T>),
+{
+	fn shape(&self) -> [usize; 2]  {self.shape}
+	fn apply(&self, x: &VectorView<'_, T>, y: &mut VectorViewMut<'_, T>)  {(self.function)(x, y)}
+}
+
+
+/// stopping criterion of iterative solvers: they stop when `|b - A x| <= max(relative |b|, absolute)` or after too many iterations
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct Tolerance<T> {
+	/// residual norm relative to the norm of `b`
+	pub relative: T,
+	/// absolute residual norm
+	pub absolute: T,
+	/// maximum number of iterations, counting every operator application in [gmres]
+	pub max_iterations: usize,
+	/// record the residual norm of every iteration in [Convergence::history]
+	pub history: bool,
+}
+impl<T: Float> Tolerance<T> {
+	/// relative tolerance only, with at most 1000 iterations
+	pub fn new(relative: T) -> Self  {Self {
+		relative,
+		absolute: T::zero(),
+		max_iterations: 1000,
+		history: false,
+	}}
+	/// change the absolute tolerance
+	pub fn absolute(self, absolute: T) -> Self  {Self {absolute, ..self}}
+	/// change the maximum number of iterations
+	pub fn max_iterations(self, max_iterations: usize) -> Self  {Self {max_iterations, ..self}}
+	/// enable or disable the residuals history
+	pub fn history(self, history: bool) -> Self  {Self {history, ..self}}
+}
+impl<T: Float> Default for Tolerance<T> {
+	/// relative tolerance of `1e-8`
+	fn default() -> Self  {Self::new(T::from(1e-8).unwrap())}
+}
+
+/// outcome of an iterative solver
+#[derive(Clone, Debug, PartialEq)]
+pub struct Convergence<T> {
+	/// whether the tolerance was reached
+	pub converged: bool,
+	/// number of iterations done
+	pub iterations: usize,
+	/// residual norm at the last iteration
+	pub residual: T,
+	/// residual norm before each iteration and after the last one, if requested by [Tolerance::history]
+	pub history: Vec<T>,
+}
+
+/// iterations bookkeeping common to all solvers
+struct Monitor<'t, T> {
+	tolerance: &'t Tolerance<T>,
+	threshold: T,
+	convergence: Convergence<T>,
+}
+impl<'t, T: Float> Monitor<'t, T> {
+	fn new(tolerance: &'t Tolerance<T>, reference: T) -> Self  {Self {
+		tolerance,
+		threshold: (tolerance.relative * reference).max(tolerance.absolute),
+		convergence: Convergence {
+			converged: false,
+			iterations: 0,
+			residual: T::infinity(),
+			history: Vec::new(),
+		},
+	}}
+	/// record the current residual, returning `true` if the solver should stop
+	fn stop(&mut self, residual: T) -> bool {
+		self.convergence.residual = residual;
+		if self.tolerance.history  {self.convergence.history.push(residual)}
+		self.convergence.converged = residual <= self.threshold;
+		self.convergence.converged || self.convergence.iterations >= self.tolerance.max_iterations
+	}
+	fn iterate(&mut self)  {self.convergence.iterations += 1}
+}
+
+
+/**
+	preconditioned conjugate gradient, for symmetric positive definite operators and preconditioners.
+
+	`x` is the initial guess and receives the solution
+*/
+pub fn cg<T, O, P, B, X>(operator: &O, preconditioner: &P, b: &Matrix<B>, x: &mut Matrix<X>, tolerance: &Tolerance<T>) -> Convergence<T>
+where
+	T: Scalar + Float,
+	O: LinearOperator<T> + ?Sized,
+	P: Preconditioner<T> + ?Sized,
+	B: Array<Element=T>,
+	X: ArrayMut<Element=T>,
+{
+	cg_in(operator, preconditioner, b, x, tolerance, |len|  vec![T::zero(); len])
+}
+/// same as [cg] but drawing its vectors from the workspace, the residuals history still allocates if requested
+pub fn cg_with_workspace<T, O, P, B, X>(operator: &O, preconditioner: &P, b: &Matrix<B>, x: &mut Matrix<X>, tolerance: &Tolerance<T>, workspace: &Workspace) -> Convergence<T>
+where
+	T: Scalar + Float,
+	O: LinearOperator<T> + ?Sized,
+	P: Preconditioner<T> + ?Sized,
+	B: Array<Element=T>,
+	X: ArrayMut<Element=T>,
+{
+	cg_in(operator, preconditioner, b, x, tolerance, |len|  workspace.slice(len, T::zero()))
+}
+/// bytes of workspace drawn by [cg_with_workspace] for a system of the given size with the given preconditioner
+pub fn cg_workspace_size<T: Element, P: Preconditioner<T> + ?Sized>(preconditioner: &P, size: usize) -> usize {
+	6 * workspace_size::<T>([size, 1]) + workspace_size::<T>([preconditioner.buffer_len(), 1])
+}
+fn cg_in<T, O, P, B, X, V>(operator: &O, preconditioner: &P, b: &Matrix<B>, x: &mut Matrix<X>, tolerance: &Tolerance<T>, mut zeros: impl FnMut(usize) -> V) -> Convergence<T>
+where
+	T: Scalar + Float,
+	O: LinearOperator<T> + ?Sized,
+	P: Preconditioner<T> + ?Sized,
+	B: Array<Element=T>,
+	X: ArrayMut<Element=T>,
+	V: DerefMut<Target=[T]>,
+{
+	let size = check_system(operator, b, x);
+	let (b, mut x_) = (gather(b, zeros(size)), gather(x, zeros(size)));
+	let mut buffer = zeros(preconditioner.buffer_len());
+	let mut monitor = Monitor::new(tolerance, norm(&b));
+	let mut r = zeros(size);
+	residual(operator, &b, &x_, &mut r);
+	let mut z = zeros(size);
+	let mut q = zeros(size);
+	precondition(preconditioner, &r, &mut z, &mut buffer);
+	let mut p = zeros(size);
+	p.copy_from_slice(&z);
+	let mut rz = dot(&r, &z);
+
+	while !monitor.stop(norm(&r)) {
+		monitor.iterate();
+		apply(operator, &p, &mut q);
+		let curvature = dot(&p, &q);
+		// the operator is not positive definite
+		if curvature.is_nan() || curvature <= T::zero()  {break}
+		let alpha = rz / curvature;
+		axpy(alpha, &p, &mut x_);
+		axpy(-alpha, &q, &mut r);
+		precondition(preconditioner, &r, &mut z, &mut buffer);
+		let next = dot(&r, &z);
+		let beta = next / rz;
+		rz = next;
+		for (p, &z) in zip(p.iter_mut(), z.iter())  {*p = z + beta * *p}
+	}
+	scatter(&x_, x);
+	monitor.convergence
+}
+
+/**
+	preconditioned minimal residual method, for symmetric (possibly indefinite) operators with symmetric positive definite preconditioners.
+
+	The residual norm used for the stopping criterion is the estimate given by the Lanczos process, measured in the norm induced by the preconditioner (which is the euclidean norm without preconditioning). `x` is the initial guess and receives the solution
+*/
+pub fn minres<T, O, P, B, X>(operator: &O, preconditioner: &P, b: &Matrix<B>, x: &mut Matrix<X>, tolerance: &Tolerance<T>) -> Convergence<T>
+where
+	T: Scalar + Float,
+	O: LinearOperator<T> + ?Sized,
+	P: Preconditioner<T> + ?Sized,
+	B: Array<Element=T>,
+	X: ArrayMut<Element=T>,
+{
+	minres_in(operator, preconditioner, b, x, tolerance, |len|  vec![T::zero(); len])
+}
+/// same as [minres] but drawing its vectors from the workspace, the residuals history still allocates if requested
+pub fn minres_with_workspace<T, O, P, B, X>(operator: &O, preconditioner: &P, b: &Matrix<B>, x: &mut Matrix<X>, tolerance: &Tolerance<T>, workspace: &Workspace) -> Convergence<T>
+where
+	T: Scalar + Float,
+	O: LinearOperator<T> + ?Sized,
+	P: Preconditioner<T> + ?Sized,
+	B: Array<Element=T>,
+	X: ArrayMut<Element=T>,
+{
+	minres_in(operator, preconditioner, b, x, tolerance, |len|  workspace.slice(len, T::zero()))
+}
+/// bytes of workspace drawn by [minres_with_workspace] for a system of the given size with the given preconditioner
+pub fn minres_workspace_size<T: Element, P: Preconditioner<T> + ?Sized>(preconditioner: &P, size: usize) -> usize {
+	9 * workspace_size::<T>([size, 1]) + workspace_size::<T>([preconditioner.buffer_len(), 1])
+}
+fn minres_in<T, O, P, B, X, V>(operator: &O, preconditioner: &P, b: &Matrix<B>, x: &mut Matrix<X>, tolerance: &Tolerance<T>, mut zeros: impl FnMut(usize) -> V) -> Convergence<T>
+where
+	T: Scalar + Float,
+	O: LinearOperator<T> + ?Sized,
+	P: Preconditioner<T> + ?Sized,
+	B: Array<Element=T>,
+	X: ArrayMut<Element=T>,
+	V: DerefMut<Target=[T]>,
+{
+	let size = check_system(operator, b, x);
+	let (b, mut x_) = (gather(b, zeros(size)), gather(x, zeros(size)));
+	let mut buffer = zeros(preconditioner.buffer_len());
+	let mut y = zeros(size);
+	precondition(preconditioner, &b, &mut y, &mut buffer);
+	let mut monitor = Monitor::new(tolerance, dot(&b, &y).max(T::zero()).sqrt());
+
+	// Lanczos vectors
+	let mut r1 = zeros(size);
+	residual(operator, &b, &x_, &mut r1);
+	precondition(preconditioner, &r1, &mut y, &mut buffer);
+	let mut r2 = zeros(size);
+	r2.copy_from_slice(&r1);
+	let mut v = zeros(size);
+	let mut beta = dot(&r1, &y).sqrt();
+	let mut old_beta = T::zero();
+	// QR factorization of the tridiagonal Lanczos matrix by Givens rotations
+	let (mut cs, mut sn) = (-T::one(), T::zero());
+	let (mut dbar, mut epsilon) = (T::zero(), T::zero());
+	let mut phibar = beta;
+	// search directions
+	let mut w = zeros(size);
+	let mut w1 = zeros(size);
+	let mut w2 = zeros(size);
+
+	while !monitor.stop(phibar) {
+		monitor.iterate();
+		for (v, &y) in zip(v.iter_mut(), y.iter())  {*v = y / beta}
+		apply(operator, &v, &mut y);
+		if monitor.convergence.iterations > 1  {axpy(-beta / old_beta, &r1, &mut y)}
+		let alpha = dot(&v, &y);
+		axpy(-alpha / beta, &r2, &mut y);
+		core::mem::swap(&mut r1, &mut r2);
+		r2.copy_from_slice(&y);
+		precondition(preconditioner, &r2, &mut y, &mut buffer);
+		old_beta = beta;
+		beta = dot(&r2, &y);
+		// the preconditioner is not positive definite
+		if beta.is_nan() || beta < T::zero()  {break}
+		beta = beta.sqrt();
+
+		let old_epsilon = epsilon;
+		let delta = cs * dbar + sn * alpha;
+		let gbar = sn * dbar - cs * alpha;
+		epsilon = sn * beta;
+		dbar = -cs * beta;
+		let gamma = gbar.hypot(beta).max(T::epsilon());
+		cs = gbar / gamma;
+		sn = beta / gamma;
+		let phi = cs * phibar;
+		phibar = sn * phibar;
+
+		core::mem::swap(&mut w1, &mut w2);
+		core::mem::swap(&mut w2, &mut w);
+		for ((w, &v), (&w1, &w2)) in zip(zip(w.iter_mut(), v.iter()), zip(w1.iter(), w2.iter())) {
+			*w = (v - old_epsilon * w1 - delta * w2) / gamma;
+		}
+		axpy(phi, &w, &mut x_);
+		// invariant subspace found, the solution is exact
+		if beta == T::zero()  {phibar = T::zero()}
+	}
+	scatter(&x_, x);
+	monitor.convergence
+}
+
+/**
+	stabilized bi-conjugate gradient with right preconditioning, for unsymmetric operators.
+
+	`x` is the initial guess and receives the solution
+*/
+pub fn bicgstab<T, O, P, B, X>(operator: &O, preconditioner: &P, b: &Matrix<B>, x: &mut Matrix<X>, tolerance: &Tolerance<T>) -> Convergence<T>
+where
+	T: Scalar + Float,
+	O: LinearOperator<T> + ?Sized,
+	P: Preconditioner<T> + ?Sized,
+	B: Array<Element=T>,
+	X: ArrayMut<Element=T>,
+{
+	bicgstab_in(operator, preconditioner, b, x, tolerance, |len|  vec![T::zero(); len])
+}
+/// same as [bicgstab] but drawing its vectors from the workspace, the residuals history still allocates if requested
+pub fn bicgstab_with_workspace<T, O, P, B, X>(operator: &O, preconditioner: &P, b: &Matrix<B>, x: &mut Matrix<X>, tolerance: &Tolerance<T>, workspace: &Workspace) -> Convergence<T>
+where
+	T: Scalar + Float,
+	O: LinearOperator<T> + ?Sized,
+	P: Preconditioner<T> + ?Sized,
+	B: Array<Element=T>,
+	X: ArrayMut<Element=T>,
+{
+	bicgstab_in(operator, preconditioner, b, x, tolerance, |len|  workspace.slice(len, T::zero()))
+}
+/// bytes of workspace drawn by [bicgstab_with_workspace] for a system of the given size with the given preconditioner
+pub fn bicgstab_workspace_size<T: Element, P: Preconditioner<T> + ?Sized>(preconditioner: &P, size: usize) -> usize {
+	8 * workspace_size::<T>([size, 1]) + workspace_size::<T>([preconditioner.buffer_len(), 1])
+}
+fn bicgstab_in<T, O, P, B, X, V>(operator: &O, preconditioner: &P, b: &Matrix<B>, x: &mut Matrix<X>, tolerance: &Tolerance<T>, mut zeros: impl FnMut(usize) -> V) -> Convergence<T>
+where
+	T: Scalar + Float,
+	O: LinearOperator<T> + ?Sized,
+	P: Preconditioner<T> + ?Sized,
+	B: Array<Element=T>,
+	X: ArrayMut<Element=T>,
+	V: DerefMut<Target=[T]>,
+{
+	let size = check_system(operator, b, x);
+	let (b, mut x_) = (gather(b, zeros(size)), gather(x, zeros(size)));
+	let mut buffer = zeros(preconditioner.buffer_len());
+	let mut monitor = Monitor::new(tolerance, norm(&b));
+	let mut r = zeros(size);
+	residual(operator, &b, &x_, &mut r);
+	let mut shadow = zeros(size);
+	shadow.copy_from_slice(&r);
+	let (mut rho, mut alpha, mut omega) = (T::one(), T::one(), T::one());
+	let mut p = zeros(size);
+	let mut v = zeros(size);
+	let mut y = zeros(size);
+	let mut t = zeros(size);
+
+	while !monitor.stop(norm(&r)) {
+		monitor.iterate();
+		let next = dot(&shadow, &r);
+		// breakdown of the bi-orthogonalization
+		if next == T::zero() || omega == T::zero()  {break}
+		let beta = (next / rho) * (alpha / omega);
+		rho = next;
+		for ((p, &r), &v) in zip(zip(p.iter_mut(), r.iter()), v.iter())  {*p = r + beta * (*p - omega * v)}
+		precondition(preconditioner, &p, &mut y, &mut buffer);
+		apply(operator, &y, &mut v);
+		alpha = rho / dot(&shadow, &v);
+		axpy(alpha, &y, &mut x_);
+		// r becomes the intermediate residual
+		axpy(-alpha, &v, &mut r);
+		if norm(&r) <= monitor.threshold  {continue}
+		precondition(preconditioner, &r, &mut y, &mut buffer);
+		apply(operator, &y, &mut t);
+		omega = dot(&t, &r) / dot(&t, &t);
+		axpy(omega, &y, &mut x_);
+		axpy(-omega, &t, &mut r);
+	}
+	scatter(&x_, x);
+	monitor.convergence
+}
+
+/**
+	restarted generalized minimal residual with right preconditioning, for unsymmetric operators.
+
+	The Krylov basis is restarted after `restart` iterations, bounding the memory to `restart+1` vectors. `x` is the initial guess and receives the solution
+*/
+pub fn gmres<T, O, P, B, X>(operator: &O, preconditioner: &P, b: &Matrix<B>, x: &mut Matrix<X>, restart: usize, tolerance: &Tolerance<T>) -> Convergence<T>
+where
+	T: Scalar + Float,
+	O: LinearOperator<T> + ?Sized,
+	P: Preconditioner<T> + ?Sized,
+	B: Array<Element=T>,
+	X: ArrayMut<Element=T>,
+{
+	gmres_in(operator, preconditioner, b, x, restart, tolerance, |len|  vec![T::zero(); len])
+}
+/// same as [gmres] but drawing its vectors from the workspace, the residuals history still allocates if requested
+pub fn gmres_with_workspace<T, O, P, B, X>(operator: &O, preconditioner: &P, b: &Matrix<B>, x: &mut Matrix<X>, restart: usize, tolerance: &Tolerance<T>, workspace: &Workspace) -> Convergence<T>
+where
+	T: Scalar + Float,
+	O: LinearOperator<T> + ?Sized,
+	P: Preconditioner<T> + ?Sized,
+	B: Array<Element=T>,
+	X: ArrayMut<Element=T>,
+{
+	gmres_in(operator, preconditioner, b, x, restart, tolerance, |len|  workspace.slice(len, T::zero()))
+}
+/// bytes of workspace drawn by [gmres_with_workspace] for a system of the given size with the given preconditioner
+pub fn gmres_workspace_size<T: Element, P: Preconditioner<T> + ?Sized>(preconditioner: &P, size: usize, restart: usize) -> usize {
+	5 * workspace_size::<T>([size, 1])
+	+ workspace_size::<T>([preconditioner.buffer_len(), 1])
+	+ workspace_size::<T>([(restart+1) * size, 1])
+	+ workspace_size::<T>([restart * (restart+1), 1])
+	+ 2 * workspace_size::<T>([restart, 1])
+	+ workspace_size::<T>([restart+1, 1])
+}
+fn gmres_in<T, O, P, B, X, V>(operator: &O, preconditioner: &P, b: &Matrix<B>, x: &mut Matrix<X>, restart: usize, tolerance: &Tolerance<T>, mut zeros: impl FnMut(usize) -> V) -> Convergence<T>
+where
+	T: Scalar + Float,
+	O: LinearOperator<T> + ?Sized,
+	P: Preconditioner<T> + ?Sized,
+	B: Array<Element=T>,
+	X: ArrayMut<Element=T>,
+	V: DerefMut<Target=[T]>,
+{
+	assert!(restart > 0, "restart must be positive");
+	let size = check_system(operator, b, x);
+	let (b, mut x_) = (gather(b, zeros(size)), gather(x, zeros(size)));
+	let mut buffer = zeros(preconditioner.buffer_len());
+	let mut monitor = Monitor::new(tolerance, norm(&b));
+	// basis vectors one after the other
+	let mut basis = zeros((restart+1) * size);
+	let vector = |i: usize|  i * size .. (i+1) * size;
+	// columns of the Hessenberg matrix one after the other, triangularized by Givens rotations
+	let mut hessenberg = zeros(restart * (restart+1));
+	let mut cosines = zeros(restart);
+	let mut sines = zeros(restart);
+	let mut g = zeros(restart+1);
+	let mut z = zeros(size);
+	let mut w = zeros(size);
+
+	let mut r = zeros(size);
+	residual(operator, &b, &x_, &mut r);
+	let mut beta = norm(&r);
+	// the Krylov space stopped growing while the residual cannot be reduced in it, so restarting wouldn't progress
+	let mut breakdown = false;
+	while !monitor.stop(beta) && !breakdown {
+		for (v, &r) in zip(&mut basis[vector(0)], r.iter())  {*v = r / beta}
+		g.fill(T::zero());
+		g[0] = beta;
+
+		// Arnoldi process
+		let mut steps = 0;
+		while steps < restart {
+			let j = steps;
+			monitor.iterate();
+			steps += 1;
+			precondition(preconditioner, &basis[vector(j)], &mut z, &mut buffer);
+			apply(operator, &z, &mut w);
+			let column = &mut hessenberg[j * (restart+1) ..][.. restart+1];
+			for i in 0 ..= j {
+				let v = &basis[vector(i)];
+				column[i] = dot(&w, v);
+				axpy(-column[i], v, &mut w);
+			}
+			column[j+1] = norm(&w);
+			let lucky = column[j+1] == T::zero();
+			if !lucky {
+				let scale = column[j+1];
+				for (v, &w) in zip(&mut basis[vector(j+1)], w.iter())  {*v = w / scale}
+			}
+			for (i, (&c, &s)) in zip(&cosines[.. j], &sines[.. j]).enumerate() {
+				let (a, b) = (column[i], column[i+1]);
+				column[i] = c * a + s * b;
+				column[i+1] = c * b - s * a;
+			}
+			let d = column[j].hypot(column[j+1]);
+			if d == T::zero() {
+				// singular Hessenberg matrix: keep the identity rotation and leave this step out of the least squares solution
+				(cosines[j], sines[j]) = (T::one(), T::zero());
+				steps -= 1;
+				breakdown = true;
+				break;
+			}
+			let (c, s) = (column[j] / d, column[j+1] / d);
+			(cosines[j], sines[j]) = (c, s);
+			column[j] = d;
+			column[j+1] = T::zero();
+			g[j+1] = -s * g[j];
+			g[j] = c * g[j];
+
+			let estimate = g[j+1].abs();
+			if lucky || estimate <= monitor.threshold || monitor.convergence.iterations >= tolerance.max_iterations
+				{break}
+			if steps < restart && tolerance.history
+				{monitor.convergence.history.push(estimate)}
+		}
+
+		// update the solution with the least squares solution in the Krylov space
+		for i in (0 .. steps).rev() {
+			let column = &hessenberg[i * (restart+1) ..];
+			g[i] = g[i] / column[i];
+			for k in 0 .. i  {g[k] = g[k] - column[k] * g[i]}
+		}
+		w.fill(T::zero());
+		for (i, &y) in g[.. steps].iter().enumerate()  {axpy(y, &basis[vector(i)], &mut w)}
+		precondition(preconditioner, &w, &mut z, &mut buffer);
+		axpy(T::one(), &z, &mut x_);
+
+		residual(operator, &b, &x_, &mut r);
+		beta = norm(&r);
+	}
+	scatter(&x_, x);
+	monitor.convergence
+}
+
+
+/// check the system is square with matching vectors, returning its size
+fn check_system<T, O, B, X>(operator: &O, b: &Matrix<B>, x: &Matrix<X>) -> usize
+where
+	T: Element,
+	O: LinearOperator<T> + ?Sized,
+	B: Array<Element=T>,
+	X: Array<Element=T>,
+{
+	let [rows, columns] = operator.shape();
+	assert_eq!(rows, columns, "operator must be square");
+	assert_eq!(b.shape(), [rows, 1], "right hand side doesn't match the operator");
+	assert_eq!(x.shape(), [rows, 1], "solution doesn't match the operator");
+	rows
+}
+/// copy the vector into the given buffer
+fn gather<A: Array, V: DerefMut<Target=[A::Element]>>(vector: &Matrix<A>, mut into: V) -> V {
+	for (i, dst) in into.iter_mut().enumerate()  {*dst = vector[[i,0]].clone()}
+	into
+}
+fn scatter<A: ArrayMut>(src: &[A::Element], vector: &mut Matrix<A>) {
+	vector.set_field(|[i,_]|  src[i].clone());
+}
+fn view<T: Element>(vector: &[T]) -> VectorView<'_, T> {
+	VectorView::try_from_slice([vector.len(), 1], vector).unwrap()
+}
+fn view_mut<T: Element>(vector: &mut [T]) -> VectorViewMut<'_, T> {
+	VectorViewMut::try_from_slice([vector.len(), 1], vector).unwrap()
+}
+fn apply<T: Element, O: LinearOperator<T> + ?Sized>(operator: &O, x: &[T], y: &mut [T]) {
+	operator.apply(&view(x), &mut view_mut(y))
+}
+fn precondition<T: Element, P: Preconditioner<T> + ?Sized>(preconditioner: &P, r: &[T], z: &mut [T], buffer: &mut [T]) {
+	preconditioner.precondition_buffer(&view(r), &mut view_mut(z), buffer)
+}
+/// `r = b - A x`
+fn residual<T: Float + Element, O: LinearOperator<T> + ?Sized>(operator: &O, b: &[T], x: &[T], r: &mut [T]) {
+	apply(operator, x, r);
+	for (r, &b) in zip(r, b)  {*r = b - *r}
+}
+fn dot<T: Float>(a: &[T], b: &[T]) -> T {
+	zip(a, b).fold(T::zero(), |sum, (&a, &b)|  sum + a*b)
+}
+fn norm<T: Float>(a: &[T]) -> T  {dot(a, a).sqrt()}
+/// `y += alpha x`
+fn axpy<T: Float>(alpha: T, x: &[T], y: &mut [T]) {
+	for (y, &x) in zip(y, x)  {*y = *y + alpha * x}
+}
+
+
+#[test]
+fn test_krylov() {
+	// 1D laplacian, symmetric positive definite
+	let size = 30;
+	let mut coo = crate::sparse::Coo::new([size, size]);
+	for i in 0 .. size {
+		coo.push([i,i], 2.);
+		if i > 0  {coo.push([i,i-1], -1.);}
+		if i+1 < size  {coo.push([i,i+1], -1.);}
+	}
+	let laplacian = coo.to_csc();
+	let dense = laplacian.to_dense();
+	let b = DVector::<f64>::field(size, |[i,_]|  (i % 7) as f64 - 3.);
+	let tolerance = Tolerance::new(1e-10).history(true);
+	let error = |operator: &dyn LinearOperator<f64>, x: &DVector<f64>| {
+		let mut r = DVector::<f64>::zeros(size);
+		operator.apply(&x.view(), &mut r.view_mut());
+		(&r - &b).as_slice().unwrap().iter().fold(0., |m: f64, v|  m.max(v.abs()))
+	};
+
+	let mut x = DVector::<f64>::zeros(size);
+	let convergence = cg(&laplacian, &Identity, &b, &mut x, &tolerance);
+	assert!(convergence.converged);
+	assert!(convergence.iterations <= size);
+	assert_eq!(convergence.history.len(), convergence.iterations+1);
+	assert!(error(&laplacian, &x) < 1e-8);
+
+	let mut x = DVector::<f64>::zeros(size);
+	let preconditioner = IncompleteCholesky::new(&laplacian).unwrap();
+	let convergence = cg(&dense, &preconditioner, &b, &mut x, &tolerance);
+	// the tridiagonal matrix has no fill-in so the incomplete factorization is exact
+	assert!(convergence.converged && convergence.iterations <= 2);
+	assert!(error(&dense, &x) < 1e-8);
+
+	// matrix-free operator
+	let operator = from_fn([size, size], |x: &VectorView<f64>, y: &mut VectorViewMut<f64>| {
+		y.set_field(|[i,_]|  2.*x[[i,0]]
+			- if i > 0 {x[[i-1,0]]} else {0.}
+			- if i+1 < size {x[[i+1,0]]} else {0.});
+	});
+	let mut x = DVector::<f64>::zeros(size);
+	assert!(minres(&operator, &Jacobi::new(&laplacian), &b, &mut x, &tolerance).converged);
+	assert!(error(&operator, &x) < 1e-8);
+
+	// symmetric indefinite
+	let mut indefinite = laplacian.clone();
+	indefinite.values_mut().iter_mut().for_each(|v|  if *v == 2. {*v = 0.5});
+	let mut x = DVector::<f64>::zeros(size);
+	assert!(minres(&indefinite, &Identity, &b, &mut x, &tolerance).converged);
+	assert!(error(&indefinite, &x) < 1e-8);
+
+	// unsymmetric
+	let mut coo = crate::sparse::Coo::new([size, size]);
+	for i in 0 .. size {
+		coo.push([i,i], 4.);
+		if i > 0  {coo.push([i,i-1], -2.);}
+		if i+1 < size  {coo.push([i,i+1], -1.);}
+		if i+5 < size  {coo.push([i,i+5], 0.5);}
+	}
+	let unsymmetric = coo.to_csr();
+	let mut x = DVector::<f64>::zeros(size);
+	assert!(bicgstab(&unsymmetric, &Identity, &b, &mut x, &tolerance).converged);
+	assert!(error(&unsymmetric, &x) < 1e-8);
+	let mut x = DVector::<f64>::zeros(size);
+	let convergence = gmres(&unsymmetric, &Identity, &b, &mut x, 5, &tolerance);
+	assert!(convergence.converged);
+	assert!(error(&unsymmetric, &x) < 1e-8);
+	let ilu = Ilu0::new(&unsymmetric.to_csc()).unwrap();
+	let mut x = DVector::<f64>::zeros(size);
+	let preconditioned = gmres(&unsymmetric, &ilu, &b, &mut x, 5, &tolerance);
+	assert!(preconditioned.converged && preconditioned.iterations < convergence.iterations);
+	assert!(error(&unsymmetric, &x) < 1e-8);
+	let mut x = DVector::<f64>::zeros(size);
+	assert!(bicgstab(&unsymmetric, &ilu, &b, &mut x, &tolerance).converged);
+	assert!(error(&unsymmetric, &x) < 1e-8);
+
+	// workspace variants do the same iterations without allocating
+	let restart = 5;
+	let tolerance = Tolerance::new(1e-10);
+	// complete factorizations as preconditioners get their buffer from the workspace too
+	let lu = crate::sparse::SparseLu::new(&unsymmetric.to_csc(), &Default::default()).unwrap();
+	let workspace = Workspace::new(cg_workspace_size::<f64, _>(&Identity, size) + minres_workspace_size::<f64, _>(&Identity, size)
+		+ bicgstab_workspace_size(&ilu, size) + gmres_workspace_size(&ilu, size, restart)
+		+ gmres_workspace_size(&lu, size, restart));
+	let (mut x, mut y) = (DVector::<f64>::zeros(size), DVector::<f64>::zeros(size));
+	assert_eq!(cg_with_workspace(&laplacian, &Identity, &b, &mut x, &tolerance, &workspace), cg(&laplacian, &Identity, &b, &mut y, &tolerance));
+	assert_eq!(x.as_slice(), y.as_slice());
+	let (mut x, mut y) = (DVector::<f64>::zeros(size), DVector::<f64>::zeros(size));
+	assert_eq!(minres_with_workspace(&indefinite, &Identity, &b, &mut x, &tolerance, &workspace), minres(&indefinite, &Identity, &b, &mut y, &tolerance));
+	assert_eq!(x.as_slice(), y.as_slice());
+	let (mut x, mut y) = (DVector::<f64>::zeros(size), DVector::<f64>::zeros(size));
+	assert_eq!(bicgstab_with_workspace(&unsymmetric, &ilu, &b, &mut x, &tolerance, &workspace), bicgstab(&unsymmetric, &ilu, &b, &mut y, &tolerance));
+	assert_eq!(x.as_slice(), y.as_slice());
+	let (mut x, mut y) = (DVector::<f64>::zeros(size), DVector::<f64>::zeros(size));
+	assert_eq!(gmres_with_workspace(&unsymmetric, &ilu, &b, &mut x, restart, &tolerance, &workspace), gmres(&unsymmetric, &ilu, &b, &mut y, restart, &tolerance));
+	assert_eq!(x.as_slice(), y.as_slice());
+	let (mut x, mut y) = (DVector::<f64>::zeros(size), DVector::<f64>::zeros(size));
+	let convergence = gmres_with_workspace(&unsymmetric, &lu, &b, &mut x, restart, &tolerance, &workspace);
+	assert!(convergence.converged && convergence.iterations <= 1);
+	assert_eq!(convergence, gmres(&unsymmetric, &lu, &b, &mut y, restart, &tolerance));
+	assert_eq!(x.as_slice(), y.as_slice());
+	assert!(workspace.used() <= workspace.capacity());
+
+	let mut x = DVector::<f64>::zeros(size);
+	let convergence = cg(&laplacian, &Identity, &b, &mut x, &Tolerance::new(1e-10).max_iterations(3));
+	assert!(!convergence.converged);
+	assert_eq!(convergence.iterations, 3);
+
+	// the right hand side is in the kernel of a singular operator
+	let mut coo = crate::sparse::Coo::new([2, 2]);
+	coo.push([0,0], 1.);
+	let singular = coo.to_csr();
+	let mut x = DVector::<f64>::zeros(2);
+	let convergence = gmres(&singular, &Identity, &DVector::from(vec![0., 1.]), &mut x, 5, &tolerance);
+	assert!(!convergence.converged && convergence.iterations == 1);
+	assert!(x.iter().all(|x|  x.is_finite()));
+}
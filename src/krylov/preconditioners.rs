@@ -0,0 +1,227 @@
+/*!
+	Preconditioners approximating `A⁻¹` to accelerate the convergence of iterative solvers
+
+	- [Identity] is no preconditioning
+	- [Jacobi] scales by the inverse diagonal, it is cheap but only helps with badly scaled systems
+	- [IncompleteCholesky] is a Cholesky factorization without fill-in, for symmetric positive definite systems
+	- [Ilu0] is an LU factorization without fill-in, for unsymmetric systems
+
+	Complete sparse factorizations like [SparseCholesky] and [SparseLu] can also be used as exact preconditioners.
+*/
+
+use crate::prelude::*;
+use crate::matrix::*;
+use crate::matrices::*;
+use crate::sparse::{Csc, SparseCholesky, SparseLu, FactorizationError};
+
+use core::iter::zip;
+use num_traits::Float;
+
+
+/// approximation of the inverse of an operator
+pub trait Preconditioner<T: Element> {
+	/// compute `z = M⁻¹ r`
+	fn precondition(&self, r: &VectorView<'_, T>, z: &mut VectorViewMut<'_, T>);
+	/// number of elements of the buffer needed by [Self::precondition_buffer]
+	fn buffer_len(&self) -> usize  {0}
+	/// same as [Self::precondition] but using a buffer of [Self::buffer_len] elements for its temporaries, so that solvers can allocate it once for all their iterations
+	fn precondition_buffer(&self, r: &VectorView<'_, T>, z: &mut VectorViewMut<'_, T>, _buffer: &mut [T]) {
+		self.precondition(r, z)
+	}
+}
+
+/// no preconditioning
+#[derive(Copy, Clone, Debug, Default)]
+pub struct Identity;
+impl<T: Element> Preconditioner<T> for Identity {
+	fn precondition(&self, r: &VectorView<'_, T>, z: &mut VectorViewMut<'_, T>) {
+		z.set_field(|index|  r[index].clone());
+	}
+}
+
+/// diagonal preconditioner `M = diag(A)`
+#[derive(Clone, Debug)]
+pub struct Jacobi<T> {
+	inverse: Vec<T>,
+}
+impl<T: Scalar + Float> Jacobi<T> {
+	/// use the diagonal of the given sparse matrix, missing or zero diagonal elements are left unscaled
+	pub fn new(matrix: &Csc<T>) -> Self {
+		Self::from_diagonal((0 .. matrix.rows().min(matrix.columns()))
+			.map(|i|  matrix.get([i,i]).copied().unwrap_or(T::zero())))
+	}
+	/// use the diagonal of the given dense matrix, zero diagonal elements are left unscaled
+	pub fn from_dense<A: Array<Element=T>>(matrix: &Matrix<A>) -> Self {
+		Self::from_diagonal((0 .. matrix.rows().min(matrix.columns())).map(|i|  matrix[[i,i]]))
+	}
+	/// use the given diagonal, zero elements are left unscaled
+	pub fn from_diagonal(diagonal: impl IntoIterator<Item=T>) -> Self {
+		Self {inverse: diagonal.into_iter()
+			.map(|d|  if d == T::zero() {T::one()} else {d.recip()})
+			.collect()}
+	}
+}
+impl<T: Scalar + Float> Preconditioner<T> for Jacobi<T> {
+	fn precondition(&self, r: &VectorView<'_, T>, z: &mut VectorViewMut<'_, T>) {
+		z.set_field(|[i,_]|  r[[i,0]] * self.inverse[i]);
+	}
+}
+
+/// incomplete Cholesky factorization `A ≈ L Lᵀ` where `L` has the pattern of the lower triangle of `A` (known as IC(0))
+#[derive(Clone, Debug)]
+pub struct IncompleteCholesky<T: Element> {
+	/// lower factor with sorted rows and the diagonal first in each column
+	factor: Csc<T>,
+}
+impl<T: Scalar + Float> IncompleteCholesky<T> {
+	/// factorize the given symmetric matrix, only its lower triangle is considered
+	pub fn new(matrix: &Csc<T>) -> Result<Self, FactorizationError> {
+		if matrix.rows() != matrix.columns()
+			{return Err(FactorizationError::NotSquare)}
+		let size = matrix.columns();
+		let mut pointers = vec![0];
+		let mut indices = Vec::new();
+		let mut values = Vec::new();
+		for j in 0 .. size {
+			let (rows, column) = matrix.column(j);
+			let start = rows.partition_point(|&i|  i < j);
+			if rows.get(start) != Some(&j)
+				{return Err(FactorizationError::NotPositiveDefinite(j))}
+			indices.extend_from_slice(&rows[start ..]);
+			values.extend_from_slice(&column[start ..]);
+			pointers.push(indices.len());
+		}
+
+		// right-looking factorization restricted to the pattern
+		for k in 0 .. size {
+			let diagonal = values[pointers[k]];
+			if diagonal.is_nan() || diagonal <= T::zero()
+				{return Err(FactorizationError::NotPositiveDefinite(k))}
+			let diagonal = diagonal.sqrt();
+			values[pointers[k]] = diagonal;
+			for value in &mut values[pointers[k]+1 .. pointers[k+1]]  {*value = *value / diagonal}
+			for p in pointers[k]+1 .. pointers[k+1] {
+				let j = indices[p];
+				// update column j with the elements of column k below row j
+				for q in p .. pointers[k+1] {
+					let i = indices[q];
+					if let Ok(position) = indices[pointers[j] .. pointers[j+1]].binary_search(&i) {
+						let position = pointers[j] + position;
+						values[position] = values[position] - values[q] * values[p];
+					}
+				}
+			}
+		}
+		Ok(Self {factor: Csc::try_from_parts([size, size], pointers, indices, values).unwrap()})
+	}
+	/// lower triangular factor `L`
+	pub fn l(&self) -> &Csc<T>  {&self.factor}
+}
+impl<T: Scalar + Float> Preconditioner<T> for IncompleteCholesky<T> {
+	fn precondition(&self, r: &VectorView<'_, T>, z: &mut VectorViewMut<'_, T>) {
+		let (pointers, indices, values) = (self.factor.pointers(), self.factor.indices(), self.factor.values());
+		z.set_field(|index|  r[index]);
+		// L y = r
+		for j in 0 .. self.factor.columns() {
+			let y = z[[j,0]] / values[pointers[j]];
+			z[[j,0]] = y;
+			for p in pointers[j]+1 .. pointers[j+1]  {z[[indices[p],0]] = z[[indices[p],0]] - values[p] * y}
+		}
+		// Lᵀ z = y
+		for j in (0 .. self.factor.columns()).rev() {
+			let mut y = z[[j,0]];
+			for p in pointers[j]+1 .. pointers[j+1]  {y = y - values[p] * z[[indices[p],0]]}
+			z[[j,0]] = y / values[pointers[j]];
+		}
+	}
+}
+
+/// incomplete LU factorization `A ≈ L U` where `L` and `U` have the pattern of `A` (known as ILU(0))
+#[derive(Clone, Debug)]
+pub struct Ilu0<T> {
+	/// rows of both factors with sorted columns, the unit diagonal of `L` is implicit
+	pointers: Vec<usize>,
+	indices: Vec<usize>,
+	values: Vec<T>,
+	/// position of the diagonal in each row
+	diagonal: Vec<usize>,
+}
+impl<T: Scalar + Float> Ilu0<T> {
+	/// factorize the given matrix, all its diagonal elements must be stored
+	pub fn new(matrix: &Csc<T>) -> Result<Self, FactorizationError> {
+		if matrix.rows() != matrix.columns()
+			{return Err(FactorizationError::NotSquare)}
+		let size = matrix.rows();
+		// the columns of the transpose are the rows of the matrix
+		let (_, pointers, indices, mut values) = matrix.transpose().into_parts();
+		let mut diagonal = Vec::with_capacity(size);
+		for i in 0 .. size {
+			match indices[pointers[i] .. pointers[i+1]].binary_search(&i) {
+				Ok(position) => diagonal.push(pointers[i] + position),
+				Err(_) => return Err(FactorizationError::Singular(i)),
+			}
+		}
+
+		// IKJ variant of the gaussian elimination restricted to the pattern
+		let mut positions = vec![usize::MAX; size];
+		for i in 0 .. size {
+			for p in pointers[i] .. pointers[i+1]  {positions[indices[p]] = p}
+			for p in pointers[i] .. diagonal[i] {
+				let k = indices[p];
+				let pivot = values[diagonal[k]];
+				if pivot == T::zero()
+					{return Err(FactorizationError::Singular(k))}
+				values[p] = values[p] / pivot;
+				for q in diagonal[k]+1 .. pointers[k+1] {
+					let position = positions[indices[q]];
+					if position != usize::MAX  {values[position] = values[position] - values[p] * values[q]}
+				}
+			}
+			for p in pointers[i] .. pointers[i+1]  {positions[indices[p]] = usize::MAX}
+		}
+		if let Some(i) = diagonal.iter().position(|&p|  values[p] == T::zero())
+			{return Err(FactorizationError::Singular(i))}
+		Ok(Self {pointers, indices, values, diagonal})
+	}
+}
+impl<T: Scalar + Float> Preconditioner<T> for Ilu0<T> {
+	fn precondition(&self, r: &VectorView<'_, T>, z: &mut VectorViewMut<'_, T>) {
+		let size = self.diagonal.len();
+		let row = |range: core::ops::Range<usize>|  zip(&self.indices[range.clone()], &self.values[range]);
+		// L y = r
+		for i in 0 .. size {
+			let y = row(self.pointers[i] .. self.diagonal[i])
+				.fold(r[[i,0]], |y, (&j, &value)|  y - value * z[[j,0]]);
+			z[[i,0]] = y;
+		}
+		// U z = y
+		for i in (0 .. size).rev() {
+			let y = row(self.diagonal[i]+1 .. self.pointers[i+1])
+				.fold(z[[i,0]], |y, (&j, &value)|  y - value * z[[j,0]]);
+			z[[i,0]] = y / self.values[self.diagonal[i]];
+		}
+	}
+}
+
+impl<T: Scalar + Float> Preconditioner<T> for SparseCholesky<T> {
+	fn precondition(&self, r: &VectorView<'_, T>, z: &mut VectorViewMut<'_, T>) {
+		z.set_field(|index|  r[index]);
+		self.solve_inplace(z);
+	}
+	fn buffer_len(&self) -> usize  {self.symbolic().size()}
+	fn precondition_buffer(&self, r: &VectorView<'_, T>, z: &mut VectorViewMut<'_, T>, buffer: &mut [T]) {
+		z.set_field(|index|  r[index]);
+		self.solve_buffer(z, buffer);
+	}
+}
+impl<T: Scalar + Float> Preconditioner<T> for SparseLu<T> {
+	fn precondition(&self, r: &VectorView<'_, T>, z: &mut VectorViewMut<'_, T>) {
+		z.set_field(|index|  r[index]);
+		self.solve_inplace(z);
+	}
+	fn buffer_len(&self) -> usize  {self.symbolic().size()}
+	fn precondition_buffer(&self, r: &VectorView<'_, T>, z: &mut VectorViewMut<'_, T>, buffer: &mut [T]) {
+		z.set_field(|index|  r[index]);
+		self.solve_buffer(z, buffer);
+	}
+}
@@ -27,6 +27,26 @@ pub mod operators;
 pub mod matrices;
 pub mod glm;
 pub mod iterator;
+pub mod sparse;
+pub mod krylov;
+pub mod triangular;
+pub mod symmetric;
+pub mod banded;
+pub mod diagonal;
+pub mod permutation;
+pub mod tensor;
+pub mod batch;
+pub mod shared;
+pub mod inline;
+pub mod allocator;
+pub mod workspace;
+pub mod interop;
+pub mod npy;
+#[cfg(feature = "memmap")]
+pub mod mmap;
+pub mod text;
+#[cfg(feature = "serde")]
+pub mod serialize;
 
 pub use prelude::*;
 pub use matrix::*;
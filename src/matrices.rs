@@ -8,13 +8,19 @@
 use super::prelude::*;
 use super::matrix::*;
 
+use super::allocator::*;
+
+use core::alloc::Layout;
+use core::fmt;
 use core::marker::PhantomData;
+use core::mem::{MaybeUninit, ManuallyDrop};
+use core::ptr::{self, NonNull};
 
 
 /// statically sized and allocated matrix
 pub type SMatrix<T, const R:usize, const C:usize> = Matrix<Static<T,R,C>>;
 /// dynamically or staticalle sized and dynamically allocated matrix
-pub type DMatrix<T,R=Dyn,C=Dyn> = Matrix<Dynamic<T,R,C>>;
+pub type DMatrix<T,R=Dyn,C=Dyn,Al=Global> = Matrix<Dynamic<T,R,C,Al>>;
 /// immutable matrix view in a matrix
 pub type MatrixView<'t,T,R=Dyn,C=Dyn> = Matrix<View<'t,T,R,C>>;
 /// mutable matrix view in a matrix
@@ -31,7 +37,8 @@ pub type VectorViewMut<'t,T,R=Dyn> = Matrix<ViewMut<'t,T,R,Stat<1>>>;
 
 
 /// column-major statically sized and allocated owned array
-#[derive(Clone)]
+#[derive(Clone, Copy)]
+#[repr(transparent)]
 pub struct Static<T: Element, const R: usize, const C: usize> {
 	pub data: [[T; R]; C],
 }
@@ -42,7 +49,7 @@ impl<T: Element, const R:usize, const C:usize>
 	type R = Stat<R>;
 	type C = Stat<C>;
 	fn shape(&self) -> [usize; 2]   {[R, C]}
-	fn strides(&self) -> [usize; 2] {[1, R]}
+	fn strides(&self) -> [isize; 2] {[1, R as isize]}
 	fn as_ptr(&self) -> *const T        {self.data.as_ptr() as _}
 }	
 impl<T: Element, const R:usize, const C:usize> 
@@ -50,37 +57,45 @@ impl<T: Element, const R:usize, const C:usize>
 {
 	fn as_mut_ptr(&mut self) -> *mut T  {self.data.as_mut_ptr() as _}
 }
-impl<T: Element + Default + Copy, const R: usize, const C: usize> 
+impl<T: Element, const R: usize, const C: usize> 
 	ArrayOwned for Static<T,R,C>
 {
-	fn empty(_: (Self::R, Self::C)) -> Self  {Self{data: [[T::default(); R]; C]}}
+	type Uninit = MaybeUninit<[[T; R]; C]>;
+	fn uninit(_: (Self::R, Self::C)) -> Self::Uninit  {MaybeUninit::uninit()}
+}
+impl<T: Element, const R: usize, const C: usize> 
+	ArrayUninit<Static<T,R,C>> for MaybeUninit<[[T; R]; C]>
+{
+	fn strides(&self) -> [isize; 2]  {[1, R as isize]}
+	fn as_mut_ptr(&mut self) -> *mut MaybeUninit<T>  {self.as_mut_ptr() as _}
+	unsafe fn assume_init(self) -> Static<T,R,C>  {Static{data: MaybeUninit::assume_init(self)}}
 }
-impl<T: Element + Default + Copy, const R1:usize, const C1:usize, const R2:usize, const C2:usize>
+impl<T: Element, const R1:usize, const C1:usize, const R2:usize, const C2:usize>
 	Compatible<Stat<R2>, Stat<C2>> for Static<T,R1,C1>
 {
 	type Owned = Static<T,R2,C2>;
 }
 /// constructors, they should ideally be placed in crate::matrix and benefit all impls instead of here, but [rust doesn't allow it yet](https://users.rust-lang.org/t/methods-implemented-for-specialized-structs-are-said-duplicates-when-specializing-over-exclusive-traits/113315/4)
-impl<T:Scalar + Copy + Default, const R:usize, const C:usize> 
+impl<T:Scalar, const R:usize, const C:usize> 
 	Matrix<Static<T, R, C>>
 {
-	pub fn empty() -> Self         {Matrix::new([R,C])}
-	pub fn zeros() -> Self         {let mut new = Matrix::new([R,C]); new.set_zero(); new}
-	pub fn ones() -> Self          {let mut new = Matrix::new([R,C]); new.set_one(); new}
-	pub fn identity() -> Self      {let mut new = Matrix::new([R,C]); new.set_identity(); new}
-	pub fn full(value: T) -> Self  {let mut new = Matrix::new([R,C]); new.set_full(value); new}
+	pub fn empty() -> Self where T: Default  {Matrix::new([R,C])}
+	pub fn zeros() -> Self         {Matrix::from_field([R,C], |_|  T::zero())}
+	pub fn ones() -> Self          {Matrix::from_field([R,C], |_|  T::one())}
+	pub fn identity() -> Self      {Matrix::from_field([R,C], |[i,j]|  if i==j {T::one()} else {T::zero()})}
+	pub fn full(value: T) -> Self  {Matrix::from_field([R,C], |_|  value.clone())}
 	pub fn field<F>(field: F) -> Self     
 	where F: FnMut([usize; 2]) -> T
-		{let mut new = Matrix::new([R,C]); new.set_field(field); new}
+		{Matrix::from_field([R,C], field)}
 }
-impl<T:Scalar + Copy, const R:usize>
+impl<T:Scalar, const R:usize>
 	From<[T;R]> for Matrix<Static<T,R,1>>
 {
 	fn from(src: [T;R]) -> Self {Matrix(Static{
 		data: [src],
 	})}
 }
-impl<T:Scalar + Copy, const R:usize, const C:usize>
+impl<T:Scalar, const R:usize, const C:usize>
 	From<[[T;R];C]> for Matrix<Static<T,R,C>>
 {
 	fn from(src: [[T;R];C]) -> Self {Matrix(Static{
@@ -90,119 +105,372 @@ impl<T:Scalar + Copy, const R:usize, const C:usize>
 
 
 
-/// column-major dynamically allocated owned array, sizing can be dynamic or static
-#[derive(Clone)]
-pub struct Dynamic<T: Element, R: Dim=Dyn, C: Dim=Dyn> {
-	shape: (R, C),
-	data: Vec<T>,
+/// default alignment in bytes of the buffers allocated for [Dynamic] matrices, a cache line
+pub const ALIGNMENT: usize = 64;
+
+/**
+	column-major dynamically allocated owned array, sizing can be dynamic or static
+
+	The buffer comes from the allocator `Al`, it is aligned on [ALIGNMENT] bytes or a chosen power of two, and its columns can be padded so that each column starts aligned. The padded leading dimension is the column stride returned by [Array::strides], padding elements are never initialized nor read. Operations give unpadded matrices, so that they still convert to contiguous slices and vectors
+*/
+pub struct Dynamic<T: Element, R: Dim=Dyn, C: Dim=Dyn, Al: Allocator=Global> {
+	pub(crate) shape: (R, C),
+	/// column stride, larger than the number of rows when columns are padded
+	leading: usize,
+	/// number of elements and alignment the buffer was allocated with, that differ for buffers taken from a [Vec]
+	capacity: usize,
+	align: usize,
+	data: NonNull<T>,
+	allocator: Al,
+}
+// safety: the buffer is owned as in a Vec
+unsafe impl<T: Element + Send, R: Dim, C: Dim, Al: Allocator + Send> Send for Dynamic<T,R,C,Al> {}
+unsafe impl<T: Element + Sync, R: Dim, C: Dim, Al: Allocator + Sync> Sync for Dynamic<T,R,C,Al> {}
+
+/// column stride for the given number of rows, padded or not so that columns are aligned on `align` bytes
+pub(crate) fn leading<T>(rows: usize, align: usize, padded: bool) -> usize {
+	let size = size_of::<T>();
+	if !padded || size == 0  {return rows}
+	// smallest number of elements spanning a multiple of the alignment
+	let step = align / align.min(1 << size.trailing_zeros());
+	rows.next_multiple_of(step)
+}
+/// memory layout of a buffer of the given number of elements
+fn buffer<T>(capacity: usize, align: usize) -> Layout {
+	capacity.checked_mul(size_of::<T>())
+		.and_then(|size|  Layout::from_size_align(size, align).ok())
+		.expect("matrix too big to allocate")
+}
+
+impl<T: Element, R: Dim, C: Dim> Dynamic<T,R,C> {
+	/// take the buffer of a vector, dropping the elements beyond the shape
+	pub(crate) fn from_vec(shape: (R, C), mut data: Vec<T>) -> Self {
+		data.truncate(shape.0.value() * shape.1.value());
+		let mut data = ManuallyDrop::new(data);
+		Self {
+			shape,
+			leading: shape.0.value(),
+			capacity: data.capacity(),
+			align: align_of::<T>(),
+			// safety: vectors never have a null pointer
+			data: unsafe {NonNull::new_unchecked(data.as_mut_ptr())},
+			allocator: Global,
+		}
+	}
+	/// retreive the elements in column-major order, moving the buffer without copy if it is contiguous and aligned as a vector
+	pub(crate) fn into_vec(self) -> Vec<T> {
+		let this = ManuallyDrop::new(self);
+		let [rows, columns] = [this.shape.0.value(), this.shape.1.value()];
+		if this.leading == rows && this.align == align_of::<T>() {
+			// safety: the buffer has the layout of a vector of this capacity, and its elements in the shape are initialized
+			return unsafe {Vec::from_raw_parts(this.data.as_ptr(), rows * columns, this.capacity)}
+		}
+		let mut data = Vec::<T>::with_capacity(rows * columns);
+		for j in 0 .. columns {
+			// safety: columns are initialized and moved to the vector spare capacity, then the buffer is deallocated without dropping them
+			unsafe {ptr::copy_nonoverlapping(this.data.as_ptr().add(j * this.leading), data.as_mut_ptr().add(j * rows), rows)}
+		}
+		unsafe {
+			data.set_len(rows * columns);
+			Global.deallocate(this.data.cast(), buffer::<T>(this.capacity, this.align));
+		}
+		data
+	}
+}
+impl<T: Element, R: Dim, C: Dim, Al: Allocator>
+	Drop for Dynamic<T,R,C,Al>
+{
+	fn drop(&mut self) {
+		for j in 0 .. self.shape.1.value() {
+			// safety: elements in the shape are initialized, padding is not
+			unsafe {ptr::drop_in_place(ptr::slice_from_raw_parts_mut(
+				self.data.as_ptr().add(j * self.leading),
+				self.shape.0.value(),
+				))}
+		}
+		// safety: the buffer was allocated with this layout
+		unsafe {self.allocator.deallocate(self.data.cast(), buffer::<T>(self.capacity, self.align))}
+	}
 }
-impl<T: Element, R: Dim, C: Dim> 
-	Array for Dynamic<T,R,C> 
+impl<T: Element, R: Dim, C: Dim, Al: Allocator + Clone>
+	Clone for Dynamic<T,R,C,Al>
+{
+	fn clone(&self) -> Self {
+		let strides = Array::strides(self);
+		DynamicUninit::new_in(self.shape, self.align, self.leading != self.shape.0.value(), self.allocator.clone())
+			// safety: indices are in the shape
+			.init(|index|  unsafe {&*self.data.as_ptr().offset(offset(index, strides))}.clone())
+	}
+}
+impl<T: Element + fmt::Debug, R: Dim, C: Dim, Al: Allocator>
+	fmt::Debug for Dynamic<T,R,C,Al>
+{
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		let [rows, columns] = Array::shape(self);
+		// safety: elements in the shape are initialized
+		let data = (0 .. columns)
+			.map(|j|  unsafe {core::slice::from_raw_parts(self.data.as_ptr().add(j * self.leading), rows)})
+			.collect::<Vec<_>>();
+		f.debug_struct("Dynamic")
+			.field("shape", &[rows, columns])
+			.field("columns", &data)
+			.finish()
+	}
+}
+impl<T: Element, R: Dim, C: Dim, Al: Allocator>
+	Array for Dynamic<T,R,C,Al>
 {
 	type Element = T;
 	type R = R;
 	type C = C;
 	fn shape(&self) -> [usize; 2]    {[self.shape.0.value(), self.shape.1.value()]}
-	fn strides(&self) -> [usize; 2]  {[1, self.shape.0.value()]}
+	fn strides(&self) -> [isize; 2]  {[1, self.leading as isize]}
 	fn as_ptr(&self) -> *const T        {self.data.as_ptr()}
-}	
-impl<T: Element, R: Dim, C: Dim> 
-	ArrayMut for Dynamic<T,R,C> 
+}
+impl<T: Element, R: Dim, C: Dim, Al: Allocator>
+	ArrayMut for Dynamic<T,R,C,Al>
 {
-	fn as_mut_ptr(&mut self) -> *mut T  {self.data.as_mut_ptr()}
+	fn as_mut_ptr(&mut self) -> *mut T  {self.data.as_ptr()}
 }
-impl<T: Element + Default, R: Dim, C: Dim> 
-	ArrayOwned for Dynamic<T,R,C>
+impl<T: Element, R: Dim, C: Dim, Al: Allocator + Default>
+	ArrayOwned for Dynamic<T,R,C,Al>
 {
-	fn empty(shape: (Self::R, Self::C)) -> Self {
-		Self {
-			shape,
-			data: vec![T::default(); shape.0.value() * shape.1.value()],
+	type Uninit = DynamicUninit<T,R,C,Al>;
+	/// columns are not padded
+	fn uninit(shape: (Self::R, Self::C)) -> Self::Uninit  {DynamicUninit::new_in(shape, ALIGNMENT, false, Al::default())}
+}
+impl<T: Element, R1:Dim, C1:Dim, R2:Dim, C2:Dim, Al: Allocator>
+	Compatible<R2,C2> for Dynamic<T,R1,C1,Al>
+{
+	type Owned = Dynamic<T,R2,C2>;
+}
+/// uninitialized counterpart of [Dynamic], as returned by [ArrayOwned::uninit]. Dropping it deallocates the buffer without dropping elements
+pub struct DynamicUninit<T: Element, R: Dim=Dyn, C: Dim=Dyn, Al: Allocator=Global> {
+	pub(crate) shape: (R, C),
+	leading: usize,
+	capacity: usize,
+	align: usize,
+	data: NonNull<MaybeUninit<T>>,
+	allocator: Al,
+}
+impl<T: Element, R: Dim, C: Dim> DynamicUninit<T,R,C> {
+	/// allocate an aligned buffer for the given dimensions, without initializing it
+	pub fn new(shape: (R, C)) -> Self  {Self::new_in(shape, ALIGNMENT, false, Global)}
+	/// allocate a buffer aligned only as its elements, so that it converts to a vector without copy
+	#[cfg(feature = "nalgebra")]
+	pub(crate) fn unaligned(shape: (R, C)) -> Self {
+		Self::allocate(shape, align_of::<T>(), false, Global).unwrap_or_else(|layout|  std::alloc::handle_alloc_error(layout))
+	}
+}
+impl<T: Element, R: Dim, C: Dim, Al: Allocator> DynamicUninit<T,R,C,Al> {
+	/**
+		allocate the buffer for the given dimensions in the given allocator, aligned on `align` bytes (or the elements alignment if bigger) with columns padded or not
+
+		panics if `align` is not a power of two
+	*/
+	pub fn new_in(shape: (R, C), align: usize, padded: bool, allocator: Al) -> Self {
+		Self::allocate(shape, align, padded, allocator).unwrap_or_else(|layout|  std::alloc::handle_alloc_error(layout))
+	}
+	/// same as [Self::new_in] but returns an error instead of aborting if the allocator fails
+	pub fn try_new_in(shape: (R, C), align: usize, padded: bool, allocator: Al) -> Result<Self, AllocError> {
+		Self::allocate(shape, align, padded, allocator).map_err(|_|  AllocError)
+	}
+	fn allocate(shape: (R, C), align: usize, padded: bool, allocator: Al) -> Result<Self, Layout> {
+		assert!(align.is_power_of_two(), "alignment must be a power of two");
+		let align = align.max(align_of::<T>());
+		let leading = leading::<T>(shape.0.value(), align, padded);
+		let capacity = leading.checked_mul(shape.1.value()).expect("matrix too big to allocate");
+		let layout = buffer::<T>(capacity, align);
+		let data = allocator.allocate(layout).map_err(|_|  layout)?.cast();
+		Ok(Self {shape, leading, capacity, align, data, allocator})
+	}
+	/// write every element with the closure called in column-major order
+	pub(crate) fn init<F>(self, field: F) -> Dynamic<T,R,C,Al>
+	where F: FnMut([usize; 2]) -> T
+	{
+		let shape = [self.shape.0.value(), self.shape.1.value()];
+		// safety: the buffer spans the shape at these strides
+		unsafe {write_field(self.data.as_ptr(), shape, [1, self.leading as isize], field)};
+		// safety: every element in the shape has been written
+		unsafe {self.into_init()}
+	}
+	/**
+		matrix storage with this buffer
+
+		# Safety
+		every element in the shape must have been initialized
+	*/
+	unsafe fn into_init(self) -> Dynamic<T,R,C,Al> {
+		let this = ManuallyDrop::new(self);
+		Dynamic {
+			shape: this.shape,
+			leading: this.leading,
+			capacity: this.capacity,
+			align: this.align,
+			data: this.data.cast(),
+			allocator: ptr::read(&this.allocator),
 		}
 	}
+	/// column stride of the buffer
+	pub(crate) fn leading(&self) -> usize  {self.leading}
+	/// pointer to the buffer
+	pub(crate) fn as_mut_ptr(&mut self) -> *mut MaybeUninit<T>  {self.data.as_ptr()}
 }
-impl<T: Element + Default, R1:Dim, C1:Dim, R2:Dim, C2:Dim>
-	Compatible<R2,C2> for Dynamic<T,R1,C1>
+impl<T: Element, R: Dim, C: Dim, Al: Allocator>
+	Drop for DynamicUninit<T,R,C,Al>
 {
-	type Owned = Dynamic<T,R2,C2>;
+	fn drop(&mut self) {
+		// safety: the buffer was allocated with this layout
+		unsafe {self.allocator.deallocate(self.data.cast(), buffer::<T>(self.capacity, self.align))}
+	}
+}
+impl<T: Element, R: Dim, C: Dim, Al: Allocator + Default>
+	ArrayUninit<Dynamic<T,R,C,Al>> for DynamicUninit<T,R,C,Al>
+{
+	fn strides(&self) -> [isize; 2]  {[1, self.leading as isize]}
+	fn as_mut_ptr(&mut self) -> *mut MaybeUninit<T>  {self.data.as_ptr()}
+	unsafe fn assume_init(self) -> Dynamic<T,R,C,Al>  {self.into_init()}
+}
+impl<T: Element, R: Dim, C: Dim, Al: Allocator>
+	Matrix<Dynamic<T,R,C,Al>>
+{
+	/// new matrix in the given allocator, aligned on `align` bytes with columns padded or not, and every element given by the closure called in column-major order
+	pub fn field_in<F>(shape: [usize; 2], align: usize, padded: bool, allocator: Al, field: F) -> Self
+	where F: FnMut([usize; 2]) -> T
+	{
+		let dims = (R::check(shape[0]).unwrap(), C::check(shape[1]).unwrap());
+		Matrix(DynamicUninit::new_in(dims, align, padded, allocator).init(field))
+	}
+	/// same as [Self::field_in] but returns an error instead of aborting if the allocator fails
+	pub fn try_field_in<F>(shape: [usize; 2], align: usize, padded: bool, allocator: Al, field: F) -> Result<Self, AllocError>
+	where F: FnMut([usize; 2]) -> T
+	{
+		let dims = (R::check(shape[0]).unwrap(), C::check(shape[1]).unwrap());
+		Ok(Matrix(DynamicUninit::try_new_in(dims, align, padded, allocator)?.init(field)))
+	}
+	/// alignment in bytes of the buffer, and of the columns if they are padded
+	pub fn alignment(&self) -> usize  {self.0.align}
+	/// `true` if columns are padded to keep them aligned
+	pub fn is_padded(&self) -> bool  {self.0.leading != self.0.shape.0.value()}
+	/// allocator owning the buffer
+	pub fn allocator(&self) -> &Al  {&self.0.allocator}
 }
 /// constructors, they should ideally be placed in crate::matrix and benefit all impls instead of here, but [rust doesn't allow it yet](https://users.rust-lang.org/t/methods-implemented-for-specialized-structs-are-said-duplicates-when-specializing-over-exclusive-traits/113315/4)
-impl<T:Scalar + Default, const R:usize, const C:usize> 
+impl<T:Scalar, const R:usize, const C:usize> 
 	Matrix<Dynamic<T, Stat<R>, Stat<C>>>
 {
-	pub fn empty() -> Self         {Matrix::new([R,C])}
-	pub fn zeros() -> Self         {let mut new = Matrix::new([R,C]); new.set_zero(); new}
-	pub fn ones() -> Self          {let mut new = Matrix::new([R,C]); new.set_one(); new}
-	pub fn identity() -> Self      {let mut new = Matrix::new([R,C]); new.set_identity(); new}
-	pub fn full(value: T) -> Self  {let mut new = Matrix::new([R,C]); new.set_full(value); new}
+	pub fn empty() -> Self where T: Default  {Matrix::new([R,C])}
+	pub fn zeros() -> Self         {Matrix::from_field([R,C], |_|  T::zero())}
+	pub fn ones() -> Self          {Matrix::from_field([R,C], |_|  T::one())}
+	pub fn identity() -> Self      {Matrix::from_field([R,C], |[i,j]|  if i==j {T::one()} else {T::zero()})}
+	pub fn full(value: T) -> Self  {Matrix::from_field([R,C], |_|  value.clone())}
 	pub fn field<F>(field: F) -> Self     
 	where F: FnMut([usize; 2]) -> T
-		{let mut new = Matrix::new([R,C]); new.set_field(field); new}
+		{Matrix::from_field([R,C], field)}
 }
-impl<T:Scalar + Default, const C:usize> 
+impl<T:Scalar, const C:usize> 
 	Matrix<Dynamic<T, Dyn, Stat<C>>>
 {
-	pub fn empty(size: usize) -> Self         {Matrix::new([size,C])}
-	pub fn zeros(size: usize) -> Self         {let mut new = Matrix::new([size,C]); new.set_zero(); new}
-	pub fn ones(size: usize) -> Self          {let mut new = Matrix::new([size,C]); new.set_one(); new}
-	pub fn identity(size: usize) -> Self      {let mut new = Matrix::new([size,C]); new.set_identity(); new}
-	pub fn full(size: usize, value: T) -> Self  {let mut new = Matrix::new([size,C]); new.set_full(value); new}
+	pub fn empty(size: usize) -> Self where T: Default  {Matrix::new([size,C])}
+	pub fn zeros(size: usize) -> Self         {Matrix::from_field([size,C], |_|  T::zero())}
+	pub fn ones(size: usize) -> Self          {Matrix::from_field([size,C], |_|  T::one())}
+	pub fn identity(size: usize) -> Self      {Matrix::from_field([size,C], |[i,j]|  if i==j {T::one()} else {T::zero()})}
+	pub fn full(size: usize, value: T) -> Self  {Matrix::from_field([size,C], |_|  value.clone())}
 	pub fn field<F>(size: usize, field: F) -> Self     
 	where F: FnMut([usize; 2]) -> T
-		{let mut new = Matrix::new([size,C]); new.set_field(field); new}
+		{Matrix::from_field([size,C], field)}
 }
-impl<T:Scalar + Default, const R:usize> 
+impl<T:Scalar, const R:usize> 
 	Matrix<Dynamic<T, Stat<R>, Dyn>>
 {
-	pub fn empty(size: usize) -> Self         {Matrix::new([R,size])}
-	pub fn zeros(size: usize) -> Self         {let mut new = Matrix::new([R,size]); new.set_zero(); new}
-	pub fn ones(size: usize) -> Self          {let mut new = Matrix::new([R,size]); new.set_one(); new}
-	pub fn identity(size: usize) -> Self      {let mut new = Matrix::new([R,size]); new.set_identity(); new}
-	pub fn full(size: usize, value: T) -> Self  {let mut new = Matrix::new([R,size]); new.set_full(value); new}
+	pub fn empty(size: usize) -> Self where T: Default  {Matrix::new([R,size])}
+	pub fn zeros(size: usize) -> Self         {Matrix::from_field([R,size], |_|  T::zero())}
+	pub fn ones(size: usize) -> Self          {Matrix::from_field([R,size], |_|  T::one())}
+	pub fn identity(size: usize) -> Self      {Matrix::from_field([R,size], |[i,j]|  if i==j {T::one()} else {T::zero()})}
+	pub fn full(size: usize, value: T) -> Self  {Matrix::from_field([R,size], |_|  value.clone())}
 	pub fn field<F>(size: usize, field: F) -> Self     
 	where F: FnMut([usize; 2]) -> T
-		{let mut new = Matrix::new([R,size]); new.set_field(field); new}
+		{Matrix::from_field([R,size], field)}
 }
-impl<T:Scalar + Default> 
+impl<T:Scalar> 
 	Matrix<Dynamic<T, Dyn, Dyn>>
 {
-	pub fn empty(shape: [usize; 2]) -> Self         {Matrix::new(shape)}
-	pub fn zeros(shape: [usize; 2]) -> Self         {let mut new = Matrix::new(shape); new.set_zero(); new}
-	pub fn ones(shape: [usize; 2]) -> Self          {let mut new = Matrix::new(shape); new.set_one(); new}
-	pub fn identity(shape: [usize; 2]) -> Self      {let mut new = Matrix::new(shape); new.set_identity(); new}
-	pub fn full(shape: [usize; 2], value: T) -> Self  {let mut new = Matrix::new(shape); new.set_full(value); new}
+	pub fn empty(shape: [usize; 2]) -> Self where T: Default  {Matrix::new(shape)}
+	pub fn zeros(shape: [usize; 2]) -> Self         {Matrix::from_field(shape, |_|  T::zero())}
+	pub fn ones(shape: [usize; 2]) -> Self          {Matrix::from_field(shape, |_|  T::one())}
+	pub fn identity(shape: [usize; 2]) -> Self      {Matrix::from_field(shape, |[i,j]|  if i==j {T::one()} else {T::zero()})}
+	pub fn full(shape: [usize; 2], value: T) -> Self  {Matrix::from_field(shape, |_|  value.clone())}
 	pub fn field<F>(shape: [usize; 2], field: F) -> Self     
 	where F: FnMut([usize; 2]) -> T
-		{let mut new = Matrix::new(shape); new.set_field(field); new}
+		{Matrix::from_field(shape, field)}
 }
 impl<T:Element, R:Dim, C:Dim> 
 	Matrix<Dynamic<T,R,C>>
 {
 	pub fn try_from_vec(shape: [usize;2], src: Vec<T>) -> Option<Self> {
 		assert!(src.len() >= shape[0]*shape[1]);
-		Some(Matrix(Dynamic{
-			data: src,
-			shape: (R::check(shape[0])?, C::check(shape[1])?),
-		}))
+		Some(Matrix(Dynamic::from_vec((R::check(shape[0])?, C::check(shape[1])?), src)))
 	}
+	/// retreive the elements in column-major order, without copy unless the columns are padded or the buffer is aligned beyond the elements alignment
+	pub fn into_vec(self) -> Vec<T>  {self.0.into_vec()}
 }
 impl<T:Element>
 	From<Vec<T>> for Matrix<Dynamic<T, Dyn, Stat<1>>>
 {
-	fn from(src: Vec<T>) -> Self  {Matrix(Dynamic{
-		shape: (Dyn(src.len()), Stat{}),
-		data: src,
-	})}
+	fn from(src: Vec<T>) -> Self  {Matrix(Dynamic::from_vec((Dyn(src.len()), Stat{}), src))}
+}
+
+
+/// reason why a view cannot be created on a memory buffer
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub enum ViewError {
+	/// the shape is not allowed by the view [Dim]s, for instance a static dimension of a different size
+	Dimension {shape: [usize; 2]},
+	/// the elements reached by the shape and strides span `required` elements but the buffer only has `available`
+	OutOfBounds {required: usize, available: usize},
+	/// the memory extent of the shape and strides overflows `isize`
+	Overflow,
+	/// distinct indices would reach the same element, which is not allowed for mutable views
+	Aliasing {shape: [usize; 2], strides: [isize; 2]},
+}
+impl fmt::Display for ViewError {
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		match self {
+			Self::Dimension {shape} => write!(f, "shape {:?} is not allowed by the view dimensions", shape),
+			Self::OutOfBounds {required, available} => write!(f, "view spans {} elements but the buffer has only {}", required, available),
+			Self::Overflow => write!(f, "view memory extent overflows"),
+			Self::Aliasing {shape, strides} => write!(f, "strides {:?} make distinct indices reach the same element in shape {:?}", strides, shape),
+		}
+	}
+}
+impl std::error::Error for ViewError {}
+
+/**
+	check a view of the given shape and strides fits in a buffer of `len` elements, and does not alias if `mutable`
+	
+	returns the view dimensions and the position of element `[0,0]` in the buffer, which is after the elements reached by negative strides
+*/
+fn check_view<R:Dim, C:Dim>(shape: [usize;2], strides: [isize;2], len: usize, mutable: bool) -> Result<((R, C), usize), ViewError> {
+	let dims = (
+		R::check(shape[0]).ok_or(ViewError::Dimension {shape})?,
+		C::check(shape[1]).ok_or(ViewError::Dimension {shape})?,
+		);
+	let required = extent(shape, strides).ok_or(ViewError::Overflow)?;
+	if required > len
+		{return Err(ViewError::OutOfBounds {required, available: len})}
+	if mutable && is_aliasing(shape, strides)
+		{return Err(ViewError::Aliasing {shape, strides})}
+	Ok((dims, lowest(shape, strides)))
 }
 
 
 /// array referncing an immutable borrowed memory buffer
 #[derive(Copy, Clone, Debug)]
 pub struct View<'t, T: Element, R: Dim=Dyn, C: Dim=Dyn> {
-	shape: (R, C),
-	strides: (usize, usize),
-	data: *const T,
-	lifetime: PhantomData<&'t T>,
+	pub(crate) shape: (R, C),
+	pub(crate) strides: (isize, isize),
+	pub(crate) data: *const T,
+	pub(crate) lifetime: PhantomData<&'t T>,
 }
 impl<T: Element, R: Dim, C: Dim>
 	Array for View<'_, T,R,C>
@@ -211,10 +479,10 @@ impl<T: Element, R: Dim, C: Dim>
 	type R = R;
 	type C = C;
 	fn shape(&self) -> [usize; 2]    {[self.shape.0.value(), self.shape.1.value()]}
-	fn strides(&self) -> [usize; 2]  {[self.strides.0, self.strides.1]}
+	fn strides(&self) -> [isize; 2]  {[self.strides.0, self.strides.1]}
 	fn as_ptr(&self) -> *const T        {self.data}
 }
-impl<T: Element + Default, R1:Dim, C1:Dim, R2:Dim, C2:Dim>
+impl<T: Element, R1:Dim, C1:Dim, R2:Dim, C2:Dim>
 	Compatible<R2,C2> for View<'_,T,R1,C1>
 {
 	type Owned = Dynamic<T,R2,C2>;
@@ -235,14 +503,14 @@ impl<A:Array> Matrix<A> {
 	pub fn reshape<R2:Dim, C2:Dim>(&self, shape: [usize;2]) -> Option<Matrix<View<'_, A::Element, R2,C2>>> {
 		let strides = self.strides();
 		let step = strides[0].min(strides[1]);
-		if (strides[0] % step) != 0 || (strides[1] % step) != 0 
+		if step <= 0 || (strides[0] % step) != 0 || (strides[1] % step) != 0 
 			{return None}
 		let previous = self.shape();
 		if previous[0]*previous[1] != shape[0]*shape[1]
 			{return None}
 		Some(Matrix(View {
 			shape: (R2::check(shape[0])?, C2::check(shape[1])?),
-			strides: (step, step*shape[0]),
+			strides: (step, step*shape[0] as isize),
 			data: self.as_ptr(),
 			lifetime: PhantomData,
 		}))
@@ -271,35 +539,98 @@ impl<A:Array> Matrix<A> {
 			lifetime: PhantomData,
 		}))
 	}
+	/// view on the `i`th row of the matrix, as a column vector
+	pub fn row(&self, i: usize) -> Matrix<View<'_, A::Element, A::C, Stat<1>>> {
+		assert!(i < self.rows());
+		let strides = self.strides();
+		Matrix(View {
+			shape: (self.dimensionality().1, Stat{}),
+			strides: (strides[1], strides[0]),
+			data: unsafe {self.as_ptr().offset(i as isize * strides[0])},
+			lifetime: PhantomData,
+		})
+	}
+	/// view on the `j`th column of the matrix
+	pub fn column(&self, j: usize) -> Matrix<View<'_, A::Element, A::R, Stat<1>>> {
+		assert!(j < self.columns());
+		let strides = self.strides();
+		Matrix(View {
+			shape: (self.dimensionality().0, Stat{}),
+			strides: (strides[0], strides[1]),
+			data: unsafe {self.as_ptr().offset(j as isize * strides[1])},
+			lifetime: PhantomData,
+		})
+	}
+	/// view with the order of rows reversed, without copy
+	pub fn flip_rows(&self) -> Matrix<View<'_, A::Element, A::R, A::C>> {
+		let [rows, _] = self.shape();
+		let strides = self.strides();
+		Matrix(View {
+			shape: self.dimensionality(),
+			strides: (-strides[0], strides[1]),
+			data: unsafe {self.as_ptr().offset(rows.saturating_sub(1) as isize * strides[0])},
+			lifetime: PhantomData,
+		})
+	}
+	/// view with the order of columns reversed, without copy
+	pub fn flip_columns(&self) -> Matrix<View<'_, A::Element, A::R, A::C>> {
+		let [_, columns] = self.shape();
+		let strides = self.strides();
+		Matrix(View {
+			shape: self.dimensionality(),
+			strides: (strides[0], -strides[1]),
+			data: unsafe {self.as_ptr().offset(columns.saturating_sub(1) as isize * strides[1])},
+			lifetime: PhantomData,
+		})
+	}
+	/// view on the matrix rotated by 90° counterclockwise, without copy
+	pub fn rot90(&self) -> Matrix<View<'_, A::Element, A::C, A::R>> {
+		let dim = self.dimensionality();
+		let strides = self.strides();
+		Matrix(View {
+			shape: (dim.1, dim.0),
+			strides: (-strides[1], strides[0]),
+			data: unsafe {self.as_ptr().offset(dim.1.value().saturating_sub(1) as isize * strides[1])},
+			lifetime: PhantomData,
+		})
+	}
 }
 impl<'t, T:Element, R:Dim, C:Dim> 
 	Matrix<View<'t,T,R,C>>
 {
 	/**
-		immutable view in a slice buffer, with specified shape
+		immutable column-major view in a slice buffer, with specified shape
 		
-		may fail when the shape is too big to hold in the slice's length
+		fails when the shape is not allowed by the view dimensionality or too big to hold in the slice's length
 	*/
-	pub fn try_from_slice(shape: [usize;2], src: &'t [T]) -> Option<Self> {
-		assert!(src.len() >= shape[0]*shape[1]);
-		Some(Matrix(View{
-			data: src.as_ptr(),
-			shape: (R::check(shape[0])?, C::check(shape[1])?),
-			strides: (1, shape[0]),
-			lifetime: PhantomData,
-		}))
+	pub fn try_from_slice(shape: [usize;2], src: &'t [T]) -> Result<Self, ViewError> {
+		Self::try_from_strides(shape, [1, shape[0] as isize], src)
 	}
 	/**
 		immutable view in a slice buffer, with specified shape and strides
 		
-		may fail when the shape*strides is too big to hold in the slice's length
+		fails when the shape is not allowed by the view dimensionality or when the elements reached by the shape and strides are beyond the slice's length
+		
+		With negative strides, element `[0,0]` is placed so that the lowest element reached is the first element of the slice
+	*/
+	pub fn try_from_strides(shape: [usize;2], strides: [isize;2], src: &'t [T]) -> Result<Self, ViewError> {
+		// safety: the slice is valid for its length during 't
+		unsafe {Self::try_from_raw_parts(src.as_ptr(), src.len(), shape, strides)}
+	}
+	/**
+		immutable view in a foreign memory buffer of `len` elements, with specified shape and strides
+		
+		The shape and strides are checked to stay in the buffer as in [Self::try_from_strides]
+		
+		# Safety
+		`data` must be valid for reads of `len` elements during `'t`, and these elements must not be mutated during `'t`
 	*/
-	pub fn try_from_strides(shape: [usize;2], strides: [usize;2], src: &'t [T]) -> Option<Self> {
-		assert!(src.len() >= shape[0]*strides[0].max(shape[1]*strides[1]));
-		Some(Matrix(View{
-			data: src.as_ptr(),
-			shape: (R::check(shape[0])?, C::check(shape[1])?),
+	pub unsafe fn try_from_raw_parts(data: *const T, len: usize, shape: [usize;2], strides: [isize;2]) -> Result<Self, ViewError> {
+		let (shape, start) = check_view(shape, strides, len, false)?;
+		Ok(Matrix(View{
+			shape,
 			strides: (strides[0], strides[1]),
+			data: data.add(start),
 			lifetime: PhantomData,
 		}))
 	}
@@ -317,7 +648,7 @@ impl<'t, T:Element>
 	fn from(src: &[T]) -> Self  {Matrix(View{
 		data: src.as_ptr(),
 		shape: (Dyn(src.len()), Stat{}),
-		strides: (1, src.len()),
+		strides: (1, src.len() as isize),
 		lifetime: PhantomData,
 	})}
 }
@@ -326,10 +657,10 @@ impl<'t, T:Element>
 /// array referncing a mutable borrowed memory buffer
 #[derive(Debug)]
 pub struct ViewMut<'t, T: Element, R: Dim=Dyn, C: Dim=Dyn> {
-	shape: (R, C),
-	strides: (usize, usize),
-	data: *mut T,
-	lifetime: PhantomData<&'t mut T>,
+	pub(crate) shape: (R, C),
+	pub(crate) strides: (isize, isize),
+	pub(crate) data: *mut T,
+	pub(crate) lifetime: PhantomData<&'t mut T>,
 }
 impl<T: Element, R: Dim, C: Dim>
 	Array for ViewMut<'_, T,R,C>
@@ -338,10 +669,10 @@ impl<T: Element, R: Dim, C: Dim>
 	type R = R;
 	type C = C;
 	fn shape(&self) -> [usize; 2]    {[self.shape.0.value(), self.shape.1.value()]}
-	fn strides(&self) -> [usize; 2]  {[self.strides.0, self.strides.1]}
+	fn strides(&self) -> [isize; 2]  {[self.strides.0, self.strides.1]}
 	fn as_ptr(&self) -> *const T        {self.data as _}
 }
-impl<T: Element + Default, R1:Dim, C1:Dim, R2:Dim, C2:Dim>
+impl<T: Element, R1:Dim, C1:Dim, R2:Dim, C2:Dim>
 	Compatible<R2,C2> for ViewMut<'_,T,R1,C1>
 {
 	type Owned = Dynamic<T,R2,C2>;
@@ -367,14 +698,14 @@ impl<A:ArrayMut> Matrix<A> {
 	pub fn reshape_mut<R2:Dim, C2:Dim>(&mut self, shape: [usize;2]) -> Option<Matrix<ViewMut<'_, A::Element, R2,C2>>> {
 		let strides = self.strides();
 		let step = strides[0].min(strides[1]);
-		if (strides[0] % step) != 0 || (strides[1] % step) != 0 
+		if step <= 0 || (strides[0] % step) != 0 || (strides[1] % step) != 0 
 			{return None}
 		let previous = self.shape();
 		if previous[0]*previous[1] != shape[0]*shape[1]
 			{return None}
 		Some(Matrix(ViewMut {
 			shape: (R2::check(shape[0])?, C2::check(shape[1])?),
-			strides: (step, step*shape[0]),
+			strides: (step, step*shape[0] as isize),
 			data: self.as_mut_ptr(),
 			lifetime: PhantomData,
 		}))
@@ -403,35 +734,98 @@ impl<A:ArrayMut> Matrix<A> {
 			lifetime: PhantomData,
 		}))
 	}
+	/// same as [Self::row] but mutable
+	pub fn row_mut(&mut self, i: usize) -> Matrix<ViewMut<'_, A::Element, A::C, Stat<1>>> {
+		assert!(i < self.rows());
+		let strides = self.strides();
+		Matrix(ViewMut {
+			shape: (self.dimensionality().1, Stat{}),
+			strides: (strides[1], strides[0]),
+			data: unsafe {self.as_mut_ptr().offset(i as isize * strides[0])},
+			lifetime: PhantomData,
+		})
+	}
+	/// same as [Self::column] but mutable
+	pub fn column_mut(&mut self, j: usize) -> Matrix<ViewMut<'_, A::Element, A::R, Stat<1>>> {
+		assert!(j < self.columns());
+		let strides = self.strides();
+		Matrix(ViewMut {
+			shape: (self.dimensionality().0, Stat{}),
+			strides: (strides[0], strides[1]),
+			data: unsafe {self.as_mut_ptr().offset(j as isize * strides[1])},
+			lifetime: PhantomData,
+		})
+	}
+	/// same as [Self::flip_rows] but mutable
+	pub fn flip_rows_mut(&mut self) -> Matrix<ViewMut<'_, A::Element, A::R, A::C>> {
+		let [rows, _] = self.shape();
+		let strides = self.strides();
+		Matrix(ViewMut {
+			shape: self.dimensionality(),
+			strides: (-strides[0], strides[1]),
+			data: unsafe {self.as_mut_ptr().offset(rows.saturating_sub(1) as isize * strides[0])},
+			lifetime: PhantomData,
+		})
+	}
+	/// same as [Self::flip_columns] but mutable
+	pub fn flip_columns_mut(&mut self) -> Matrix<ViewMut<'_, A::Element, A::R, A::C>> {
+		let [_, columns] = self.shape();
+		let strides = self.strides();
+		Matrix(ViewMut {
+			shape: self.dimensionality(),
+			strides: (strides[0], -strides[1]),
+			data: unsafe {self.as_mut_ptr().offset(columns.saturating_sub(1) as isize * strides[1])},
+			lifetime: PhantomData,
+		})
+	}
+	/// same as [Self::rot90] but mutable
+	pub fn rot90_mut(&mut self) -> Matrix<ViewMut<'_, A::Element, A::C, A::R>> {
+		let dim = self.dimensionality();
+		let strides = self.strides();
+		Matrix(ViewMut {
+			shape: (dim.1, dim.0),
+			strides: (-strides[1], strides[0]),
+			data: unsafe {self.as_mut_ptr().offset(dim.1.value().saturating_sub(1) as isize * strides[1])},
+			lifetime: PhantomData,
+		})
+	}
 }
 impl<'t, T:Element, R:Dim, C:Dim> 
 	Matrix<ViewMut<'t,T,R,C>>
 {
 	/**
-		mutable view in a slice buffer, with specified shape
+		mutable column-major view in a slice buffer, with specified shape
 		
-		may fail when the shape is too big to hold in the slice's length
+		fails when the shape is not allowed by the view dimensionality or too big to hold in the slice's length
 	*/
-	pub fn try_from_slice(shape: [usize;2], src: &'t mut [T]) -> Option<Self> {
-		assert!(src.len() >= shape[0]*shape[1]);
-		Some(Matrix(ViewMut{
-			data: src.as_mut_ptr(),
-			shape: (R::check(shape[0])?, C::check(shape[1])?),
-			strides: (1, shape[0]),
-			lifetime: PhantomData,
-		}))
+	pub fn try_from_slice(shape: [usize;2], src: &'t mut [T]) -> Result<Self, ViewError> {
+		Self::try_from_strides(shape, [1, shape[0] as isize], src)
 	}
 	/**
 		mutable view in a slice buffer, with specified shape and strides
 		
-		may fail when the shape*strides is too big to hold in the slice's length
+		fails when the shape is not allowed by the view dimensionality, when the elements reached by the shape and strides are beyond the slice's length, or when distinct indices would reach the same element
+		
+		With negative strides, element `[0,0]` is placed so that the lowest element reached is the first element of the slice
+	*/
+	pub fn try_from_strides(shape: [usize;2], strides: [isize;2], src: &'t mut [T]) -> Result<Self, ViewError> {
+		// safety: the slice is valid and exclusively borrowed for its length during 't
+		unsafe {Self::try_from_raw_parts(src.as_mut_ptr(), src.len(), shape, strides)}
+	}
+	/**
+		mutable view in a foreign memory buffer of `len` elements, with specified shape and strides
+		
+		The shape and strides are checked to stay in the buffer and to not alias as in [Self::try_from_strides]
+		
+		# Safety
+		`data` must be valid for reads and writes of `len` elements during `'t`, and these elements must not be accessed by other means during `'t`
 	*/
-	pub fn try_from_strides(shape: [usize;2], strides: [usize;2], src: &'t mut [T]) -> Option<Self> {
-		assert!(src.len() >= shape[0]*strides[0].max(shape[1]*strides[1]));
-		Some(Matrix(ViewMut{
-			data: src.as_mut_ptr(),
-			shape: (R::check(shape[0])?, C::check(shape[1])?),
+	pub unsafe fn try_from_raw_parts(data: *mut T, len: usize, shape: [usize;2], strides: [isize;2]) -> Result<Self, ViewError> {
+		let (shape, start) = check_view(shape, strides, len, true)?;
+		Ok(Matrix(ViewMut{
+			shape,
 			strides: (strides[0], strides[1]),
+			data: data.add(start),
 			lifetime: PhantomData,
 		}))
 	}
@@ -449,7 +843,7 @@ impl<'t, T:Element>
 	fn from(src: &mut [T]) -> Self  {Matrix(ViewMut{
 		data: src.as_mut_ptr(),
 		shape: (Dyn(src.len()), Stat{}),
-		strides: (1, src.len()),
+		strides: (1, src.len() as isize),
 		lifetime: PhantomData,
 	})}
 }
@@ -467,4 +861,112 @@ fn test_constructors() {
     let cv = c.view();
     dbg!(&av, &bv, &cv);
 }
+#[test]
+fn test_view_validation() {
+	let buffer: Vec<f64> = (0 .. 12).map(|v| v as f64).collect();
+	// exact extent, the last element is at (2-1)*1 + (3-1)*5
+	let view = MatrixView::<f64>::try_from_strides([2, 3], [1, 5], &buffer[.. 12]).unwrap();
+	assert_eq!(view[[1,2]], 11.);
+	assert_eq!(view.area(), 12);
+	assert_eq!(MatrixView::<f64>::try_from_strides([2, 3], [1, 5], &buffer[.. 11]).err(),
+		Some(ViewError::OutOfBounds {required: 12, available: 11}));
+	assert_eq!(MatrixView::<f64>::try_from_slice([4, 4], &buffer).err(),
+		Some(ViewError::OutOfBounds {required: 16, available: 12}));
+	assert_eq!(MatrixView::<f64, Stat<3>>::try_from_slice([2, 2], &buffer).err(),
+		Some(ViewError::Dimension {shape: [2, 2]}));
+	assert_eq!(MatrixView::<f64>::try_from_strides([2, 2], [isize::MAX, 1], &buffer).err(),
+		Some(ViewError::Overflow));
+	assert!(MatrixView::<f64>::try_from_slice([0, 3], &[]).is_ok());
+	// broadcasting is fine for immutable views only
+	assert!(MatrixView::<f64>::try_from_strides([3, 2], [0, 1], &buffer).is_ok());
+
+	let mut buffer = buffer;
+	assert!(Matrix::<ViewMut<f64>>::try_from_strides([3, 2], [0, 1], &mut buffer).is_err());
+	// offsets 2*i + 3*j collide for (3,0) and (0,2) but not in a 3x2 shape
+	assert!(Matrix::<ViewMut<f64>>::try_from_strides([3, 2], [2, 3], &mut buffer).is_ok());
+	assert_eq!(Matrix::<ViewMut<f64>>::try_from_strides([3, 2], [1, 2], &mut buffer).err(),
+		Some(ViewError::Aliasing {shape: [3, 2], strides: [1, 2]}));
+	assert!(Matrix::<ViewMut<f64>>::try_from_strides([1, 4], [0, 2], &mut buffer).is_ok());
+}
+#[test]
+fn test_flip() {
+	let matrix = DMatrix::<f64>::field([2, 3], |[i,j]|  (3*i + j) as f64);
+	let flipped = matrix.flip_rows();
+	assert_eq!(flipped.strides(), [-1, 2]);
+	assert_eq!(flipped[[0,0]], 3.);
+	assert_eq!(flipped.flip_rows()[[1,2]], matrix[[1,2]]);
+	assert_eq!(matrix.flip_columns()[[1,0]], 5.);
+	// memory stays contiguous when reversed, the slice being in memory order
+	assert_eq!(flipped.area(), 6);
+	assert_eq!(flipped.as_slice(), matrix.as_slice());
+	let rotated = matrix.rot90();
+	assert_eq!(rotated.shape(), [3, 2]);
+	assert_eq!(rotated.column(0).iter().copied().collect::<Vec<_>>(), [2., 1., 0.]);
+	assert_eq!(rotated.rot90().rot90().rot90()[[1,2]], matrix[[1,2]]);
+	// reversed views mix with other matrices and tensors
+	let doubled = &flipped + &flipped;
+	assert_eq!(doubled[[0,1]], 8.);
+	assert_eq!(flipped.tensor()[[0,2]], 5.);
+	
+	let mut buffer: Vec<f64> = (0 .. 6).map(|v| v as f64).collect();
+	let view = MatrixView::<f64>::try_from_strides([2, 3], [-1, -2], &buffer).unwrap();
+	assert_eq!(view[[0,0]], 5.);
+	assert_eq!(view[[1,2]], 0.);
+	let mut reversed = Matrix::<ViewMut<f64>>::try_from_strides([3, 2], [-2, 1], &mut buffer).unwrap();
+	reversed.flip_columns_mut()[[0,0]] = 10.;
+	reversed.rot90_mut().set_full(1.);
+	assert_eq!(buffer, [1.; 6]);
+}
+#[test]
+fn test_uninit() {
+	use std::rc::Rc;
+	
+	// elements without default value nor copy are written once, and dropped once
+	let counter = Rc::new(0);
+	let matrix = SMatrix::<Rc<i32>, 2, 3>::from_field([2, 3], |_|  counter.clone());
+	assert_eq!(Rc::strong_count(&counter), 7);
+	let mapped: DMatrix<Rc<i32>, Stat<2>, Stat<3>> = matrix.map(|element|  element.clone());
+	assert_eq!(Rc::strong_count(&counter), 13);
+	drop(matrix);
+	let copy = DMatrix::<Rc<i32>, Stat<3>, Stat<2>>::from(&mapped.transpose());
+	assert_eq!(copy.shape(), [3, 2]);
+	drop((mapped, copy));
+	assert_eq!(Rc::strong_count(&counter), 1);
+	
+	let names = DMatrix::<String>::from_field([2, 2], |[i,j]|  format!("{}{}", i, j));
+	assert_eq!(names[[1,0]], "10");
+	let lengths: DMatrix<core::num::NonZeroUsize> = names.map(|name|  core::num::NonZeroUsize::new(name.len()).unwrap());
+	assert_eq!(lengths[[0,1]].get(), 2);
+}
+#[test]
+fn test_dynamic_layout() {
+	// columns of 5 doubles are padded to 8 to start on 64 bytes
+	let a = DMatrix::<f64>::field_in([5, 3], ALIGNMENT, true, Global, |[i,j]|  (i + 5*j) as f64);
+	assert_eq!(a.strides(), [1, 8]);
+	assert!(a.is_padded());
+	for j in 0 .. 3 {
+		assert_eq!(a.column(j).as_ptr() as usize % ALIGNMENT, 0);
+	}
+	assert_eq!(a[[4,2]], 14.);
+	assert_eq!(a.as_slice(), None);
+	assert_eq!(a.clone().strides(), [1, 8]);
+	// operations give aligned contiguous matrices
+	let dense = DMatrix::<f64>::field([5, 3], |[i,j]|  (i + 5*j) as f64);
+	let product = &a * &dense.transpose();
+	assert_eq!(product.strides(), [1, 5]);
+	assert_eq!(product.as_ptr() as usize % ALIGNMENT, 0);
+	assert_eq!(product[[4,1]], (&dense * &dense.transpose())[[4,1]]);
+	assert_eq!(a.into_vec(), dense.clone().into_vec());
+	let b = DMatrix::<u8>::field_in([3, 2], 32, true, Global, |[i,j]|  (i*j) as u8);
+	assert_eq!((b.strides(), b.alignment()), ([1, 32], 32));
+	assert_eq!(b.clone().as_ptr() as usize % 32, 0);
 
+	// vectors are taken and given back without copy
+	let data = vec![1u8, 2, 3, 4, 5, 6, 7];
+	let ptr = data.as_ptr();
+	let matrix = DMatrix::<u8>::try_from_vec([2, 3], data).unwrap();
+	assert_eq!(matrix.alignment(), 1);
+	assert_eq!(matrix.as_ptr(), ptr);
+	let data = matrix.into_vec();
+	assert_eq!((data.as_ptr(), data.len()), (ptr, 6));
+}
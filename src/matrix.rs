@@ -7,6 +7,7 @@
 use crate::prelude::*;
 
 use core::iter::zip;
+use core::mem::MaybeUninit;
 use core::ops::{Index, IndexMut};
 use num_traits::{Zero, One};
 
@@ -26,6 +27,7 @@ use num_traits::{Zero, One};
 	If you seek a special [type alias](super::matrices#types), you would better got its dedicated page
 */
 #[derive(Clone)]
+#[repr(transparent)]
 pub struct Matrix<A:Array> (pub A);
 
 /**
@@ -43,8 +45,8 @@ pub trait Array: Sized {
 	
 	/// size of the array in each dimension
 	fn shape(&self) -> [usize; 2];
-	/// step between values in each dimension
-	fn strides(&self) -> [usize; 2];
+	/// step between values in each dimension, negative steps walk memory backward from element `[0,0]`
+	fn strides(&self) -> [isize; 2];
 	/// pointer to element `[0,0]` in the array
 	fn as_ptr(&self) -> *const Self::Element;
 }
@@ -63,8 +65,34 @@ pub trait ArrayMut: Array {
 	This typically adds constructors, and enables such type to be the result type of operators. Only supported by some array types
 */
 pub trait ArrayOwned: ArrayMut {
-	/// instantiate a new array with the given dimensions
-    fn empty(shape: (Self::R, Self::C)) -> Self;
+	/// storage of the same layout with uninitialized elements
+	type Uninit: ArrayUninit<Self>;
+	/// instantiate a new array with the given dimensions and uninitialized elements
+	fn uninit(shape: (Self::R, Self::C)) -> Self::Uninit;
+	/// instantiate a new array with the given dimensions and default elements
+	fn empty(shape: (Self::R, Self::C)) -> Self
+	where Self::Element: Default
+	{
+		Matrix::from_field([shape.0.value(), shape.1.value()], |_|  Default::default()).0
+	}
+}
+/**
+	Owned storage with uninitialized elements, turned into the array `A` once every element is written
+	
+	This allows to build arrays without paying the initialization of elements that will be overwritten, nor requiring a default value for them
+*/
+pub trait ArrayUninit<A: Array> {
+	/// step between elements in each dimension, as for [Array::strides]
+	fn strides(&self) -> [isize; 2];
+	/// pointer to element `[0,0]`
+	fn as_mut_ptr(&mut self) -> *mut MaybeUninit<A::Element>;
+	/**
+		turn into the initialized array
+		
+		# Safety
+		every element in the shape must have been written
+	*/
+	unsafe fn assume_init(self) -> A;
 }
 /**
 	Designate the array type that will be used by operations applied on a matrix based on this array
@@ -85,7 +113,7 @@ impl<A:Array>
 	fn index(&self, index: [usize; 2]) -> &Self::Output {
 		assert!(zip(index, self.shape()).all(|(i,l)|  i<l));
 		// safety: it is the responsibility of the array trait implementor to ensure any acces to values at these strides can be read
-		unsafe { &* self.as_ptr().add(zip(index, self.strides()).map(|(i,s)|  i*s).sum()) }
+		unsafe { &* self.as_ptr().offset(offset(index, self.strides())) }
 	}
 }
 impl<A:ArrayMut>
@@ -95,13 +123,65 @@ impl<A:ArrayMut>
 		assert!(zip(index, self.shape()).all(|(i,l)|  i<l));
 		// safety: it is the responsibility of the array trait implementor to ensure any acces to values at these strides can be written
 		// we are owning the underlying array so nobody else can have this mutable ref
-		unsafe { &mut* self.as_mut_ptr().add(zip(index, self.strides()).map(|(i,s)|  i*s).sum()) }
+		unsafe { &mut* self.as_mut_ptr().offset(offset(index, self.strides())) }
+	}
+}
+impl<A:Array> Matrix<A> {
+	/**
+		`[row, column]` indexing without bounds checking
+		
+		# Safety
+		the index must be in the matrix shape
+	*/
+	pub unsafe fn get_unchecked(&self, index: [usize; 2]) -> &A::Element {
+		&* self.as_ptr().offset(offset(index, self.strides()))
+	}
+}
+impl<A:ArrayMut> Matrix<A> {
+	/**
+		same as [Self::get_unchecked] but mutable
+		
+		# Safety
+		the index must be in the matrix shape
+	*/
+	pub unsafe fn get_unchecked_mut(&mut self, index: [usize; 2]) -> &mut A::Element {
+		&mut* self.as_mut_ptr().offset(offset(index, self.strides()))
 	}
 }
 impl<A: ArrayOwned> Matrix<A> {
-    pub fn new(shape: [usize;2]) -> Self {
+    /// new matrix with default elements
+    pub fn new(shape: [usize;2]) -> Self
+    where A::Element: Default
+    {
         Self(A::empty((A::R::check(shape[0]).unwrap(), A::C::check(shape[1]).unwrap())))
     }
+	/**
+		new matrix with every element given by the closure, called in column-major order
+		
+		Elements are written directly in uninitialized memory, so there is no initialization pass and no default value needed. If the closure panics, the elements already written are leaked
+	*/
+	pub fn from_field<F>(shape: [usize;2], mut field: F) -> Self
+	where F: FnMut([usize; 2]) -> A::Element
+	{
+		let mut uninit = A::uninit((A::R::check(shape[0]).unwrap(), A::C::check(shape[1]).unwrap()));
+		// safety: the storage implementor ensures the memory at these strides can be written for indices in the shape
+		unsafe {write_field(uninit.as_mut_ptr(), shape, uninit.strides(), &mut field)};
+		// safety: every element in the shape has been written
+		Matrix(unsafe {uninit.assume_init()})
+	}
+}
+/**
+	write every element in the shape with the given closure, called in column-major order
+
+	# Safety
+	`ptr` must be valid for writes at the offsets given by `strides` for every index in `shape`
+*/
+pub(crate) unsafe fn write_field<T>(ptr: *mut MaybeUninit<T>, shape: [usize; 2], strides: [isize; 2], mut field: impl FnMut([usize; 2]) -> T) {
+	for j in 0 .. shape[1] {
+		for i in 0 .. shape[0] {
+			(*ptr.offset(offset([i,j], strides))).write(field([i,j]));
+		}
+	}
 }
 
 
@@ -111,8 +191,8 @@ impl<A:Array> Matrix<A> {
 	// forward array methods
 	/// matrix size in each dimension
 	pub fn shape(&self) -> [usize; 2]  {self.0.shape()}
-	/// memory step between matrix elements (unit is the byte size of an element)
-	pub fn strides(&self) -> [usize; 2]  {self.0.strides()}
+	/// memory step between matrix elements (unit is the byte size of an element), negative for reversed dimensions
+	pub fn strides(&self) -> [isize; 2]  {self.0.strides()}
 	/// pointer to element `[0,0]`
 	pub fn as_ptr(&self) -> *const A::Element  {self.0.as_ptr()}
 
@@ -130,24 +210,29 @@ impl<A:Array> Matrix<A> {
 		self.shape().iter().product()
 	}
 	
-	/// number of elements in the matrix
+	/// number of elements in memory spanned by the matrix, from its lowest to its highest element in memory
 	pub fn area(&self) -> usize {
-		zip(self.shape(), self.strides()).map(|(l,s)|  (l-1)*s).sum::<usize>() + 1
+		extent(self.shape(), self.strides()).unwrap()
 	}
-	/// `True` if the indexed memory is contiguous
+	/// `True` if the indexed memory is contiguous, in column-major or row-major order, possibly with reversed dimensions
 	pub fn is_contiguous(&self) -> bool {
 		let shape = self.shape();
-		let strides = self.strides();
-		shape[0]*strides[0] == strides[1]  ||  shape[1]*strides[1] == strides[0]
+		let strides = self.strides().map(isize::unsigned_abs);
+		(strides[0] == 1 && (shape[1] <= 1 || shape[0] == strides[1]))
+		|| (strides[1] == 1 && (shape[0] <= 1 || shape[1] == strides[0]))
 	}
-	/// cast as a slice if the buffer is contiguous
+	/**
+		cast as a slice if the buffer is contiguous
+		
+		The slice covers the memory from its lowest address, so the elements of reversed dimensions appear in reverse order
+	*/
 	pub fn as_slice(&self) -> Option<&[A::Element]> {
 		if self.is_contiguous() {Some(unsafe {self.as_slice_unchecked()})}
 		else {None}
 	}
 	/// cast as a slice without contiguity check
 	pub unsafe fn as_slice_unchecked(&self) -> &[A::Element] {
-		core::slice::from_raw_parts(self.as_ptr(), self.area())
+		core::slice::from_raw_parts(self.as_ptr().sub(lowest(self.shape(), self.strides())), self.area())
 	}
 }
 
@@ -162,7 +247,8 @@ impl<A:ArrayMut> Matrix<A> {
 	}
 	/// same as [Self::as_slice_unchecked] but mutable
 	pub unsafe fn as_slice_mut_unchecked(&mut self) -> &mut [A::Element] {
-		core::slice::from_raw_parts_mut(self.as_mut_ptr(), self.area())
+		let start = lowest(self.shape(), self.strides());
+		core::slice::from_raw_parts_mut(self.as_mut_ptr().sub(start), self.area())
 	}
 	
 	/// set the matrix eleemnts from the given iterator, as in column-major order
@@ -173,7 +259,8 @@ impl<A:ArrayMut> Matrix<A> {
 		let mut it = it.into_iter();
 		for j in 0 .. self.columns() {
 			for i in 0 .. self.rows() {
-				if let Some(v) = it.next()  {self[[i,j]] = v}
+				// safety: indices are in the matrix shape
+				if let Some(v) = it.next()  {unsafe {*self.get_unchecked_mut([i,j]) = v}}
 				else {return self}
 			}
 		}
@@ -183,18 +270,36 @@ impl<A:ArrayMut> Matrix<A> {
 	pub fn set_field<F>(&mut self, mut field: F) -> &mut Self 
 		where F: FnMut([usize; 2]) -> A::Element
 	{
-		for j in 0 .. self.columns() {
-			for i in 0 .. self.rows() {
-				let index = [i,j];
-				self[index] = field(index);
-			}
-		}
-		self
+		self.update_field(|index, value|  *value = field(index))
 	}
 	/// set every element in the array to the given value
 	pub fn set_full(&mut self, value: A::Element) -> &mut Self {
+		if let Some(slice) = self.as_slice_mut() {
+			slice.fill(value);
+			return self
+		}
 		self.set_field(|_| value.clone())
 	}
+	/**
+		call the given closure on every element in the matrix with its index, in column-major order
+		
+		This walks the memory with pointer arithmetic, with no bounds checking
+	*/
+	pub(crate) fn update_field<F>(&mut self, mut field: F) -> &mut Self 
+		where F: FnMut([usize; 2], &mut A::Element)
+	{
+		let [rows, columns] = self.shape();
+		let strides = self.strides();
+		let ptr = self.as_mut_ptr();
+		for j in 0 .. columns {
+			// safety: it is the responsibility of the array trait implementor to ensure any acces to values at these strides can be written, and indices stay in the matrix shape
+			let column = unsafe {ptr.offset(j as isize * strides[1])};
+			for i in 0 .. rows {
+				field([i,j], unsafe {&mut *column.offset(i as isize * strides[0])});
+			}
+		}
+		self
+	}
 }
 
 impl<A:ArrayMut> Matrix<A>
@@ -233,11 +338,41 @@ where
 	Src::Element: Into<Dst::Element>,
 {
 	fn from(src: &Matrix<Src>) -> Matrix<Dst> {
-		let mut new = Matrix::new(src.shape());
-		new.set_field(|i| src[i].clone().into());
-		new
+		Matrix::from_field(src.shape(), |i| unsafe {src.get_unchecked(i)}.clone().into())
 	}
 }
+/// number of elements in memory spanned by an array of the given shape and strides, or `None` if it overflows `isize`
+pub fn extent(shape: [usize; 2], strides: [isize; 2]) -> Option<usize> {
+	if shape.contains(&0)  {return Some(0)}
+	zip(shape, strides)
+		.try_fold(1usize, |extent, (l,s)|  extent.checked_add((l-1).checked_mul(s.unsigned_abs())?))
+		.filter(|&extent|  extent <= isize::MAX as usize)
+}
+/// number of elements in memory before element `[0,0]` in an array of the given shape and strides, nonzero only with negative strides
+pub fn lowest(shape: [usize; 2], strides: [isize; 2]) -> usize {
+	if shape.contains(&0)  {return 0}
+	zip(shape, strides)
+		.filter(|&(_,s)|  s < 0)
+		.map(|(l,s)|  (l-1) * s.unsigned_abs())
+		.sum()
+}
+/// memory offset of the given index from element `[0,0]`
+pub(crate) fn offset(index: [usize; 2], strides: [isize; 2]) -> isize {
+	index[0] as isize * strides[0] + index[1] as isize * strides[1]
+}
+/// `true` if distinct indices in the given shape reach the same element in memory with the given strides
+pub fn is_aliasing(shape: [usize; 2], strides: [isize; 2]) -> bool {
+	let [rows, columns] = shape;
+	let [row_step, column_step] = strides.map(isize::unsigned_abs);
+	if rows == 0 || columns == 0  {return false}
+	if (rows > 1 && row_step == 0) || (columns > 1 && column_step == 0)  {return true}
+	if rows == 1 || columns == 1  {return false}
+	// the smallest index steps reaching the same offset are `i*row_step == j*column_step` with `i = column_step/gcd` and `j = row_step/gcd`
+	let (mut a, mut b) = (row_step, column_step);
+	while b != 0  {(a, b) = (b, a % b)}
+	column_step / a < rows && row_step / a < columns
+}
+
 pub enum CastError<E> {
 	/// cast failed because matrices do not share the same number of columns
 	ColumnsMismatch,
@@ -254,9 +389,8 @@ impl<A:Array> Matrix<A> {
 		F: FnMut(&A::Element) -> Dst::Element,
 		Dst: ArrayOwned<R=A::R, C=A::C>,
 	{
-		let mut dst = Matrix::new(self.shape());
-		self.map_to(f, &mut dst);
-		dst
+		// safety: indices are in the matrix shape
+		Matrix::from_field(self.shape(), |index|  f(unsafe {self.get_unchecked(index)}))
 	}
 	/// apply a function over all elements and store the result in the given output, avoiding any dynamic allocation
 	pub fn map_to<'o,Dst,F>(&self, mut f: F, dst: &'o mut Matrix<Dst>) -> &'o mut Matrix<Dst>
@@ -265,7 +399,16 @@ impl<A:Array> Matrix<A> {
 		Dst: ArrayMut<R=A::R, C=A::C>,
 	{
 		assert_eq!(self.shape(), dst.shape());
-		dst.set_field(|index|  f(&self[index]))
+		if self.strides() == dst.strides() {
+			if let (Some(src), Some(out)) = (self.as_slice(), dst.as_slice_mut()) {
+				for (out, src) in zip(out, src) {
+					*out = f(src);
+				}
+				return dst
+			}
+		}
+		// safety: both matrices have the same shape
+		dst.set_field(|index|  f(unsafe {self.get_unchecked(index)}))
 	}
 	
 	/// cast this matrix into a different type and dimensionality
@@ -298,17 +441,21 @@ impl<A:Array> Matrix<A> {
 		Dst: ArrayOwned,
 	{
 		let shape = self.shape();
-		let mut dst = Matrix(Dst::empty((
+		let mut dst = Dst::uninit((
 			Dst::R::check(shape[0]).ok_or(CastError::RowsMismatch)?,
 			Dst::C::check(shape[1]).ok_or(CastError::ColumnsMismatch)?,
-			)));
+			));
+		let strides = dst.strides();
+		let ptr = dst.as_mut_ptr();
 		for j in 0 .. shape[1] {
 			for i in 0 .. shape[0] {
 				let index = [i,j];
-				dst[index] = f(&self[index]).map_err(|e| CastError::Element(e))?;
+				// safety: dst has been created with the same shape, elements already written are leaked on error
+				unsafe {(*ptr.offset(offset(index, strides))).write(f(self.get_unchecked(index)).map_err(|e| CastError::Element(e))?)};
 			}
 		}
-		Ok(dst)
+		// safety: every element has been written
+		Ok(Matrix(unsafe {dst.assume_init()}))
 	}
 	/// apply a function over all elements in the array, the shape however must be greater or equal
 	pub fn try_map_to<'o,Dst,E,F>(&self, mut f: F, dst: &'o mut Matrix<Dst>) -> Result<&'o mut Matrix<Dst>, CastError<E>>
@@ -321,7 +468,8 @@ impl<A:Array> Matrix<A> {
 		for j in 0 .. self.columns() {
 			for i in 0 .. self.rows() {
 				let index = [i,j];
-				dst[index] = f(&self[index]).map_err(|e| CastError::Element(e))?;
+				// safety: dst has been checked to be greater or equal in shape
+				unsafe {*dst.get_unchecked_mut(index) = f(self.get_unchecked(index)).map_err(|e| CastError::Element(e))?};
 			}
 		}
 		Ok(dst)
@@ -0,0 +1,259 @@
+/*!
+	Memory-mapped matrices, enabled by the `memmap` feature
+
+	[Mmap] and [MmapMut] map a file in memory instead of reading it, so matrices bigger than the RAM can go through the usual operations, the operating system loading and evicting pages as they are accessed. Files can be
+
+	- raw column-major elements in the machine byte order, with a shape given on opening
+	- `.npy` files in the machine byte order, the shape and order being read from their header. Files in C order are mapped as row-major matrices without copy
+
+	The shape and file size are checked on opening. As for any memory map, the file must not be modified by other means while it is mapped, which is why opening is `unsafe`
+*/
+
+use crate::prelude::*;
+use crate::matrix::*;
+use crate::matrices::*;
+use crate::npy::{NpyElement, NpyError, Header, read_header, write_header};
+
+use core::marker::PhantomData;
+use std::{
+	io,
+	path::Path,
+	fs::{File, OpenOptions},
+	};
+
+
+/// matrix mapping a file read-only
+pub type MmapMatrix<T, R=Dyn, C=Dyn> = Matrix<Mmap<T,R,C>>;
+/// matrix mapping a file for reading and writing
+pub type MmapMatrixMut<T, R=Dyn, C=Dyn> = Matrix<MmapMut<T,R,C>>;
+
+
+/// position of the matrix elements in a mapped file
+struct Layout<R, C> {
+	shape: (R, C),
+	strides: (isize, isize),
+	offset: usize,
+}
+/// number of bytes of the elements of a matrix with the given shape, if it can be addressed
+fn data_size<T>(shape: [usize; 2]) -> Result<usize, NpyError> {
+	shape[0].checked_mul(shape[1])
+		.and_then(|size|  size.checked_mul(size_of::<T>()))
+		.filter(|&size|  size <= isize::MAX as usize)
+		.ok_or(NpyError::Shape(shape.to_vec()))
+}
+/// check a matrix of the given shape and order fits exactly in the file after `offset` bytes
+fn layout<T, R: Dim, C: Dim>(shape: [usize; 2], fortran: bool, offset: usize, file: &File) -> Result<Layout<R, C>, NpyError> {
+	let [rows, columns] = shape;
+	let dims = (
+		R::check(rows).ok_or(NpyError::Shape(shape.to_vec()))?,
+		C::check(columns).ok_or(NpyError::Shape(shape.to_vec()))?,
+		);
+	let required = data_size::<T>(shape)?;
+	let available = (file.metadata()?.len() as usize).saturating_sub(offset);
+	if required != available
+		{return Err(NpyError::Size {required, available})}
+	// maps start on a page boundary, so only the offset can misalign elements
+	if !offset.is_multiple_of(align_of::<T>())
+		{return Err(NpyError::Header("array data is not aligned"))}
+	Ok(Layout {
+		shape: dims,
+		strides: if fortran {(1, rows as isize)} else {(columns as isize, 1)},
+		offset,
+	})
+}
+/// layout of the array in a `.npy` file, which must be in the machine byte order
+fn npy_layout<T: NpyElement, R: Dim, C: Dim>(file: &File) -> Result<Layout<R, C>, NpyError> {
+	let Header {little_endian, fortran, shape, length} = read_header::<T>(io::BufReader::new(file))?;
+	if little_endian != cfg!(target_endian = "little") {
+		let order = if little_endian {'<'} else {'>'};
+		return Err(NpyError::Dtype(format!("{}{}", order, T::DTYPE)))
+	}
+	layout::<T, R, C>(shape, fortran, length, file)
+}
+
+
+/// read-only array in a memory-mapped file
+pub struct Mmap<T: NpyElement, R: Dim=Dyn, C: Dim=Dyn> {
+	layout: Layout<R, C>,
+	map: memmap2::Mmap,
+	element: PhantomData<T>,
+}
+impl<T: NpyElement, R: Dim, C: Dim>
+	Array for Mmap<T,R,C>
+{
+	type Element = T;
+	type R = R;
+	type C = C;
+	fn shape(&self) -> [usize; 2]    {[self.layout.shape.0.value(), self.layout.shape.1.value()]}
+	fn strides(&self) -> [isize; 2]  {[self.layout.strides.0, self.layout.strides.1]}
+	fn as_ptr(&self) -> *const T        {self.map[self.layout.offset ..].as_ptr() as _}
+}
+impl<T: NpyElement, R1: Dim, C1: Dim, R2: Dim, C2: Dim>
+	Compatible<R2,C2> for Mmap<T,R1,C1>
+{
+	type Owned = Dynamic<T,R2,C2>;
+}
+impl<T: NpyElement, R: Dim, C: Dim> Matrix<Mmap<T,R,C>> {
+	/**
+		map a file of raw column-major elements with the given shape
+
+		# Safety
+		the file must not be modified while it is mapped
+	*/
+	pub unsafe fn open_raw(path: impl AsRef<Path>, shape: [usize; 2]) -> Result<Self, NpyError> {
+		let file = File::open(path)?;
+		let layout = layout::<T, R, C>(shape, true, 0, &file)?;
+		Ok(Matrix(Mmap {layout, map: memmap2::Mmap::map(&file)?, element: PhantomData}))
+	}
+	/**
+		map a `.npy` file, its element type and shape must match the requested matrix type
+
+		# Safety
+		the file must not be modified while it is mapped
+	*/
+	pub unsafe fn open_npy(path: impl AsRef<Path>) -> Result<Self, NpyError> {
+		let file = File::open(path)?;
+		let layout = npy_layout::<T, R, C>(&file)?;
+		Ok(Matrix(Mmap {layout, map: memmap2::Mmap::map(&file)?, element: PhantomData}))
+	}
+}
+
+
+/// array in a memory-mapped file, modifications are written back to the file
+pub struct MmapMut<T: NpyElement, R: Dim=Dyn, C: Dim=Dyn> {
+	layout: Layout<R, C>,
+	map: memmap2::MmapMut,
+	element: PhantomData<T>,
+}
+impl<T: NpyElement, R: Dim, C: Dim>
+	Array for MmapMut<T,R,C>
+{
+	type Element = T;
+	type R = R;
+	type C = C;
+	fn shape(&self) -> [usize; 2]    {[self.layout.shape.0.value(), self.layout.shape.1.value()]}
+	fn strides(&self) -> [isize; 2]  {[self.layout.strides.0, self.layout.strides.1]}
+	fn as_ptr(&self) -> *const T        {self.map[self.layout.offset ..].as_ptr() as _}
+}
+impl<T: NpyElement, R: Dim, C: Dim>
+	ArrayMut for MmapMut<T,R,C>
+{
+	fn as_mut_ptr(&mut self) -> *mut T  {self.map[self.layout.offset ..].as_mut_ptr() as _}
+}
+impl<T: NpyElement, R1: Dim, C1: Dim, R2: Dim, C2: Dim>
+	Compatible<R2,C2> for MmapMut<T,R1,C1>
+{
+	type Owned = Dynamic<T,R2,C2>;
+}
+impl<T: NpyElement, R: Dim, C: Dim> Matrix<MmapMut<T,R,C>> {
+	/**
+		same as [`Matrix::<Mmap>::open_raw`] but writable
+
+		# Safety
+		the file must not be accessed by other means while it is mapped
+	*/
+	pub unsafe fn open_raw(path: impl AsRef<Path>, shape: [usize; 2]) -> Result<Self, NpyError> {
+		let file = OpenOptions::new().read(true).write(true).open(path)?;
+		let layout = layout::<T, R, C>(shape, true, 0, &file)?;
+		Ok(Matrix(MmapMut {layout, map: memmap2::MmapMut::map_mut(&file)?, element: PhantomData}))
+	}
+	/**
+		same as [`Matrix::<Mmap>::open_npy`] but writable
+
+		# Safety
+		the file must not be accessed by other means while it is mapped
+	*/
+	pub unsafe fn open_npy(path: impl AsRef<Path>) -> Result<Self, NpyError> {
+		let file = OpenOptions::new().read(true).write(true).open(path)?;
+		let layout = npy_layout::<T, R, C>(&file)?;
+		Ok(Matrix(MmapMut {layout, map: memmap2::MmapMut::map_mut(&file)?, element: PhantomData}))
+	}
+	/**
+		create or truncate a file of raw column-major elements with the given shape, and map it. Elements are initially zero
+
+		# Safety
+		the file must not be accessed by other means while it is mapped
+	*/
+	pub unsafe fn create_raw(path: impl AsRef<Path>, shape: [usize; 2]) -> Result<Self, NpyError> {
+		// checked before the file is truncated
+		let size = data_size::<T>(shape)?;
+		let file = OpenOptions::new().read(true).write(true).create(true).truncate(true).open(path)?;
+		file.set_len(size as u64)?;
+		let layout = layout::<T, R, C>(shape, true, 0, &file)?;
+		Ok(Matrix(MmapMut {layout, map: memmap2::MmapMut::map_mut(&file)?, element: PhantomData}))
+	}
+	/**
+		create or truncate a `.npy` file in column-major order with the given shape, and map it. Elements are initially zero
+
+		# Safety
+		the file must not be accessed by other means while it is mapped
+	*/
+	pub unsafe fn create_npy(path: impl AsRef<Path>, shape: [usize; 2]) -> Result<Self, NpyError> {
+		if cfg!(target_endian = "big")
+			{return Err(NpyError::Dtype(format!(">{}", T::DTYPE)))}
+		// checked before the file is truncated
+		let size = data_size::<T>(shape)?;
+		let mut file = OpenOptions::new().read(true).write(true).create(true).truncate(true).open(path)?;
+		write_header::<T>(&mut file, true, shape)?;
+		let offset = file.metadata()?.len();
+		file.set_len(offset + size as u64)?;
+		let layout = layout::<T, R, C>(shape, true, offset as usize, &file)?;
+		Ok(Matrix(MmapMut {layout, map: memmap2::MmapMut::map_mut(&file)?, element: PhantomData}))
+	}
+	/// write the modifications back to the file, blocking until done
+	pub fn flush(&self) -> io::Result<()>  {self.0.map.flush()}
+}
+
+
+
+#[test]
+fn test_mmap() {
+	use crate::npy;
+	use crate::operators::MulTo;
+
+	let directory = std::env::temp_dir();
+	let name = |suffix: &str|  directory.join(format!("flexalgebra-mmap-{}-{}", std::process::id(), suffix));
+	let a = DMatrix::<f64>::field([3, 4], |[i,j]|  (i + 3*j) as f64);
+
+	// npy files in both orders
+	npy::save(name("a.npy"), &a).unwrap();
+	npy::save(name("t.npy"), &a.transpose()).unwrap();
+	let mapped = unsafe {MmapMatrix::<f64>::open_npy(name("a.npy"))}.unwrap();
+	assert_eq!(mapped.shape(), [3, 4]);
+	assert_eq!(mapped.as_slice(), a.as_slice());
+	let transposed = unsafe {MmapMatrix::<f64>::open_npy(name("t.npy"))}.unwrap();
+	assert_eq!(transposed.strides(), [3, 1]);
+	assert_eq!(transposed[[3,1]], a[[1,3]]);
+	assert!(matches!(unsafe {MmapMatrix::<f32>::open_npy(name("a.npy"))}, Err(NpyError::Dtype(_))));
+	assert!(matches!(unsafe {MmapMatrix::<f64, Stat<4>>::open_npy(name("a.npy"))}, Err(NpyError::Shape(_))));
+
+	// raw files, checked against the shape
+	std::fs::write(name("a.raw"), a.as_slice().unwrap().iter().flat_map(|v|  v.to_ne_bytes()).collect::<Vec<_>>()).unwrap();
+	let raw = unsafe {MmapMatrix::<f64>::open_raw(name("a.raw"), [3, 4])}.unwrap();
+	assert_eq!(raw.column(2).as_slice(), a.column(2).as_slice());
+	assert!(matches!(unsafe {MmapMatrix::<f64>::open_raw(name("a.raw"), [4, 4])},
+		Err(NpyError::Size {required: 128, available: 96})));
+
+	// operations write to mapped outputs
+	let mut product = unsafe {MmapMatrixMut::<f64>::create_npy(name("p.npy"), [3, 3])}.unwrap();
+	mapped.mul_to(&transposed, &mut product);
+	product.flush().unwrap();
+	let expected = &a * &a.transpose();
+	assert_eq!(product.as_slice(), expected.as_slice());
+	drop(product);
+	let loaded: DMatrix<f64> = npy::load(name("p.npy")).unwrap();
+	assert_eq!(loaded.as_slice(), expected.as_slice());
+	let mut scratch = unsafe {MmapMatrixMut::<f32>::create_raw(name("s.raw"), [2, 2])}.unwrap();
+	scratch.set_identity();
+	assert_eq!(scratch.as_slice(), Some(&[1., 0., 0., 1.][..]));
+	// overflowing shapes are rejected before the file is truncated
+	drop(scratch);
+	assert!(matches!(unsafe {MmapMatrixMut::<f32>::create_raw(name("s.raw"), [usize::MAX, 2])}, Err(NpyError::Shape(_))));
+	assert!(matches!(unsafe {MmapMatrixMut::<f64>::create_npy(name("p.npy"), [1 << 32, 1 << 32])}, Err(NpyError::Shape(_))));
+	assert_eq!(std::fs::metadata(name("s.raw")).unwrap().len(), 16);
+	assert_eq!(npy::load::<Dynamic<f64>>(name("p.npy")).unwrap().as_slice(), expected.as_slice());
+
+	for suffix in ["a.npy", "t.npy", "a.raw", "p.npy", "s.raw"] {
+		std::fs::remove_file(name(suffix)).unwrap();
+	}
+}
@@ -0,0 +1,433 @@
+/*!
+	Reading and writing matrices in the [NumPy](https://numpy.org/doc/stable/reference/generated/numpy.lib.format.html) `.npy` format, and `.npz` archives with the `npz` feature
+
+	Matrices are written in `fortran_order` since flexalgebra's storage is column-major, except for row-major views (like a transposed matrix) that are written in C order. Both orders are read back, as well as both endiannesses.
+
+	1-dimensional arrays are read as column vectors. Arrays with more than 2 dimensions are rejected.
+*/
+
+use crate::prelude::*;
+use crate::matrix::*;
+
+use std::{
+	fmt, io,
+	io::{Read, Write},
+	path::Path,
+	fs::File,
+	};
+
+
+const MAGIC: &[u8] = b"\x93NUMPY";
+
+/**
+	matrix element that can be stored in a `.npy` file
+
+	This trait is sealed and only implemented for primitive integers and floats, as memory-mapped files reinterpret their bytes as elements, which must be valid for any bit pattern and have no padding
+*/
+pub trait NpyElement: Element + sealed::Sealed {
+	/// numpy type kind and byte size, as in `f8`
+	const DTYPE: &'static str;
+	/// decode one element from its bytes in the file
+	fn from_npy(bytes: &[u8], little_endian: bool) -> Self;
+	/// encode one element in little endian
+	fn to_npy(&self, out: &mut Vec<u8>);
+}
+mod sealed {
+	/// prevents implementations of [NpyElement](super::NpyElement) outside this crate
+	pub trait Sealed {}
+}
+macro_rules! impl_npy {
+	($t:ty, $dtype:expr) => {
+		impl sealed::Sealed for $t {}
+		impl NpyElement for $t {
+			const DTYPE: &'static str = $dtype;
+			fn from_npy(bytes: &[u8], little_endian: bool) -> Self {
+				let bytes = bytes.try_into().unwrap();
+				if little_endian  {<$t>::from_le_bytes(bytes)}
+				else              {<$t>::from_be_bytes(bytes)}
+			}
+			fn to_npy(&self, out: &mut Vec<u8>) {
+				out.extend_from_slice(&self.to_le_bytes())
+			}
+		}
+	}
+}
+impl_npy!(f32, "f4");
+impl_npy!(f64, "f8");
+impl_npy!(i8, "i1");
+impl_npy!(i16, "i2");
+impl_npy!(i32, "i4");
+impl_npy!(i64, "i8");
+impl_npy!(u8, "u1");
+impl_npy!(u16, "u2");
+impl_npy!(u32, "u4");
+impl_npy!(u64, "u8");
+
+
+/// error while reading a `.npy` or `.npz` file
+#[derive(Debug)]
+pub enum NpyError {
+	/// the underlying reader or writer failed
+	Io(io::Error),
+	/// the file is not a valid npy file
+	Header(&'static str),
+	/// the array element type in the file is not the requested one
+	Dtype(String),
+	/// the array shape in the file cannot fit the requested matrix dimensionality
+	Shape(Vec<usize>),
+	/// the file holds `available` bytes of array data but the shape requires `required`
+	Size {required: usize, available: usize},
+	/// the `.npz` archive is invalid or doesn't contain the requested array
+	#[cfg(feature = "npz")]
+	Zip(zip::result::ZipError),
+}
+impl fmt::Display for NpyError {
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		match self {
+			Self::Io(error) => write!(f, "{}", error),
+			Self::Header(reason) => write!(f, "invalid npy header: {}", reason),
+			Self::Dtype(dtype) => write!(f, "unexpected npy dtype {:?}", dtype),
+			Self::Shape(shape) => write!(f, "npy shape {:?} doesn't fit the matrix dimensionality", shape),
+			Self::Size {required, available} => write!(f, "array data needs {} bytes but the file has {}", required, available),
+			#[cfg(feature = "npz")]
+			Self::Zip(error) => write!(f, "{}", error),
+		}
+	}
+}
+impl std::error::Error for NpyError {}
+impl From<io::Error> for NpyError {
+	fn from(error: io::Error) -> Self  {Self::Io(error)}
+}
+#[cfg(feature = "npz")]
+impl From<zip::result::ZipError> for NpyError {
+	fn from(error: zip::result::ZipError) -> Self  {Self::Zip(error)}
+}
+
+
+/// write a matrix in the `.npy` format
+pub fn write<A, W>(matrix: &Matrix<A>, mut writer: W) -> io::Result<()>
+where
+	A: Array,
+	A::Element: NpyElement,
+	W: Write,
+{
+	let strides = matrix.strides();
+	// row-major memory is better written in C order
+	let fortran = !(strides[1] == 1 && strides[0] != 1);
+	write_header::<A::Element>(&mut writer, fortran, matrix.shape())?;
+
+	let mut buffer = Vec::new();
+	if fortran {
+		for column in matrix.column_iter() {
+			buffer.clear();
+			column.iter().for_each(|value|  value.to_npy(&mut buffer));
+			writer.write_all(&buffer)?;
+		}
+	}
+	else {
+		for row in matrix.row_iter() {
+			buffer.clear();
+			row.iter().for_each(|value|  value.to_npy(&mut buffer));
+			writer.write_all(&buffer)?;
+		}
+	}
+	Ok(())
+}
+
+/// write the header of a `.npy` file for a little endian array of the given shape, padded so that data starts on a 64 bytes boundary
+pub(crate) fn write_header<E: NpyElement>(mut writer: impl Write, fortran: bool, shape: [usize; 2]) -> io::Result<()> {
+	let [rows, columns] = shape;
+	let endianness = if E::DTYPE.ends_with('1') {'|'} else {'<'};
+	let mut header = format!("{{'descr': '{}{}', 'fortran_order': {}, 'shape': ({}, {}), }}",
+		endianness, E::DTYPE,
+		if fortran {"True"} else {"False"},
+		rows, columns);
+	let unpadded = MAGIC.len() + 2 + 2 + header.len() + 1;
+	header.extend(core::iter::repeat_n(' ', unpadded.next_multiple_of(64) - unpadded));
+	header.push('\n');
+
+	writer.write_all(MAGIC)?;
+	writer.write_all(&[1, 0])?;
+	writer.write_all(&u16::try_from(header.len())
+		.map_err(|_| io::Error::new(io::ErrorKind::InvalidInput, "npy header too long"))?
+		.to_le_bytes())?;
+	writer.write_all(header.as_bytes())
+}
+
+/// informations in the header of a `.npy` file
+pub(crate) struct Header {
+	/// byte order of the elements
+	pub little_endian: bool,
+	/// `true` if elements are in column-major order
+	pub fortran: bool,
+	/// array shape as a matrix, vectors being columns
+	pub shape: [usize; 2],
+	/// number of bytes before the array data
+	#[cfg_attr(not(feature = "memmap"), allow(dead_code))]
+	pub length: usize,
+}
+/// read the header of a `.npy` file, checking the element type and that the array is a matrix
+pub(crate) fn read_header<E: NpyElement>(mut reader: impl Read) -> Result<Header, NpyError> {
+	let mut magic = [0; 8];
+	reader.read_exact(&mut magic)?;
+	if &magic[.. MAGIC.len()] != MAGIC
+		{return Err(NpyError::Header("missing magic string"))}
+	let (length, prefix) = match magic[MAGIC.len()] {
+		1 => {
+			let mut length = [0; 2];
+			reader.read_exact(&mut length)?;
+			(u16::from_le_bytes(length) as usize, magic.len() + 2)
+			},
+		2 | 3 => {
+			let mut length = [0; 4];
+			reader.read_exact(&mut length)?;
+			(u32::from_le_bytes(length) as usize, magic.len() + 4)
+			},
+		_ => return Err(NpyError::Header("unsupported version")),
+	};
+	// the length comes from the file, so the header grows with the bytes actually read
+	let mut header = Vec::new();
+	reader.by_ref().take(length as u64).read_to_end(&mut header)?;
+	if header.len() != length
+		{return Err(NpyError::Header("truncated header"))}
+	let header = core::str::from_utf8(&header).map_err(|_| NpyError::Header("header is not text"))?;
+
+	// element type
+	let descr = parse_string(field(header, "descr")?)?;
+	// byte order prefixes are ascii so slicing after them is on a char boundary
+	let (little_endian, dtype) = match descr.chars().next() {
+		None => return Err(NpyError::Header("empty descr")),
+		Some('<' | '|') => (true, &descr[1..]),
+		Some('>') => (false, &descr[1..]),
+		Some('=') => (cfg!(target_endian = "little"), &descr[1..]),
+		Some(_) => (cfg!(target_endian = "little"), descr),
+	};
+	if dtype != E::DTYPE
+		{return Err(NpyError::Dtype(descr.to_owned()))}
+	// memory layout
+	let fortran = match field(header, "fortran_order")? {
+		value if value.starts_with("True") => true,
+		value if value.starts_with("False") => false,
+		_ => return Err(NpyError::Header("invalid fortran_order")),
+	};
+	let shape = parse_shape(field(header, "shape")?)?;
+	let [rows, columns] = match shape[..] {
+		[] => [1, 1],
+		[rows] => [rows, 1],
+		[rows, columns] => [rows, columns],
+		_ => return Err(NpyError::Shape(shape)),
+	};
+	Ok(Header {little_endian, fortran, shape: [rows, columns], length: prefix + length})
+}
+
+/// read a matrix from the `.npy` format, the element type and shape must match the requested matrix type
+pub fn read<A, R>(mut reader: R) -> Result<Matrix<A>, NpyError>
+where
+	A: ArrayOwned,
+	A::Element: NpyElement,
+	R: Read,
+{
+	let Header {little_endian, fortran, shape, ..} = read_header::<A::Element>(&mut reader)?;
+	let [rows, columns] = shape;
+	if A::R::check(rows).is_none() || A::C::check(columns).is_none()
+		{return Err(NpyError::Shape(shape.to_vec()))}
+
+	// data
+	let size = A::Element::DTYPE[1..].parse::<usize>().unwrap();
+	let required = rows.checked_mul(columns)
+		.and_then(|len|  len.checked_mul(size))
+		.ok_or(NpyError::Header("shape too big"))?;
+	// only allocate what the file actually contains
+	let mut data = Vec::new();
+	reader.take(required as u64).read_to_end(&mut data)?;
+	if data.len() < required
+		{return Err(NpyError::Size {required, available: data.len()})}
+	let data = data.chunks_exact(size)
+		.map(|bytes|  A::Element::from_npy(bytes, little_endian))
+		.collect::<Vec<_>>();
+	if fortran  {Ok(Matrix::from_field(shape, |[i,j]|  data[i + j*rows].clone()))}
+	else        {Ok(Matrix::from_field(shape, |[i,j]|  data[i*columns + j].clone()))}
+}
+
+/// value text of the given key in the header dictionnary
+fn field<'h>(header: &'h str, key: &str) -> Result<&'h str, NpyError> {
+	let start = header.find(&format!("'{}'", key))
+		.or_else(|| header.find(&format!("\"{}\"", key)))
+		.ok_or(NpyError::Header("missing header field"))?;
+	let rest = &header[start + key.len() + 2 ..];
+	let colon = rest.find(':').ok_or(NpyError::Header("missing header value"))?;
+	Ok(rest[colon+1 ..].trim_start())
+}
+/// quoted string at the start of the given text
+fn parse_string(text: &str) -> Result<&str, NpyError> {
+	let quote = text.chars().next().filter(|c| *c == '\'' || *c == '"')
+		.ok_or(NpyError::Header("expected a string"))?;
+	let end = text[1..].find(quote).ok_or(NpyError::Header("unterminated string"))?;
+	Ok(&text[1 .. end+1])
+}
+/// python tuple of integers at the start of the given text
+fn parse_shape(text: &str) -> Result<Vec<usize>, NpyError> {
+	let end = text.find(')').filter(|_| text.starts_with('('))
+		.ok_or(NpyError::Header("expected a shape tuple"))?;
+	text[1 .. end].split(',')
+		.map(str::trim)
+		.filter(|item|  !item.is_empty())
+		.map(|item|  item.parse().map_err(|_| NpyError::Header("invalid shape")))
+		.collect()
+}
+
+/// write a matrix in a new `.npy` file
+pub fn save<A>(path: impl AsRef<Path>, matrix: &Matrix<A>) -> io::Result<()>
+where
+	A: Array,
+	A::Element: NpyElement,
+{
+	let mut file = io::BufWriter::new(File::create(path)?);
+	write(matrix, &mut file)?;
+	file.flush()
+}
+
+/// read a matrix from a `.npy` file
+pub fn load<A>(path: impl AsRef<Path>) -> Result<Matrix<A>, NpyError>
+where
+	A: ArrayOwned,
+	A::Element: NpyElement,
+{
+	read(io::BufReader::new(File::open(path)?))
+}
+
+
+/**
+	writer for `.npz` archives of named matrices, as produced by `numpy.savez`
+
+	Matrices of different types can be added one after the other, before calling [NpzWriter::finish]
+*/
+#[cfg(feature = "npz")]
+pub struct NpzWriter<W: Write + io::Seek> {
+	zip: zip::ZipWriter<W>,
+	options: zip::write::SimpleFileOptions,
+}
+#[cfg(feature = "npz")]
+impl<W: Write + io::Seek> NpzWriter<W> {
+	/// start an archive with uncompressed entries, like `numpy.savez`
+	pub fn new(writer: W) -> Self  {Self {
+		zip: zip::ZipWriter::new(writer),
+		options: zip::write::SimpleFileOptions::default().compression_method(zip::CompressionMethod::Stored),
+	}}
+	/// start an archive with compressed entries, like `numpy.savez_compressed`
+	pub fn compressed(writer: W) -> Self  {Self {
+		zip: zip::ZipWriter::new(writer),
+		options: zip::write::SimpleFileOptions::default().compression_method(zip::CompressionMethod::Deflated),
+	}}
+	/// add a matrix to the archive with the given name
+	pub fn add<A>(&mut self, name: &str, matrix: &Matrix<A>) -> Result<&mut Self, NpyError>
+	where
+		A: Array,
+		A::Element: NpyElement,
+	{
+		self.zip.start_file(format!("{}.npy", name), self.options)?;
+		write(matrix, &mut self.zip)?;
+		Ok(self)
+	}
+	/// write the archive directory and return the underlying writer
+	pub fn finish(self) -> Result<W, NpyError> {
+		Ok(self.zip.finish()?)
+	}
+}
+
+/// reader for `.npz` archives of named matrices, as produced by `numpy.savez`
+#[cfg(feature = "npz")]
+pub struct NpzReader<R: Read + io::Seek> {
+	zip: zip::ZipArchive<R>,
+}
+#[cfg(feature = "npz")]
+impl<R: Read + io::Seek> NpzReader<R> {
+	/// open an archive
+	pub fn new(reader: R) -> Result<Self, NpyError> {
+		Ok(Self {zip: zip::ZipArchive::new(reader)?})
+	}
+	/// names of the arrays in the archive
+	pub fn names(&self) -> impl Iterator<Item=&str> {
+		self.zip.file_names().map(|name|  name.strip_suffix(".npy").unwrap_or(name))
+	}
+	/// read the matrix with the given name
+	pub fn read<A>(&mut self, name: &str) -> Result<Matrix<A>, NpyError>
+	where
+		A: ArrayOwned,
+		A::Element: NpyElement,
+	{
+		read(self.zip.by_name(&format!("{}.npy", name))?)
+	}
+}
+
+
+
+#[test]
+fn test_npy() {
+	use crate::matrices::*;
+
+	let a = SMatrix::from([[1.,2.], [3.,4.], [5.,6.]]);
+	let mut file = Vec::new();
+	write(&a, &mut file).unwrap();
+	assert_eq!(file.len() % 64, 48);
+	assert!(file.starts_with(b"\x93NUMPY\x01\x00\x76\x00{'descr': '<f8', 'fortran_order': True, 'shape': (2, 3), }"));
+	let b: SMatrix<f64,2,3> = read(file.as_slice()).unwrap();
+	assert_eq!(a.as_slice(), b.as_slice());
+	assert!(matches!(read::<Static<f64,3,2>, _>(file.as_slice()), Err(NpyError::Shape(_))));
+	assert!(matches!(read::<Dynamic<f32>, _>(file.as_slice()), Err(NpyError::Dtype(_))));
+
+	// row-major views are written in C order
+	let mut file = Vec::new();
+	write(&a.transpose(), &mut file).unwrap();
+	assert!(file.starts_with(b"\x93NUMPY\x01\x00\x76\x00{'descr': '<f8', 'fortran_order': False, 'shape': (3, 2), }"));
+	let b: DMatrix<f64> = read(file.as_slice()).unwrap();
+	assert_eq!(b.shape(), [3,2]);
+	assert_eq!(b.as_slice(), Some(&[1.,3.,5.,  2.,4.,6.][..]));
+
+	// big endian vector, as numpy writes with `dtype='>i2'`
+	let mut file = b"\x93NUMPY\x01\x00\x76\x00{'descr': '>i2', 'fortran_order': False, 'shape': (3,), }".to_vec();
+	file.extend(core::iter::repeat_n(b' ', 128 - 1 - file.len()));
+	file.push(b'\n');
+	file.extend([0,1, 1,0, 255,255]);
+	let b: DVector<i16> = read(file.as_slice()).unwrap();
+	assert_eq!(b.as_slice(), Some(&[1, 256, -1][..]));
+	// truncated data
+	assert!(matches!(read::<Dynamic<i16,Dyn,Stat<1>>, _>(&file[.. file.len()-1]), Err(NpyError::Size {required: 6, available: 5})));
+
+	// malformed headers are errors rather than panics
+	let header = |descr: &str, shape: &str| {
+		let mut file = b"\x93NUMPY\x01\x00\x76\x00".to_vec();
+		file.extend(format!("{{'descr': '{}', 'fortran_order': False, 'shape': {}, }}", descr, shape).bytes());
+		file.resize(127, b' ');
+		file.push(b'\n');
+		file
+	};
+	assert!(matches!(read::<Dynamic<f64>, _>(header("", "(1, 1)").as_slice()), Err(NpyError::Header(_))));
+	assert!(matches!(read::<Dynamic<f64>, _>(header("é", "(1, 1)").as_slice()), Err(NpyError::Dtype(_))));
+	assert!(matches!(read::<Dynamic<f64>, _>(header("f8", &format!("({}, {})", usize::MAX, 2)).as_slice()), Err(NpyError::Header(_))));
+	// a header length bigger than the file is not allocated upfront
+	assert!(matches!(read::<Dynamic<f64>, _>(&b"\x93NUMPY\x02\x00\xff\xff\xff\xff{}"[..]), Err(NpyError::Header(_))));
+	// native byte order
+	let mut file = header("=f8", "(1,)");
+	file.extend(2f64.to_ne_bytes());
+	assert_eq!(read::<Dynamic<f64>, _>(file.as_slice()).unwrap()[[0,0]], 2.);
+}
+#[cfg(feature = "npz")]
+#[test]
+fn test_npz() {
+	use crate::matrices::*;
+
+	let a = SMatrix::from([[1.,2.], [3.,4.], [5.,6.]]);
+	let b = DVector::from(vec![1u8, 2, 3]);
+	let mut archive = NpzWriter::compressed(io::Cursor::new(Vec::new()));
+	archive.add("a", &a).unwrap().add("b", &b).unwrap();
+	let file = archive.finish().unwrap();
+
+	let mut archive = NpzReader::new(file).unwrap();
+	let mut names = archive.names().collect::<Vec<_>>();
+	names.sort();
+	assert_eq!(names, ["a", "b"]);
+	assert_eq!(archive.read::<Static<f64,2,3>>("a").unwrap().as_slice(), a.as_slice());
+	assert_eq!(archive.read::<Dynamic<u8,Dyn,Stat<1>>>("b").unwrap().as_slice(), b.as_slice());
+	assert!(archive.read::<Dynamic<u8>>("c").is_err());
+}
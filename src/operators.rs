@@ -9,6 +9,7 @@ use super::{
 use core::{
     ops::*,
     fmt, cmp::max,
+    iter::zip,
     };
 
 
@@ -32,6 +33,22 @@ pub trait DivTo<T,O> {
 
 
 
+/**
+	element of the matrix product `left * right` at the given index
+
+	# Safety
+	the index must be in the product shape and the columns of `left` must match the rows of `right`
+*/
+pub(crate) unsafe fn product_element<L, R>(left: &Matrix<L>, right: &Matrix<R>, [i, j]: [usize; 2]) -> L::Element
+where
+	L: Array,
+	R: Array<Element=L::Element>,
+	L::Element: Scalar,
+{
+	(0 .. left.shape()[1])
+		.map(|d|  left.get_unchecked([i, d]).clone() * right.get_unchecked([d, j]).clone())
+		.reduce(Add::add).unwrap()
+}
 impl<L,R,RO,CO> 
 	Mul<&Matrix<R>> for &Matrix<L>
 where 
@@ -42,9 +59,9 @@ where
 	type Output = Matrix<L::Owned>;
 	/// matrix product
 	fn mul(self, right: &Matrix<R>) -> Self::Output {
-		let mut new = Matrix::new([self.shape()[0], right.shape()[1]]);
-		self.mul_to(right, &mut new);
-		new
+		assert_eq!(self.shape()[1], right.shape()[0]);
+		// safety: shapes have been checked above
+		Matrix::from_field([self.shape()[0], right.shape()[1]], |index|  unsafe {product_element(self, right, index)})
 	}
 }
 impl<L,R,O>
@@ -58,11 +75,9 @@ where
     /// matrix product without dynamic allocation
 	fn mul_to<'o>(&self, right: &Matrix<R>, out: &'o mut Matrix<O>) -> &'o mut Matrix<O> {
 		assert_eq!(self.shape()[1], right.shape()[0]);
-		out.set_field(|i|  
-				(0 .. self.shape()[1])
-				.map(|d|  self[[i[0], d]].clone() * right[[d, i[1]]].clone())
-				.reduce(Add::add).unwrap()
-				)
+		assert_eq!(out.shape(), [self.shape()[0], right.shape()[1]]);
+		// safety: shapes have been checked above
+		out.set_field(|index|  unsafe {product_element(self, right, index)})
 	}
 }
 
@@ -78,9 +93,9 @@ macro_rules! elementwise_binop {
             type Output = Matrix<L::Owned>;
             /// elementwise operation
             fn $method(self, right: &Matrix<R>) -> Self::Output {
-                let mut new = Matrix::new(self.shape());
-                self.$methodto(right, &mut new);
-                new
+                assert_eq!(self.shape(), right.shape());
+                // safety: shapes have been checked above
+                Matrix::from_field(self.shape(), |i|  unsafe {self.get_unchecked(i).clone().$method(right.get_unchecked(i).clone())})
             }
         }
         impl<L,R,O>
@@ -95,7 +110,18 @@ macro_rules! elementwise_binop {
             fn $methodto<'o>(&self, right: &Matrix<R>, out: &'o mut Matrix<O>) -> &'o mut Matrix<O>
             {
                 assert_eq!(self.shape(), right.shape());
-                out.set_field(|i|  self[i].clone().$method(right[i].clone()))
+                assert_eq!(self.shape(), out.shape());
+                let strides = out.strides();
+                if self.strides() == strides && right.strides() == strides {
+                    if let (Some(l), Some(r), Some(o)) = (self.as_slice(), right.as_slice(), out.as_slice_mut()) {
+                        for (o, (l, r)) in zip(o, zip(l, r)) {
+                            *o = l.clone().$method(r.clone());
+                        }
+                        return out
+                    }
+                }
+                // safety: shapes have been checked above
+                out.set_field(|i|  unsafe {self.get_unchecked(i).clone().$method(right.get_unchecked(i).clone())})
             }
         }
         impl<L,R>
@@ -108,8 +134,16 @@ macro_rules! elementwise_binop {
             /// inplace operation without dynamic allocation
             fn $methodassign(&mut self, right: &Matrix<R>) {
                 assert_eq!(self.shape(), right.shape());
-                let out = unsafe {core::mem::transmute::<&mut Self, &mut Self>(self)};
-                self.$methodto(right, out);
+                if self.strides() == right.strides() {
+                    if let (Some(r), Some(l)) = (right.as_slice(), self.as_slice_mut()) {
+                        for (l, r) in zip(l, r) {
+                            *l = l.clone().$method(r.clone());
+                        }
+                        return
+                    }
+                }
+                // safety: shapes have been checked above
+                self.update_field(|i, l|  *l = l.clone().$method(unsafe {right.get_unchecked(i)}.clone()));
             }
         }
     }
@@ -128,9 +162,8 @@ macro_rules! scalar_binop {
             type Output = Matrix<L::Owned>;
             /// scalar operation
             fn $method(self, right: R) -> Self::Output {
-                let mut new = Matrix::new(self.shape());
-                self.$methodto(&right, &mut new);
-                new
+                // safety: indices are in the matrix shape
+                Matrix::from_field(self.shape(), |i|  unsafe {self.get_unchecked(i)}.clone().$method(right.clone()))
             }
         }
         impl<L,R,O>
@@ -142,7 +175,17 @@ macro_rules! scalar_binop {
         {
             /// scalar operation without dynamic allocation
             fn $methodto<'o>(&self, right: &R, out: &'o mut Matrix<O>) -> &'o mut Matrix<O> {
-                out.set_field(|i|  self[i].clone().$method(right.clone()))
+                assert_eq!(self.shape(), out.shape());
+                if self.strides() == out.strides() {
+                    if let (Some(l), Some(o)) = (self.as_slice(), out.as_slice_mut()) {
+                        for (o, l) in zip(o, l) {
+                            *o = l.clone().$method(right.clone());
+                        }
+                        return out
+                    }
+                }
+                // safety: shapes have been checked above
+                out.set_field(|i|  unsafe {self.get_unchecked(i)}.clone().$method(right.clone()))
             }
         }
         impl<L,R>
@@ -153,8 +196,13 @@ macro_rules! scalar_binop {
         {
             /// inplace operation without dynamic allocation
             fn $methodassign(&mut self, right: R) {
-                let out = unsafe {core::mem::transmute::<&mut Self, &mut Self>(self)};
-                self.$methodto(&right, out);
+                if let Some(l) = self.as_slice_mut() {
+                    for l in l {
+                        *l = l.clone().$method(right.clone());
+                    }
+                    return
+                }
+                self.update_field(|_, l|  *l = l.clone().$method(right.clone()));
             }
         }
     }
@@ -178,6 +226,17 @@ fn test_operators_static() {
     assert!((&a * &b).as_slice() == Some(&[1.0, 2.0, 3.0]));
     let c = SVector::<f32,3>::from([5.,6.,7.]);
     assert!((&(&a * &b) + &c).as_slice() == Some(&[6.,8.,10.]));
+    
+    // strided operands do not take the contiguous path
+    let a = SMatrix::<f32,2,3>::from([[1.,2.], [3.,4.], [5.,6.]]);
+    let mut b = SMatrix::<f32,3,2>::ones();
+    b += &a.transpose();
+    assert!(b.as_slice() == Some(&[2.,4.,6.,  3.,5.,7.]));
+    b -= &a.transpose();
+    b *= 2.;
+    assert!(b.iter().all(|&v| v == 2.));
+    let c = &a.transpose() + &b;
+    assert!(c.as_slice() == Some(&[3.,5.,7.,  4.,6.,8.]));
 }
 #[test]
 fn test_operators_dynamic() {
@@ -190,6 +249,9 @@ fn test_operators_dynamic() {
     assert!((&a * &b).as_slice() == Some(&[1.0, 2.0, 3.0]));
     let c = DVector::<f32>::from(vec![5.,6.,7.]);
     assert!((&(&a * &b) + &c).as_slice() == Some(&[6.,8.,10.]));
+    // empty matrices go through the contiguous paths
+    let empty = DMatrix::<f32>::full([0,3], 1.);
+    assert!((&empty + &empty).as_slice() == Some(&[]));
 }
 
 
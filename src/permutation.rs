@@ -0,0 +1,259 @@
+/*!
+	Permutation matrices, storing only their permutation indices
+
+	Products with dense matrices reorder their rows or columns in `O(n m)` without multiplication, and the permutations can also be applied in place on any mutable matrix or view.
+*/
+
+use crate::prelude::*;
+use crate::matrix::*;
+use crate::operators::*;
+use crate::workspace::{Workspace, ScratchMatrix, MulWithWorkspace};
+use crate::sparse::ordering::{is_permutation, inverse};
+
+use core::ops::Mul;
+
+
+/**
+	square matrix with a single one in each row and column
+
+	Row `i` of `P A` is row `indices()[i]` of `A`, so `P[i, indices()[i]] = 1`. The parity of the permutation is kept to give its sign and determinant in `O(1)`
+*/
+#[derive(Clone, Debug, Eq, PartialEq, Hash)]
+pub struct PermutationMatrix {
+	indices: Vec<usize>,
+	odd: bool,
+}
+impl PermutationMatrix {
+	/// identity permutation of the given size
+	pub fn identity(size: usize) -> Self  {Self {indices: (0 .. size).collect(), odd: false}}
+	/// permutation from its indices, or `None` if they are not a permutation
+	pub fn try_from_indices(indices: Vec<usize>) -> Option<Self> {
+		if !is_permutation(&indices)  {return None}
+		// each cycle of length l is made of l-1 transpositions
+		let mut visited = vec![false; indices.len()];
+		let mut transpositions = 0;
+		for start in 0 .. indices.len() {
+			// only count cycles not walked from a previous start
+			if visited[start]  {continue}
+			let mut i = start;
+			while !visited[i] {
+				visited[i] = true;
+				i = indices[i];
+				transpositions += 1;
+			}
+			transpositions -= 1;
+		}
+		Some(Self {indices, odd: transpositions % 2 == 1})
+	}
+	/**
+		permutation from successive row swaps: row `i` is swapped with row `swaps[i]`, for increasing `i`
+
+		This is the pivots format of LAPACK and [BandedLu](crate::banded::BandedLu)
+	*/
+	pub fn from_swaps(size: usize, swaps: &[usize]) -> Self {
+		let mut new = Self::identity(size);
+		for (i, &j) in swaps.iter().enumerate()  {new.swap(i, j)}
+		new
+	}
+	/// number of rows and columns
+	pub fn size(&self) -> usize  {self.indices.len()}
+	/// permutation indices: row `i` of `P A` is row `indices()[i]` of `A`
+	pub fn indices(&self) -> &[usize]  {&self.indices}
+	/// retreive the permutation indices
+	pub fn into_indices(self) -> Vec<usize>  {self.indices}
+	/// true if the permutation is made of an odd number of transpositions
+	pub fn is_odd(&self) -> bool  {self.odd}
+	/// `1` for even permutations and `-1` for odd ones
+	pub fn sign(&self) -> i8  {if self.odd {-1} else {1}}
+	/// determinant of the permutation matrix, its sign
+	pub fn determinant<T: Scalar>(&self) -> T {
+		if self.odd {T::zero() - T::one()} else {T::one()}
+	}
+	/// swap two rows of the permutation matrix
+	pub fn swap(&mut self, i: usize, j: usize) {
+		if i != j {
+			self.indices.swap(i, j);
+			self.odd = !self.odd;
+		}
+	}
+	/// inverse permutation, it is also the transpose
+	pub fn inverse(&self) -> Self  {Self {indices: inverse(&self.indices), odd: self.odd}}
+	/// transpose permutation, it is also the inverse
+	pub fn transpose(&self) -> Self  {self.inverse()}
+	/// element at the given index
+	pub fn get<T: Scalar>(&self, [i, j]: [usize; 2]) -> T {
+		if self.indices[i] == j {T::one()} else {T::zero()}
+	}
+
+	/// permute the rows of the given matrix in place, computing `self * matrix`
+	pub fn permute_rows<A: ArrayMut>(&self, matrix: &mut Matrix<A>) {
+		assert_eq!(matrix.rows(), self.size());
+		permute(&self.indices, matrix);
+	}
+	/// permute the columns of the given matrix in place, computing `matrix * self`
+	pub fn permute_columns<A: ArrayMut>(&self, matrix: &mut Matrix<A>) {
+		assert_eq!(matrix.columns(), self.size());
+		// column j of A P is column k of A where indices[k] = j
+		permute(&inverse(&self.indices), &mut matrix.transpose_mut());
+	}
+}
+
+/// move row `sources[i]` to row `i`, following the permutation cycles
+fn permute<A: ArrayMut>(sources: &[usize], matrix: &mut Matrix<A>) {
+	let mut visited = vec![false; sources.len()];
+	for start in 0 .. sources.len() {
+		if visited[start] || sources[start] == start  {continue}
+		let mut i = start;
+		while !visited[i] {
+			visited[i] = true;
+			i = sources[i];
+		}
+		for c in 0 .. matrix.columns() {
+			let saved = matrix[[start,c]].clone();
+			let mut i = start;
+			while sources[i] != start {
+				matrix[[i,c]] = matrix[[sources[i],c]].clone();
+				i = sources[i];
+			}
+			matrix[[i,c]] = saved;
+		}
+	}
+}
+
+
+impl<R,O>
+	MulTo<Matrix<R>, Matrix<O>> for PermutationMatrix
+where
+	R: Array<Element=O::Element>,
+	O: ArrayMut,
+{
+	/// permute the rows of the right matrix
+	fn mul_to<'o>(&self, right: &Matrix<R>, out: &'o mut Matrix<O>) -> &'o mut Matrix<O> {
+		assert_eq!(right.rows(), self.size());
+		assert_eq!(out.shape(), right.shape());
+		out.set_field(|[i, j]|  right[[self.indices[i], j]].clone())
+	}
+}
+impl<L,O>
+	MulTo<PermutationMatrix, Matrix<O>> for Matrix<L>
+where
+	L: Array<Element=O::Element>,
+	O: ArrayMut,
+{
+	/// permute the columns of the left matrix
+	fn mul_to<'o>(&self, right: &PermutationMatrix, out: &'o mut Matrix<O>) -> &'o mut Matrix<O> {
+		assert_eq!(self.columns(), right.size());
+		assert_eq!(out.shape(), self.shape());
+		for (k, &j) in right.indices.iter().enumerate() {
+			for i in 0 .. self.rows() {
+				out[[i,j]] = self[[i,k]].clone();
+			}
+		}
+		out
+	}
+}
+impl<R,RR,RC>
+	Mul<&Matrix<R>> for &PermutationMatrix
+where
+	R: Array<R=RR, C=RC> + Compatible<RR, RC>,
+{
+	type Output = Matrix<R::Owned>;
+	/// permute the rows of the right matrix
+	fn mul(self, right: &Matrix<R>) -> Self::Output {
+		assert_eq!(right.rows(), self.size());
+		Matrix::from_field(right.shape(), |[i, j]|  right[[self.indices[i], j]].clone())
+	}
+}
+impl<L,LR,LC>
+	Mul<&PermutationMatrix> for &Matrix<L>
+where
+	L: Array<R=LR, C=LC> + Compatible<LR, LC>,
+{
+	type Output = Matrix<L::Owned>;
+	/// permute the columns of the left matrix
+	fn mul(self, right: &PermutationMatrix) -> Self::Output {
+		assert_eq!(self.columns(), right.size());
+		// column j of A P is column k of A where indices[k] = j
+		let inverse = inverse(&right.indices);
+		Matrix::from_field(self.shape(), |[i, j]|  self[[i, inverse[j]]].clone())
+	}
+}
+impl<'w,R>
+	MulWithWorkspace<'w, Matrix<R>> for PermutationMatrix
+where
+	R: Array,
+{
+	type Output = ScratchMatrix<'w, R::Element, R::R, R::C>;
+	/// permute the rows of the right matrix
+	fn mul_with_workspace(&self, right: &Matrix<R>, workspace: &'w Workspace) -> Self::Output {
+		// the copy only provides initialized elements to overwrite, since elements may not have a zero
+		let mut out = right.owned_with_workspace(workspace);
+		self.mul_to(right, &mut out);
+		out
+	}
+}
+impl<'w,L>
+	MulWithWorkspace<'w, PermutationMatrix> for Matrix<L>
+where
+	L: Array,
+{
+	type Output = ScratchMatrix<'w, L::Element, L::R, L::C>;
+	/// permute the columns of the left matrix
+	fn mul_with_workspace(&self, right: &PermutationMatrix, workspace: &'w Workspace) -> Self::Output {
+		let mut out = self.owned_with_workspace(workspace);
+		self.mul_to(right, &mut out);
+		out
+	}
+}
+impl Mul<&PermutationMatrix> for &PermutationMatrix {
+	type Output = PermutationMatrix;
+	/// composition of permutations
+	fn mul(self, right: &PermutationMatrix) -> PermutationMatrix {
+		assert_eq!(self.size(), right.size());
+		PermutationMatrix {
+			indices: self.indices.iter().map(|&i|  right.indices[i]).collect(),
+			odd: self.odd != right.odd,
+		}
+	}
+}
+
+
+
+#[test]
+fn test_permutation() {
+	use crate::matrices::*;
+
+	let permutation = PermutationMatrix::try_from_indices(vec![2, 0, 1, 3]).unwrap();
+	assert!(!permutation.is_odd());
+	assert_eq!(permutation.determinant::<f64>(), 1.);
+	assert!(PermutationMatrix::try_from_indices(vec![2, 0, 2, 3]).is_none());
+	let transposition = PermutationMatrix::try_from_indices(vec![1, 0]).unwrap();
+	assert!(transposition.is_odd());
+	assert_eq!(transposition.determinant::<f64>(), -1.);
+	assert_eq!(PermutationMatrix::try_from_indices(vec![1, 0, 3, 2]).unwrap().sign(), 1);
+	assert_eq!(PermutationMatrix::try_from_indices(vec![3, 0, 1, 2]).unwrap().sign(), -1);
+	let swapped = PermutationMatrix::from_swaps(4, &[1, 1, 3, 3]);
+	assert_eq!(swapped.indices(), [1, 0, 3, 2]);
+	assert_eq!(swapped.sign(), 1);
+	assert_eq!(PermutationMatrix::from_swaps(3, &[2, 1, 2]).sign(), -1);
+
+	let dense = SMatrix::<f64,4,4>::field(|index|  permutation.get(index));
+	let matrix = SMatrix::<f64,4,3>::field(|[i,j]|  (i + 4*j) as f64);
+	let permuted = &permutation * &matrix;
+	assert_eq!(permuted.as_slice(), (&dense * &matrix).as_slice());
+	assert_eq!(permuted.column(0).owned().as_slice(), Some(&[2., 0., 1., 3.][..]));
+	let mut inplace = matrix.clone();
+	permutation.permute_rows(&mut inplace.view_mut());
+	assert_eq!(inplace.as_slice(), permuted.as_slice());
+
+	let matrix = SMatrix::<f64,3,4>::field(|[i,j]|  (i + 3*j) as f64);
+	let permuted = &matrix * &permutation;
+	assert_eq!(permuted.as_slice(), (&matrix * &dense).as_slice());
+	let mut inplace = matrix.clone();
+	permutation.permute_columns(&mut inplace);
+	assert_eq!(inplace.as_slice(), permuted.as_slice());
+
+	let identity = &permutation * &permutation.inverse();
+	assert_eq!(identity, PermutationMatrix::identity(4));
+	assert_eq!((&permutation * &swapped).sign(), swapped.sign());
+}
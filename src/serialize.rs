@@ -0,0 +1,204 @@
+/*!
+	Serialization and deserialization of matrices with [serde](https://serde.rs), enabled by the `serde` feature
+	
+	Any matrix whose elements are serializable can be serialized, whatever its array type. Any matrix based on an [ArrayOwned] can be deserialized, its shape being checked against the array [Dim]s
+	
+	Two formats are provided:
+	
+	- the compact flat format, used by default: a struct `{shape: [rows, columns], data: [...]}` with data in column-major order
+	- the nested rows format in module [rows], compatible with JSON arrays of arrays. It is selected on a field using `#[serde(with = "flexalgebra::serialize::rows")]`
+*/
+
+use crate::prelude::*;
+use crate::matrix::*;
+
+use core::fmt;
+use core::marker::PhantomData;
+use serde::{
+	ser::{Serialize, Serializer, SerializeStruct},
+	de::{self, Deserialize, Deserializer, Visitor, SeqAccess, MapAccess},
+	};
+
+
+/// column-major sequence of elements of a matrix
+struct Flat<'t, A:Array> (&'t Matrix<A>);
+impl<A:Array> Serialize for Flat<'_,A>
+where A::Element: Serialize
+{
+	fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+		serializer.collect_seq(self.0.iter())
+	}
+}
+
+impl<A:Array> Serialize for Matrix<A>
+where A::Element: Serialize
+{
+	/// serialize in the flat format `{shape, data}`
+	fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+		let mut state = serializer.serialize_struct("Matrix", 2)?;
+		state.serialize_field("shape", &self.shape())?;
+		state.serialize_field("data", &Flat(self))?;
+		state.end()
+	}
+}
+
+/// build a matrix from its shape and column-major data, checking them against the array dimensionality
+fn assemble<A: ArrayOwned, E: de::Error>(shape: [usize; 2], data: Vec<A::Element>) -> Result<Matrix<A>, E> {
+	A::R::check(shape[0]).ok_or_else(|| E::custom(format_args!("matrix cannot have {} rows", shape[0])))?;
+	A::C::check(shape[1]).ok_or_else(|| E::custom(format_args!("matrix cannot have {} columns", shape[1])))?;
+	let len = shape[0].checked_mul(shape[1])
+		.ok_or_else(|| E::custom(format_args!("matrix shape {}x{} overflows", shape[0], shape[1])))?;
+	if data.len() != len
+		{return Err(E::invalid_length(data.len(), &"as many elements as the matrix shape"))}
+	// elements are moved in column-major order, as the field is called
+	let mut data = data.into_iter();
+	Ok(Matrix::from_field(shape, |_|  data.next().unwrap()))
+}
+
+impl<'de, A:ArrayOwned> Deserialize<'de> for Matrix<A>
+where A::Element: Deserialize<'de>
+{
+	/// deserialize from the flat format `{shape, data}`
+	fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+		deserializer.deserialize_struct("Matrix", &["shape", "data"], FlatVisitor(PhantomData))
+	}
+}
+
+/// field names of the flat format, deserialized as identifiers so that keys need not be borrowed from the input
+enum Field {Shape, Data}
+impl<'de> Deserialize<'de> for Field {
+	fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+		struct FieldVisitor;
+		impl Visitor<'_> for FieldVisitor {
+			type Value = Field;
+			fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+				write!(f, "`shape` or `data`")
+			}
+			fn visit_str<E: de::Error>(self, key: &str) -> Result<Field, E> {
+				match key {
+					"shape" => Ok(Field::Shape),
+					"data" => Ok(Field::Data),
+					_ => Err(de::Error::unknown_field(key, &["shape", "data"])),
+				}
+			}
+		}
+		deserializer.deserialize_identifier(FieldVisitor)
+	}
+}
+
+struct FlatVisitor<A> (PhantomData<A>);
+impl<'de, A:ArrayOwned> Visitor<'de> for FlatVisitor<A>
+where A::Element: Deserialize<'de>
+{
+	type Value = Matrix<A>;
+	
+	fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+		write!(f, "a matrix as a struct with shape and data")
+	}
+	fn visit_seq<V: SeqAccess<'de>>(self, mut seq: V) -> Result<Self::Value, V::Error> {
+		let shape = seq.next_element()?.ok_or_else(|| de::Error::invalid_length(0, &self))?;
+		let data = seq.next_element()?.ok_or_else(|| de::Error::invalid_length(1, &self))?;
+		assemble(shape, data)
+	}
+	fn visit_map<V: MapAccess<'de>>(self, mut map: V) -> Result<Self::Value, V::Error> {
+		let mut shape = None;
+		let mut data = None;
+		while let Some(key) = map.next_key()? {
+			match key {
+				Field::Shape => {
+					if shape.is_some()  {return Err(de::Error::duplicate_field("shape"))}
+					shape = Some(map.next_value()?);
+				},
+				Field::Data => {
+					if data.is_some()  {return Err(de::Error::duplicate_field("data"))}
+					data = Some(map.next_value()?);
+				},
+			}
+		}
+		assemble(
+			shape.ok_or_else(|| de::Error::missing_field("shape"))?,
+			data.ok_or_else(|| de::Error::missing_field("data"))?,
+			)
+	}
+}
+
+
+/**
+	Nested rows format: a matrix is a sequence of rows, each row being a sequence of elements
+	
+	This is the natural format for JSON or TOML arrays of arrays. Use it on a struct field with `#[serde(with = "flexalgebra::serialize::rows")]`
+*/
+pub mod rows {
+	use super::*;
+	
+	/// one row of a matrix
+	struct Row<'t, A:Array> (&'t Matrix<A>, usize);
+	impl<A:Array> Serialize for Row<'_,A>
+	where A::Element: Serialize
+	{
+		fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+			serializer.collect_seq(self.0.row(self.1).iter())
+		}
+	}
+	
+	/// serialize a matrix as a sequence of rows
+	pub fn serialize<A:Array, S:Serializer>(matrix: &Matrix<A>, serializer: S) -> Result<S::Ok, S::Error>
+	where A::Element: Serialize
+	{
+		serializer.collect_seq((0 .. matrix.rows()).map(|i|  Row(matrix, i)))
+	}
+	
+	/// deserialize a matrix from a sequence of rows, all rows must have the same length
+	pub fn deserialize<'de, A:ArrayOwned, D:Deserializer<'de>>(deserializer: D) -> Result<Matrix<A>, D::Error>
+	where A::Element: Deserialize<'de>
+	{
+		let rows = Vec::<Vec<A::Element>>::deserialize(deserializer)?;
+		let columns = rows.first().map(|row| row.len()).unwrap_or(0);
+		if let Some(row) = rows.iter().find(|row| row.len() != columns)
+			{return Err(de::Error::invalid_length(row.len(), &"rows of the same length"))}
+		let shape = [rows.len(), columns];
+		let mut data = Vec::with_capacity(shape[0]*shape[1]);
+		let mut rows = rows.into_iter().map(|row| row.into_iter()).collect::<Vec<_>>();
+		for _ in 0 .. columns {
+			for row in rows.iter_mut() {
+				data.extend(row.next());
+			}
+		}
+		assemble(shape, data)
+	}
+}
+
+
+
+#[test]
+fn test_serde() {
+	use crate::matrices::*;
+	
+	let a = SMatrix::from([[1.,2.], [3.,4.], [5.,6.]]);
+	let text = serde_json::to_string(&a).unwrap();
+	assert_eq!(text, r#"{"shape":[2,3],"data":[1.0,2.0,3.0,4.0,5.0,6.0]}"#);
+	let b: SMatrix<f64,2,3> = serde_json::from_str(&text).unwrap();
+	assert_eq!(a.as_slice(), b.as_slice());
+	let c: DMatrix<f64> = serde_json::from_str(&text).unwrap();
+	assert_eq!(a.as_slice(), c.as_slice());
+	assert!(serde_json::from_str::<SMatrix<f64,3,2>>(&text).is_err());
+	assert!(serde_json::from_str::<DMatrix<f64,Stat<2>,Stat<2>>>(&text).is_err());
+	assert!(serde_json::from_str::<DMatrix<f64>>(r#"{"shape":[2,3],"data":[1.0]}"#).is_err());
+	assert!(serde_json::from_str::<DMatrix<f64>>(r#"{"shape":[4294967296,4294967296],"data":[]}"#).is_err());
+	// readers cannot lend borrowed keys
+	let d: DMatrix<f64> = serde_json::from_reader(text.as_bytes()).unwrap();
+	assert_eq!(a.as_slice(), d.as_slice());
+	assert!(serde_json::from_str::<DMatrix<f64>>(r#"{"shape":[1,1],"values":[1.0]}"#).is_err());
+	assert_eq!(serde_json::to_string(&a.transpose()).unwrap(), r#"{"shape":[3,2],"data":[1.0,3.0,5.0,2.0,4.0,6.0]}"#);
+	
+	#[derive(serde::Serialize, serde::Deserialize)]
+	struct Calibration {
+		#[serde(with = "rows")]
+		intrinsics: SMatrix<f64,2,3>,
+	}
+	let text = serde_json::to_string(&Calibration {intrinsics: a.clone()}).unwrap();
+	assert_eq!(text, r#"{"intrinsics":[[1.0,3.0,5.0],[2.0,4.0,6.0]]}"#);
+	let b: Calibration = serde_json::from_str(&text).unwrap();
+	assert_eq!(a.as_slice(), b.intrinsics.as_slice());
+	assert!(serde_json::from_str::<Calibration>(r#"{"intrinsics":[[1.0,3.0],[2.0,4.0,6.0]]}"#).is_err());
+}
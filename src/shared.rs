@@ -0,0 +1,146 @@
+/*!
+	Reference-counted storages, to pass large matrices between threads or processing stages without copying them or borrowing them
+
+	- [Shared] is read-only, cloning it only increments a reference count
+	- [Cow] is also cheap to clone, but clones its buffer on the first mutable access if the buffer is still shared (copy-on-write)
+
+	Both share a [Dynamic] matrix, so they convert from and to it without copy, and operations on them give [Dynamic] matrices
+*/
+
+use crate::prelude::*;
+use crate::matrix::*;
+use crate::matrices::*;
+
+use core::mem::MaybeUninit;
+use std::sync::Arc;
+
+
+/// dynamically or statically sized matrix sharing its buffer
+pub type SharedMatrix<T, R=Dyn, C=Dyn> = Matrix<Shared<T,R,C>>;
+/// dynamically or statically sized matrix sharing its buffer until modified
+pub type CowMatrix<T, R=Dyn, C=Dyn> = Matrix<Cow<T,R,C>>;
+
+
+/// column-major immutable array in a reference-counted buffer, cloning it does not copy the elements
+#[derive(Clone, Debug)]
+pub struct Shared<T: Element, R: Dim=Dyn, C: Dim=Dyn> {
+	data: Arc<Dynamic<T,R,C>>,
+}
+impl<T: Element, R: Dim, C: Dim>
+	Array for Shared<T,R,C>
+{
+	type Element = T;
+	type R = R;
+	type C = C;
+	fn shape(&self) -> [usize; 2]    {self.data.shape()}
+	fn strides(&self) -> [isize; 2]  {self.data.strides()}
+	fn as_ptr(&self) -> *const T        {self.data.as_ptr()}
+}
+impl<T: Element, R1: Dim, C1: Dim, R2: Dim, C2: Dim>
+	Compatible<R2,C2> for Shared<T,R1,C1>
+{
+	type Owned = Dynamic<T,R2,C2>;
+}
+impl<T: Element, R: Dim, C: Dim> Matrix<Shared<T,R,C>> {
+	/// number of matrices sharing this buffer
+	pub fn share_count(&self) -> usize  {Arc::strong_count(&self.0.data)}
+	/// retreive the buffer as a [Dynamic] matrix, copying it only if it is still shared
+	pub fn into_dynamic(self) -> Matrix<Dynamic<T,R,C>>  {Matrix(Arc::unwrap_or_clone(self.0.data))}
+	/// same matrix that can be modified, without copy until then
+	pub fn into_cow(self) -> Matrix<Cow<T,R,C>>  {Matrix(Cow {data: self.0.data})}
+}
+impl<T: Element, R: Dim, C: Dim>
+	From<Matrix<Dynamic<T,R,C>>> for Matrix<Shared<T,R,C>>
+{
+	fn from(src: Matrix<Dynamic<T,R,C>>) -> Self  {Matrix(Shared {data: Arc::new(src.0)})}
+}
+
+
+/**
+	column-major array in a reference-counted buffer, copied on the first mutable access if it is shared
+
+	Cloning it does not copy the elements. [ArrayMut::as_mut_ptr] makes the buffer unique before returning, so [Array::as_ptr] must be called after it to get the same pointer
+*/
+#[derive(Clone, Debug)]
+pub struct Cow<T: Element, R: Dim=Dyn, C: Dim=Dyn> {
+	data: Arc<Dynamic<T,R,C>>,
+}
+impl<T: Element, R: Dim, C: Dim>
+	Array for Cow<T,R,C>
+{
+	type Element = T;
+	type R = R;
+	type C = C;
+	fn shape(&self) -> [usize; 2]    {self.data.shape()}
+	fn strides(&self) -> [isize; 2]  {self.data.strides()}
+	fn as_ptr(&self) -> *const T        {self.data.as_ptr()}
+}
+impl<T: Element, R: Dim, C: Dim>
+	ArrayMut for Cow<T,R,C>
+{
+	fn as_mut_ptr(&mut self) -> *mut T  {Arc::make_mut(&mut self.data).as_mut_ptr()}
+}
+impl<T: Element, R: Dim, C: Dim>
+	ArrayOwned for Cow<T,R,C>
+{
+	type Uninit = DynamicUninit<T,R,C>;
+	fn uninit(shape: (R, C)) -> Self::Uninit  {DynamicUninit::new(shape)}
+}
+impl<T: Element, R: Dim, C: Dim>
+	ArrayUninit<Cow<T,R,C>> for DynamicUninit<T,R,C>
+{
+	fn strides(&self) -> [isize; 2]  {[1, self.leading() as isize]}
+	fn as_mut_ptr(&mut self) -> *mut MaybeUninit<T>  {DynamicUninit::as_mut_ptr(self)}
+	unsafe fn assume_init(self) -> Cow<T,R,C>  {Cow {data: Arc::new(ArrayUninit::<Dynamic<T,R,C>>::assume_init(self))}}
+}
+impl<T: Element, R1: Dim, C1: Dim, R2: Dim, C2: Dim>
+	Compatible<R2,C2> for Cow<T,R1,C1>
+{
+	type Owned = Dynamic<T,R2,C2>;
+}
+impl<T: Element, R: Dim, C: Dim> Matrix<Cow<T,R,C>> {
+	/// `true` if the buffer is used by other matrices, so the next mutable access will copy it
+	pub fn is_shared(&self) -> bool  {Arc::strong_count(&self.0.data) > 1}
+	/// retreive the buffer as a [Dynamic] matrix, copying it only if it is still shared
+	pub fn into_dynamic(self) -> Matrix<Dynamic<T,R,C>>  {Matrix(Arc::unwrap_or_clone(self.0.data))}
+	/// read-only matrix sharing the same buffer
+	pub fn share(&self) -> Matrix<Shared<T,R,C>>  {Matrix(Shared {data: self.0.data.clone()})}
+}
+impl<T: Element, R: Dim, C: Dim>
+	From<Matrix<Dynamic<T,R,C>>> for Matrix<Cow<T,R,C>>
+{
+	fn from(src: Matrix<Dynamic<T,R,C>>) -> Self  {Matrix(Cow {data: Arc::new(src.0)})}
+}
+
+
+
+#[test]
+fn test_shared() {
+	let dense = DMatrix::<f64>::field([3, 2], |[i,j]|  (i + 3*j) as f64);
+	let shared = SharedMatrix::from(dense.clone());
+	// clones and threads share the same buffer
+	let clone = shared.clone();
+	assert_eq!(clone.as_ptr(), shared.as_ptr());
+	assert_eq!(shared.share_count(), 2);
+	let sum = std::thread::spawn(move ||  clone.iter().sum::<f64>()).join().unwrap();
+	assert_eq!(sum, 15.);
+	assert_eq!(shared.share_count(), 1);
+	// operations give owned matrices
+	let doubled: DMatrix<f64> = &shared + &shared;
+	assert_eq!(doubled[[2,1]], 10.);
+
+	// copy on write
+	let mut cow = shared.clone().into_cow();
+	assert!(cow.is_shared());
+	cow[[0,0]] = 10.;
+	assert!(!cow.is_shared());
+	assert_ne!(cow.as_ptr(), shared.as_ptr());
+	assert_eq!(shared[[0,0]], 0.);
+	assert_eq!(cow[[0,0]], 10.);
+	// a unique buffer is modified and retreived without copy
+	let ptr = cow.as_ptr();
+	cow.set_full(1.);
+	assert_eq!(cow.as_ptr(), ptr);
+	assert_eq!(cow.into_dynamic().as_ptr(), ptr);
+	assert_eq!(shared.into_dynamic().as_slice(), dense.as_slice());
+}
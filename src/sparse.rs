@@ -0,0 +1,729 @@
+/*!
+	Sparse matrices, storing only their nonzero elements
+
+	- [Coo] lists entries with their indices, it is convenient to assemble a matrix from triplets
+	- [Csc] compresses entries column by column, it is the format of choice for decompositions
+	- [Csr] compresses entries row by row, it is the format of choice for matrix-vector products
+
+	Compressed matrices always have their indices sorted and without duplicates in each column (or row).
+	They can be multiplied with any dense [Matrix] using the common operators or [MulTo] to write the result in any [ArrayMut]
+
+	Square compressed matrices can be factorized to solve linear systems, with [SparseCholesky] for symmetric positive definite matrices and [SparseLu] for the others
+*/
+
+use crate::prelude::*;
+use crate::matrix::*;
+use crate::matrices::*;
+use crate::operators::*;
+use crate::workspace::{self, Workspace, ScratchMatrix, MulWithWorkspace};
+
+use core::ops::{Add, Sub, Mul};
+use core::fmt;
+
+pub mod ordering;
+pub mod cholesky;
+pub mod lu;
+
+pub use self::ordering::Ordering;
+pub use self::cholesky::{CholeskySymbolic, SparseCholesky};
+pub use self::lu::{LuSymbolic, SparseLu};
+
+
+/// sparse matrix in coordinate format: a list of `(row, column, value)` triplets. Duplicate entries are summed when converting to other formats
+#[derive(Clone, Debug)]
+pub struct Coo<T: Element> {
+	shape: [usize; 2],
+	rows: Vec<usize>,
+	columns: Vec<usize>,
+	values: Vec<T>,
+}
+impl<T: Element> Coo<T> {
+	/// empty matrix of the given shape
+	pub fn new(shape: [usize; 2]) -> Self  {Self::with_capacity(shape, 0)}
+	/// empty matrix of the given shape, with memory reserved for the given number of entries
+	pub fn with_capacity(shape: [usize; 2], capacity: usize) -> Self  {Self {
+		shape,
+		rows: Vec::with_capacity(capacity),
+		columns: Vec::with_capacity(capacity),
+		values: Vec::with_capacity(capacity),
+	}}
+	/// matrix size in each dimension
+	pub fn shape(&self) -> [usize; 2]  {self.shape}
+	/// number of entries, including duplicates
+	pub fn len(&self) -> usize  {self.values.len()}
+	/// `true` if there is no entry
+	pub fn is_empty(&self) -> bool  {self.values.is_empty()}
+	/// add an entry at `[row, column]`, it will be summed with any other entry at the same index
+	pub fn push(&mut self, index: [usize; 2], value: T) -> &mut Self {
+		assert!(index[0] < self.shape[0] && index[1] < self.shape[1]);
+		self.rows.push(index[0]);
+		self.columns.push(index[1]);
+		self.values.push(value);
+		self
+	}
+	/// iterate over the entries in insertion order
+	pub fn iter(&self) -> impl Iterator<Item=([usize; 2], &T)> {
+		self.rows.iter().zip(&self.columns).zip(&self.values)
+			.map(|((&i, &j), value)|  ([i, j], value))
+	}
+}
+impl<T: Scalar> Coo<T> {
+	/// entries of a dense matrix that are not zero
+	pub fn from_dense<A: Array<Element=T>>(dense: &Matrix<A>) -> Self {
+		let mut new = Self::new(dense.shape());
+		for (index, value) in dense.iter().index() {
+			if !value.is_zero()  {new.push(index, value.clone());}
+		}
+		new
+	}
+	/// convert to compressed columns, summing duplicates
+	pub fn to_csc(&self) -> Csc<T> {
+		// bucket entries by row, then by column, so that rows come sorted in each column
+		let by_row = compress(self.shape[0], &self.rows, &self.columns, self.values.iter().cloned());
+		let mut csc = compress(self.shape[1],
+			&by_row.indices,
+			&by_row.majors(),
+			by_row.values.into_iter());
+		csc.shape = self.shape;
+		csc.sum_duplicates();
+		csc
+	}
+	/// convert to compressed rows, summing duplicates
+	pub fn to_csr(&self) -> Csr<T> {
+		self.to_csc().to_csr()
+	}
+}
+impl<T: Scalar> Coo<T> {
+	/// convert to a dense matrix, summing duplicates
+	pub fn to_dense(&self) -> DMatrix<T> {
+		let mut dense = DMatrix::<T>::zeros(self.shape);
+		for (index, value) in self.iter() {
+			dense[index] = dense[index].clone() + value.clone();
+		}
+		dense
+	}
+}
+
+/**
+	bucket entries by their `major` index using a counting sort, keeping their `minor` index.
+	The order of entries in each bucket is the order of the input.
+
+	The result has shape `[_, major]`, the other dimension is left to 0
+*/
+fn compress<T: Element>(size: usize, major: &[usize], minor: &[usize], values: impl Iterator<Item=T>) -> Csc<T> {
+	let mut pointers = vec![0; size+1];
+	for &k in major  {pointers[k+1] += 1}
+	for k in 0 .. size  {pointers[k+1] += pointers[k]}
+	let mut next = pointers.clone();
+	let mut indices = vec![0; major.len()];
+	let mut slots = vec![None; major.len()];
+	for ((&k, &m), value) in major.iter().zip(minor).zip(values) {
+		indices[next[k]] = m;
+		slots[next[k]] = Some(value);
+		next[k] += 1;
+	}
+	Csc {
+		shape: [0, size],
+		pointers,
+		indices,
+		values: slots.into_iter().map(Option::unwrap).collect(),
+	}
+}
+
+
+/// compressed sparse column matrix
+#[derive(Clone, Debug)]
+pub struct Csc<T: Element> {
+	shape: [usize; 2],
+	/// start of each column in `indices` and `values`, with an additional end
+	pointers: Vec<usize>,
+	/// row of each stored element
+	indices: Vec<usize>,
+	/// value of each stored element
+	values: Vec<T>,
+}
+impl<T: Element> Csc<T> {
+	/// matrix with no stored element
+	pub fn new(shape: [usize; 2]) -> Self  {Self {
+		shape,
+		pointers: vec![0; shape[1]+1],
+		indices: Vec::new(),
+		values: Vec::new(),
+	}}
+	/// build from the raw compressed arrays, or `None` if they are inconsistent or the row indices are not sorted and unique in each column
+	pub fn try_from_parts(shape: [usize; 2], pointers: Vec<usize>, indices: Vec<usize>, values: Vec<T>) -> Option<Self> {
+		if pointers.len().checked_sub(1) != Some(shape[1])
+			|| pointers[0] != 0
+			|| pointers[shape[1]] != indices.len()
+			|| indices.len() != values.len()
+			// with the last pointer checked above, this keeps every column within the indices
+			|| pointers.windows(2).any(|pair|  pair[0] > pair[1])
+			{return None}
+		for j in 0 .. shape[1] {
+			let column = &indices[pointers[j] .. pointers[j+1]];
+			if column.windows(2).any(|pair|  pair[0] >= pair[1])
+			|| column.last().is_some_and(|&i|  i >= shape[0])
+				{return None}
+		}
+		Some(Self {shape, pointers, indices, values})
+	}
+	/// decompose into `(shape, pointers, indices, values)`
+	pub fn into_parts(self) -> ([usize; 2], Vec<usize>, Vec<usize>, Vec<T>) {
+		(self.shape, self.pointers, self.indices, self.values)
+	}
+
+	/// matrix size in each dimension
+	pub fn shape(&self) -> [usize; 2]  {self.shape}
+	/// number of rows (same as `shape()[0]`)
+	pub fn rows(&self) -> usize  {self.shape[0]}
+	/// number of columns (same as `shape()[1]`)
+	pub fn columns(&self) -> usize  {self.shape[1]}
+	/// number of stored elements
+	pub fn nnz(&self) -> usize  {self.values.len()}
+	/// start of each column in [Self::indices] and [Self::values], with an additional end
+	pub fn pointers(&self) -> &[usize]  {&self.pointers}
+	/// row of each stored element
+	pub fn indices(&self) -> &[usize]  {&self.indices}
+	/// value of each stored element
+	pub fn values(&self) -> &[T]  {&self.values}
+	/// value of each stored element, the sparsity pattern cannot be changed
+	pub fn values_mut(&mut self) -> &mut [T]  {&mut self.values}
+	/// row indices and values stored in the given column
+	pub fn column(&self, j: usize) -> (&[usize], &[T]) {
+		let range = self.pointers[j] .. self.pointers[j+1];
+		(&self.indices[range.clone()], &self.values[range])
+	}
+	/// stored element at `[row, column]`, or `None` if it is not stored
+	pub fn get(&self, index: [usize; 2]) -> Option<&T> {
+		let (rows, values) = self.column(index[1]);
+		rows.binary_search(&index[0]).ok().map(|k|  &values[k])
+	}
+	/// iterate over stored elements in column-major order
+	pub fn iter(&self) -> impl Iterator<Item=([usize; 2], &T)> {
+		(0 .. self.shape[1]).flat_map(move |j|  {
+			let (rows, values) = self.column(j);
+			rows.iter().zip(values).map(move |(&i, value)|  ([i, j], value))
+		})
+	}
+	/// major index of each stored element (its column), in storage order
+	fn majors(&self) -> Vec<usize> {
+		let mut columns = Vec::with_capacity(self.nnz());
+		for j in 0 .. self.shape[1] {
+			columns.extend(core::iter::repeat_n(j, self.pointers[j+1] - self.pointers[j]));
+		}
+		columns
+	}
+
+	/// transposed matrix, computed in `O(nnz)`
+	pub fn transpose(&self) -> Csc<T> {
+		let mut new = compress(self.shape[0], &self.indices, &self.majors(), self.values.iter().cloned());
+		new.shape = [self.shape[1], self.shape[0]];
+		new
+	}
+	/// same matrix in compressed rows
+	pub fn to_csr(&self) -> Csr<T>  {Csr {transposed: self.transpose()}}
+	/// the [Csr] matrix sharing the same compressed arrays, which is the transposed matrix. No computation is done
+	pub fn into_transposed_csr(self) -> Csr<T>  {Csr {transposed: self}}
+}
+impl<T: Scalar> Csc<T> {
+	/// nonzero elements of a dense matrix
+	pub fn from_dense<A: Array<Element=T>>(dense: &Matrix<A>) -> Self {
+		let mut new = Self::new(dense.shape());
+		for (j, column) in dense.column_iter().enumerate() {
+			for (i, value) in column.iter().enumerate() {
+				if !value.is_zero() {
+					new.indices.push(i);
+					new.values.push(value.clone());
+				}
+			}
+			new.pointers[j+1] = new.indices.len();
+		}
+		new
+	}
+	/// merge consecutive entries with the same row in each column
+	fn sum_duplicates(&mut self) {
+		let mut end = 0;
+		let mut start = 0;
+		for j in 0 .. self.shape[1] {
+			let stop = self.pointers[j+1];
+			let column = end;
+			for k in start .. stop {
+				if end > column && self.indices[end-1] == self.indices[k] {
+					self.values[end-1] = self.values[end-1].clone() + self.values[k].clone();
+				}
+				else {
+					self.indices[end] = self.indices[k];
+					self.values[end] = self.values[k].clone();
+					end += 1;
+				}
+			}
+			start = stop;
+			self.pointers[j+1] = end;
+		}
+		self.indices.truncate(end);
+		self.values.truncate(end);
+	}
+	/// merge two matrices with the same shape, applying `op` to stored elements (missing ones are zeros)
+	fn merge(&self, other: &Self, op: impl Fn(T, T) -> T) -> Self {
+		assert_eq!(self.shape, other.shape);
+		let mut new = Self::new(self.shape);
+		for j in 0 .. self.shape[1] {
+			let (mut left, mut right) = (self.pointers[j], other.pointers[j]);
+			let (left_end, right_end) = (self.pointers[j+1], other.pointers[j+1]);
+			while left < left_end || right < right_end {
+				let i = match (self.indices.get(left).filter(|_| left < left_end), other.indices.get(right).filter(|_| right < right_end)) {
+					(Some(&l), Some(&r)) => l.min(r),
+					(Some(&l), None) => l,
+					(None, Some(&r)) => r,
+					(None, None) => unreachable!(),
+				};
+				let take = |indices: &[usize], values: &[T], k: &mut usize, end: usize| {
+					if *k < end && indices[*k] == i  {*k += 1; values[*k-1].clone()}
+					else {T::zero()}
+				};
+				let l = take(&self.indices, &self.values, &mut left, left_end);
+				let r = take(&other.indices, &other.values, &mut right, right_end);
+				new.indices.push(i);
+				new.values.push(op(l, r));
+			}
+			new.pointers[j+1] = new.indices.len();
+		}
+		new
+	}
+}
+impl<T: Scalar> Csc<T> {
+	/// convert to a dense matrix
+	pub fn to_dense(&self) -> DMatrix<T> {
+		let mut dense = DMatrix::<T>::zeros(self.shape);
+		for (index, value) in self.iter() {
+			dense[index] = value.clone();
+		}
+		dense
+	}
+}
+impl<T: Scalar> From<&Coo<T>> for Csc<T> {
+	fn from(src: &Coo<T>) -> Self  {src.to_csc()}
+}
+impl<T: Scalar> From<&Csr<T>> for Csc<T> {
+	fn from(src: &Csr<T>) -> Self  {src.to_csc()}
+}
+
+
+/**
+	compressed sparse row matrix
+
+	It is stored as the [Csc] matrix of its transpose, so that both formats share their algorithms
+*/
+#[derive(Clone, Debug)]
+pub struct Csr<T: Element> {
+	transposed: Csc<T>,
+}
+impl<T: Element> Csr<T> {
+	/// matrix with no stored element
+	pub fn new(shape: [usize; 2]) -> Self  {Self {transposed: Csc::new([shape[1], shape[0]])}}
+	/// build from the raw compressed arrays, or `None` if they are inconsistent or the column indices are not sorted and unique in each row
+	pub fn try_from_parts(shape: [usize; 2], pointers: Vec<usize>, indices: Vec<usize>, values: Vec<T>) -> Option<Self> {
+		Some(Self {transposed: Csc::try_from_parts([shape[1], shape[0]], pointers, indices, values)?})
+	}
+	/// decompose into `(shape, pointers, indices, values)`
+	pub fn into_parts(self) -> ([usize; 2], Vec<usize>, Vec<usize>, Vec<T>) {
+		let (shape, pointers, indices, values) = self.transposed.into_parts();
+		([shape[1], shape[0]], pointers, indices, values)
+	}
+
+	/// matrix size in each dimension
+	pub fn shape(&self) -> [usize; 2]  {[self.transposed.shape[1], self.transposed.shape[0]]}
+	/// number of rows (same as `shape()[0]`)
+	pub fn rows(&self) -> usize  {self.transposed.shape[1]}
+	/// number of columns (same as `shape()[1]`)
+	pub fn columns(&self) -> usize  {self.transposed.shape[0]}
+	/// number of stored elements
+	pub fn nnz(&self) -> usize  {self.transposed.nnz()}
+	/// start of each row in [Self::indices] and [Self::values], with an additional end
+	pub fn pointers(&self) -> &[usize]  {&self.transposed.pointers}
+	/// column of each stored element
+	pub fn indices(&self) -> &[usize]  {&self.transposed.indices}
+	/// value of each stored element
+	pub fn values(&self) -> &[T]  {&self.transposed.values}
+	/// value of each stored element, the sparsity pattern cannot be changed
+	pub fn values_mut(&mut self) -> &mut [T]  {&mut self.transposed.values}
+	/// column indices and values stored in the given row
+	pub fn row(&self, i: usize) -> (&[usize], &[T])  {self.transposed.column(i)}
+	/// stored element at `[row, column]`, or `None` if it is not stored
+	pub fn get(&self, index: [usize; 2]) -> Option<&T>  {self.transposed.get([index[1], index[0]])}
+	/// iterate over stored elements in row-major order
+	pub fn iter(&self) -> impl Iterator<Item=([usize; 2], &T)> {
+		self.transposed.iter().map(|([j, i], value)|  ([i, j], value))
+	}
+
+	/// transposed matrix, computed in `O(nnz)`
+	pub fn transpose(&self) -> Csr<T>  {Csr {transposed: self.transposed.transpose()}}
+	/// same matrix in compressed columns
+	pub fn to_csc(&self) -> Csc<T>  {self.transposed.transpose()}
+	/// the [Csc] matrix sharing the same compressed arrays, which is the transposed matrix. No computation is done
+	pub fn into_transposed_csc(self) -> Csc<T>  {self.transposed}
+}
+impl<T: Scalar> Csr<T> {
+	/// nonzero elements of a dense matrix
+	pub fn from_dense<A: Array<Element=T>>(dense: &Matrix<A>) -> Self {
+		Self {transposed: Csc::from_dense(&dense.transpose())}
+	}
+}
+impl<T: Scalar> Csr<T> {
+	/// convert to a dense matrix
+	pub fn to_dense(&self) -> DMatrix<T> {
+		self.transposed.to_dense().transpose().owned()
+	}
+}
+impl<T: Scalar> From<&Coo<T>> for Csr<T> {
+	fn from(src: &Coo<T>) -> Self  {src.to_csr()}
+}
+impl<T: Scalar> From<&Csc<T>> for Csr<T> {
+	fn from(src: &Csc<T>) -> Self  {src.to_csr()}
+}
+
+
+macro_rules! sparse_binop {
+	($trait:ident, $method:ident) => {
+		impl<T: Scalar> $trait<&Csc<T>> for &Csc<T> {
+			type Output = Csc<T>;
+			/// elementwise operation, the result pattern is the union of both patterns
+			fn $method(self, right: &Csc<T>) -> Csc<T> {
+				self.merge(right, T::$method)
+			}
+		}
+		impl<T: Scalar> $trait<&Csr<T>> for &Csr<T> {
+			type Output = Csr<T>;
+			/// elementwise operation, the result pattern is the union of both patterns
+			fn $method(self, right: &Csr<T>) -> Csr<T> {
+				Csr {transposed: self.transposed.merge(&right.transposed, T::$method)}
+			}
+		}
+	}
+}
+sparse_binop!(Add, add);
+sparse_binop!(Sub, sub);
+
+
+impl<T,R,O>
+	MulTo<Matrix<R>, Matrix<O>> for Csc<T>
+where
+	T: Scalar,
+	R: Array<Element=T, C=O::C>,
+	O: ArrayMut<Element=T>,
+{
+	/// sparse-dense product without dynamic allocation
+	fn mul_to<'o>(&self, right: &Matrix<R>, out: &'o mut Matrix<O>) -> &'o mut Matrix<O> {
+		assert_eq!(self.columns(), right.rows());
+		assert_eq!(out.shape(), [self.rows(), right.columns()]);
+		out.set_zero();
+		for c in 0 .. right.columns() {
+			for p in 0 .. self.columns() {
+				// safety: shapes have been checked above
+				let factor = unsafe {right.get_unchecked([p, c])};
+				let (rows, values) = self.column(p);
+				for (&i, value) in rows.iter().zip(values) {
+					let out = unsafe {out.get_unchecked_mut([i, c])};
+					*out = out.clone() + value.clone() * factor.clone();
+				}
+			}
+		}
+		out
+	}
+}
+impl<T,R,O>
+	MulTo<Matrix<R>, Matrix<O>> for Csr<T>
+where
+	T: Scalar,
+	R: Array<Element=T, C=O::C>,
+	O: ArrayMut<Element=T>,
+{
+	/// sparse-dense product without dynamic allocation
+	fn mul_to<'o>(&self, right: &Matrix<R>, out: &'o mut Matrix<O>) -> &'o mut Matrix<O> {
+		assert_eq!(self.columns(), right.rows());
+		assert_eq!(out.shape(), [self.rows(), right.columns()]);
+		out.set_field(|[i, c]|  {
+			let (columns, values) = self.row(i);
+			// safety: shapes have been checked above
+			columns.iter().zip(values)
+				.map(|(&p, value)|  value.clone() * unsafe {right.get_unchecked([p, c])}.clone())
+				.fold(T::zero(), Add::add)
+		})
+	}
+}
+impl<L,T,O>
+	MulTo<Csc<T>, Matrix<O>> for Matrix<L>
+where
+	T: Scalar,
+	L: Array<Element=T, R=O::R>,
+	O: ArrayMut<Element=T>,
+{
+	/// dense-sparse product without dynamic allocation
+	fn mul_to<'o>(&self, right: &Csc<T>, out: &'o mut Matrix<O>) -> &'o mut Matrix<O> {
+		assert_eq!(self.columns(), right.rows());
+		assert_eq!(out.shape(), [self.rows(), right.columns()]);
+		out.set_field(|[i, j]|  {
+			let (rows, values) = right.column(j);
+			// safety: shapes have been checked above
+			rows.iter().zip(values)
+				.map(|(&p, value)|  unsafe {self.get_unchecked([i, p])}.clone() * value.clone())
+				.fold(T::zero(), Add::add)
+		})
+	}
+}
+impl<L,T,O>
+	MulTo<Csr<T>, Matrix<O>> for Matrix<L>
+where
+	T: Scalar,
+	L: Array<Element=T, R=O::R>,
+	O: ArrayMut<Element=T>,
+{
+	/// dense-sparse product without dynamic allocation
+	fn mul_to<'o>(&self, right: &Csr<T>, out: &'o mut Matrix<O>) -> &'o mut Matrix<O> {
+		assert_eq!(self.columns(), right.rows());
+		assert_eq!(out.shape(), [self.rows(), right.columns()]);
+		out.set_zero();
+		for p in 0 .. right.rows() {
+			let (columns, values) = right.row(p);
+			for (&j, value) in columns.iter().zip(values) {
+				for i in 0 .. self.rows() {
+					// safety: shapes have been checked above
+					let factor = unsafe {self.get_unchecked([i, p])};
+					let out = unsafe {out.get_unchecked_mut([i, j])};
+					*out = out.clone() + factor.clone() * value.clone();
+				}
+			}
+		}
+		out
+	}
+}
+
+macro_rules! sparse_mul {
+	($sparse:ident) => {
+		impl<T,R>
+			Mul<&Matrix<R>> for &$sparse<T>
+		where
+			T: Scalar,
+			R: Array<Element=T>,
+		{
+			type Output = Matrix<Dynamic<T, Dyn, R::C>>;
+			/// sparse-dense product
+			fn mul(self, right: &Matrix<R>) -> Self::Output {
+				// the product accumulates in zeros
+				let mut new = Matrix::from_field([self.rows(), right.columns()], |_|  T::zero());
+				self.mul_to(right, &mut new);
+				new
+			}
+		}
+		impl<L,T>
+			Mul<&$sparse<T>> for &Matrix<L>
+		where
+			T: Scalar,
+			L: Array<Element=T>,
+		{
+			type Output = Matrix<Dynamic<T, L::R, Dyn>>;
+			/// dense-sparse product
+			fn mul(self, right: &$sparse<T>) -> Self::Output {
+				// the product accumulates in zeros
+				let mut new = Matrix::from_field([self.rows(), right.columns()], |_|  T::zero());
+				self.mul_to(right, &mut new);
+				new
+			}
+		}
+		impl<'w,T,R>
+			MulWithWorkspace<'w, Matrix<R>> for $sparse<T>
+		where
+			T: Scalar,
+			R: Array<Element=T>,
+		{
+			type Output = ScratchMatrix<'w, T, Dyn, R::C>;
+			/// sparse-dense product
+			fn mul_with_workspace(&self, right: &Matrix<R>, workspace: &'w Workspace) -> Self::Output {
+				workspace::product(self, right, [self.rows(), right.columns()], workspace)
+			}
+		}
+		impl<'w,L,T>
+			MulWithWorkspace<'w, $sparse<T>> for Matrix<L>
+		where
+			T: Scalar,
+			L: Array<Element=T>,
+		{
+			type Output = ScratchMatrix<'w, T, L::R, Dyn>;
+			/// dense-sparse product
+			fn mul_with_workspace(&self, right: &$sparse<T>, workspace: &'w Workspace) -> Self::Output {
+				workspace::product(self, right, [self.rows(), right.columns()], workspace)
+			}
+		}
+	}
+}
+sparse_mul!(Csc);
+sparse_mul!(Csr);
+
+
+/// failure of a sparse factorization
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub enum FactorizationError {
+	/// the matrix is not square
+	NotSquare,
+	/// the given ordering is not a permutation of the matrix rows/columns
+	InvalidOrdering,
+	/// the matrix doesn't have the sparsity pattern of the symbolic analysis
+	PatternMismatch,
+	/// the matrix is not positive definite, the pivot of the given column (in the original matrix order) is not positive
+	NotPositiveDefinite(usize),
+	/// the matrix is singular, no nonzero pivot was found for the given column (in the original matrix order)
+	Singular(usize),
+}
+impl fmt::Display for FactorizationError {
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		match self {
+			Self::NotSquare => write!(f, "matrix is not square"),
+			Self::InvalidOrdering => write!(f, "ordering is not a permutation of the matrix size"),
+			Self::PatternMismatch => write!(f, "matrix pattern differs from the symbolic analysis"),
+			Self::NotPositiveDefinite(column) => write!(f, "matrix is not positive definite at column {}", column),
+			Self::Singular(column) => write!(f, "matrix is singular at column {}", column),
+		}
+	}
+}
+impl std::error::Error for FactorizationError {}
+
+
+
+#[test]
+fn test_sparse() {
+	let mut coo = Coo::new([3, 4]);
+	coo.push([0,0], 1.)
+		.push([2,1], 2.)
+		.push([1,3], 3.)
+		.push([2,1], 4.)
+		.push([0,3], 5.);
+	let dense = coo.to_dense();
+	assert_eq!(dense.as_slice(), Some(&[1.,0.,0.,  0.,0.,6.,  0.,0.,0.,  5.,3.,0.][..]));
+
+	let csc = coo.to_csc();
+	assert_eq!(csc.nnz(), 4);
+	assert_eq!(csc.pointers(), [0, 1, 2, 2, 4]);
+	assert_eq!(csc.indices(), [0, 2, 0, 1]);
+	assert_eq!(csc.get([2,1]), Some(&6.));
+	assert_eq!(csc.get([1,1]), None);
+	assert_eq!(csc.to_dense().as_slice(), dense.as_slice());
+	assert_eq!(Csc::from_dense(&dense).indices(), csc.indices());
+
+	let csr = coo.to_csr();
+	assert_eq!(csr.pointers(), [0, 2, 3, 4]);
+	assert_eq!(csr.indices(), [0, 3, 3, 1]);
+	assert_eq!(csr.to_dense().as_slice(), dense.as_slice());
+	assert_eq!(csr.to_csc().indices(), csc.indices());
+	assert_eq!(csc.transpose().to_dense().as_slice(), dense.transpose().owned().as_slice());
+	assert!(Csc::try_from_parts([2,2], vec![0,2,2], vec![1,0], vec![1.,2.]).is_none());
+	assert!(Csc::try_from_parts([2,2], vec![0,2,2], vec![0,1], vec![1.,2.]).is_some());
+	assert!(Csc::try_from_parts([2,2], vec![0,5,2], vec![0,1], vec![1.,2.]).is_none());
+	assert!(Csr::try_from_parts([2,2], vec![0,5,2], vec![0,1], vec![1.,2.]).is_none());
+
+	let sum = &csc + &Csc::from_dense(&DMatrix::<f64>::identity([3, 4]));
+	assert_eq!(sum.to_dense().as_slice(), (&dense + &DMatrix::<f64>::identity([3, 4])).as_slice());
+	let difference = &csr - &csr;
+	assert!(difference.values().iter().all(|&v| v == 0.));
+
+	let right = DMatrix::<f64>::field([4, 2], |[i,j]|  (i + 10*j) as f64);
+	let expected = &dense * &right;
+	assert_eq!((&csc * &right).as_slice(), expected.as_slice());
+	assert_eq!((&csr * &right).as_slice(), expected.as_slice());
+	let left = DMatrix::<f64>::field([2, 3], |[i,j]|  (i + 10*j) as f64);
+	let expected = &left * &dense;
+	assert_eq!((&left * &csc).as_slice(), expected.as_slice());
+	assert_eq!((&left * &csr).as_slice(), expected.as_slice());
+	let mut out = DMatrix::<f64>::zeros([2, 4]);
+	left.mul_to(&csc, &mut out.view_mut());
+	assert_eq!(out.as_slice(), expected.as_slice());
+	let right = crate::SMatrix::<f64,4,2>::field(|[i,j]|  (i + 10*j) as f64);
+	let mut out = crate::SMatrix::<f64,3,2>::zeros();
+	csr.mul_to(&right, &mut out);
+	assert_eq!(out.as_slice(), (&csc * &right).as_slice());
+}
+
+#[test]
+fn test_sparse_factorizations() {
+	// 2D laplacian on a 5x5 grid
+	let side = 5;
+	let size = side*side;
+	let mut coo = Coo::new([size, size]);
+	for i in 0 .. side {
+		for j in 0 .. side {
+			let node = i*side + j;
+			coo.push([node, node], 4.);
+			if i > 0  {coo.push([node, node-side], -1.);}
+			if i+1 < side  {coo.push([node, node+side], -1.);}
+			if j > 0  {coo.push([node, node-1], -1.);}
+			if j+1 < side  {coo.push([node, node+1], -1.);}
+		}
+	}
+	let laplacian = coo.to_csc();
+	let b = DMatrix::<f64>::field([size, 2], |[i,j]|  (i*(j+1)) as f64 - 3.);
+	let close = |a: &DMatrix<f64>, b: &DMatrix<f64>|  a.as_slice().unwrap().iter()
+		.zip(b.as_slice().unwrap())
+		.all(|(a, b)|  (a - b).abs() < 1e-9);
+
+	for ordering in [Ordering::Natural, Ordering::MinimumDegree, Ordering::Given((0 .. size).rev().collect())] {
+		let mut cholesky = SparseCholesky::new(&laplacian, &ordering).unwrap();
+		let x = cholesky.solve(&b);
+		assert!(close(&(&laplacian * &x), &b));
+		// the minimum degree ordering reduces fill-in
+		if ordering == Ordering::MinimumDegree {
+			assert!(cholesky.symbolic().nnz() < SparseCholesky::new(&laplacian, &Ordering::Natural).unwrap().symbolic().nnz());
+		}
+
+		let mut scaled = laplacian.clone();
+		scaled.values_mut().iter_mut().for_each(|v|  *v *= 2.);
+		cholesky.refactorize(&scaled).unwrap();
+		assert!(close(&(&scaled * &cholesky.solve(&b)), &b));
+
+		let mut lu = SparseLu::new(&laplacian, &ordering).unwrap();
+		assert!(close(&(&laplacian * &lu.solve(&b)), &b));
+		lu.refactorize(&scaled).unwrap();
+		assert!(close(&(&scaled * &lu.solve(&b)), &b));
+	}
+	let mut indefinite = laplacian.clone();
+	indefinite.values_mut().iter_mut().for_each(|v|  *v = -*v);
+	assert_eq!(SparseCholesky::new(&indefinite, &Ordering::Natural).err(), Some(FactorizationError::NotPositiveDefinite(0)));
+	assert_eq!(SparseCholesky::new(&Coo::<f64>::new([2, 3]).to_csc(), &Ordering::Natural).err(), Some(FactorizationError::NotSquare));
+	assert_eq!(SparseCholesky::new(&laplacian, &Ordering::Given(vec![0; size])).err(), Some(FactorizationError::InvalidOrdering));
+	assert_eq!(SparseLu::new(&laplacian, &Ordering::Given(vec![0, 1])).err(), Some(FactorizationError::InvalidOrdering));
+
+	// an arrow matrix has no fill-in when its dense row and column are eliminated last
+	let mut arrow = Coo::new([6, 6]);
+	for i in 0 .. 6 {
+		arrow.push([i,i], 8.);
+		if i != 2  {arrow.push([i,2], 1.).push([2,i], 1.);}
+	}
+	let order = Ordering::MinimumDegree.compute(&arrow.to_csc()).unwrap();
+	assert_eq!(order.len(), 6);
+	assert!(order[4 ..].contains(&2));
+	assert_eq!(SparseCholesky::new(&arrow.to_csc(), &Ordering::MinimumDegree).unwrap().symbolic().nnz(), 11);
+
+	// unsymmetric matrix with zeros on the diagonal, needing row pivoting
+	let mut coo = Coo::new([4, 4]);
+	coo.push([1,0], 2.).push([2,0], 1.)
+		.push([0,1], 3.).push([3,1], -1.)
+		.push([3,2], 5.).push([0,2], 1.)
+		.push([2,3], 4.).push([1,3], 1.);
+	let matrix = coo.to_csc();
+	let b = DMatrix::<f64>::field([4, 1], |[i,_]|  i as f64 + 1.);
+	let mut lu = SparseLu::new(&matrix, &Ordering::default()).unwrap();
+	let x = lu.solve(&b);
+	assert!(close(&(&matrix * &x), &b));
+	let mut x = b.clone();
+	lu.solve_inplace(&mut x);
+	assert!(close(&(&matrix * &x), &b));
+	// other values pivot on other rows, within the memory reserved by the symbolic analysis
+	let mut other = matrix.clone();
+	other.values_mut().copy_from_slice(&[1., 5., 2., 1., 3., 1., 6., 1.]);
+	lu.refactorize(&other).unwrap();
+	assert!(close(&(&other * &lu.solve(&b)), &b));
+	assert!(lu.nnz().iter().all(|&nnz|  nnz <= lu.symbolic().nnz_bound()));
+	assert_eq!(lu.refactorize(&laplacian), Err(FactorizationError::PatternMismatch));
+
+	let mut coo = Coo::new([2, 2]);
+	coo.push([0,0], 1.).push([1,0], 2.).push([0,1], 2.).push([1,1], 4.);
+	assert_eq!(SparseLu::new(&coo.to_csc(), &Ordering::Natural).err(), Some(FactorizationError::Singular(1)));
+}
@@ -0,0 +1,295 @@
+/*!
+	Sparse Cholesky factorization `P A Pᵀ = L Lᵀ` of symmetric positive definite matrices
+
+	The factorization is split in a symbolic analysis, depending only on the sparsity pattern, and a numeric factorization. The symbolic analysis can be reused to refactorize any matrix with the same pattern, which is much cheaper than the first factorization.
+*/
+
+use crate::prelude::*;
+use crate::matrix::*;
+use crate::matrices::*;
+use crate::workspace::{self, ScratchMatrix, workspace_size};
+use super::{Csc, FactorizationError};
+use super::ordering::{Ordering, inverse};
+
+use num_traits::Float;
+
+
+const NONE: usize = usize::MAX;
+
+/// symbolic analysis of a sparse Cholesky factorization, it can be reused for every matrix with the same sparsity pattern
+#[derive(Clone, Debug)]
+pub struct CholeskySymbolic {
+	/// `order[k]` is the original index of the `k`-th eliminated row and column
+	order: Vec<usize>,
+	/// elimination tree of the permuted matrix, `NONE` for roots
+	parent: Vec<usize>,
+	/// pattern of the upper triangle of the permuted matrix, rows are not sorted
+	permuted_pointers: Vec<usize>,
+	permuted_indices: Vec<usize>,
+	/// position in the permuted pattern of each element of the original matrix, `NONE` for ignored lower elements
+	map: Vec<usize>,
+	/// column pointers of the factor
+	factor_pointers: Vec<usize>,
+	/// pattern of the analyzed matrix, to check refactorized matrices
+	pointers: Vec<usize>,
+	indices: Vec<usize>,
+}
+
+/// buffers used by the numeric factorization
+#[derive(Clone, Debug)]
+struct Workspace<T> {
+	values: Vec<T>,
+	dense: Vec<T>,
+	stack: Vec<usize>,
+	mark: Vec<usize>,
+	next: Vec<usize>,
+}
+
+impl CholeskySymbolic {
+	/**
+		analyze the sparsity pattern of the given matrix. Only its upper triangle is considered, so the matrix can be given either full or upper triangular
+
+		`ordering` is the elimination order to use to reduce the factor fill-in
+	*/
+	pub fn new<T: Element>(matrix: &Csc<T>, ordering: &Ordering) -> Result<Self, FactorizationError> {
+		if matrix.rows() != matrix.columns()
+			{return Err(FactorizationError::NotSquare)}
+		let size = matrix.columns();
+		let order = ordering.compute(matrix)?;
+		let inverse = inverse(&order);
+
+		// upper triangle of the permuted matrix
+		let mut permuted_pointers = vec![0; size+1];
+		for ([i, j], _) in matrix.iter() {
+			if i <= j  {permuted_pointers[inverse[i].max(inverse[j]) + 1] += 1}
+		}
+		for k in 0 .. size  {permuted_pointers[k+1] += permuted_pointers[k]}
+		let mut next = permuted_pointers.clone();
+		let mut permuted_indices = vec![0; permuted_pointers[size]];
+		let mut map = vec![NONE; matrix.nnz()];
+		for (p, ([i, j], _)) in matrix.iter().enumerate() {
+			if i <= j {
+				let (i, j) = (inverse[i], inverse[j]);
+				let column = i.max(j);
+				permuted_indices[next[column]] = i.min(j);
+				map[p] = next[column];
+				next[column] += 1;
+			}
+		}
+
+		// elimination tree
+		let mut parent = vec![NONE; size];
+		let mut ancestor = vec![NONE; size];
+		for k in 0 .. size {
+			for &row in &permuted_indices[permuted_pointers[k] .. permuted_pointers[k+1]] {
+				let mut i = row;
+				while i != NONE && i < k {
+					let next = ancestor[i];
+					ancestor[i] = k;
+					if next == NONE  {parent[i] = k}
+					i = next;
+				}
+			}
+		}
+
+		let mut symbolic = Self {
+			order,
+			parent,
+			permuted_pointers,
+			permuted_indices,
+			map,
+			factor_pointers: Vec::new(),
+			pointers: matrix.pointers().to_vec(),
+			indices: matrix.indices().to_vec(),
+		};
+
+		// column counts of the factor, walking the pattern of each of its rows
+		let mut counts = vec![1; size];
+		let mut stack = vec![0; size];
+		let mut mark = vec![NONE; size];
+		for k in 0 .. size {
+			let top = symbolic.reach(k, &mut stack, &mut mark);
+			for &i in &stack[top ..]  {counts[i] += 1}
+		}
+		symbolic.factor_pointers = core::iter::once(0)
+			.chain(counts.iter().scan(0, |total, count| {*total += count; Some(*total)}))
+			.collect();
+		Ok(symbolic)
+	}
+
+	/// size of the analyzed matrix
+	pub fn size(&self) -> usize  {self.order.len()}
+	/// elimination order: `order()[k]` is the original index of the `k`-th eliminated row and column
+	pub fn order(&self) -> &[usize]  {&self.order}
+	/// number of nonzeros in the factor
+	pub fn nnz(&self) -> usize  {self.factor_pointers[self.size()]}
+
+	/**
+		pattern of the row `k` of the factor, excluding the diagonal, in topological order.
+
+		It is written in `stack[top..]` and `top` is returned
+	*/
+	fn reach(&self, k: usize, stack: &mut [usize], mark: &mut [usize]) -> usize {
+		let mut top = self.size();
+		mark[k] = k;
+		for &row in &self.permuted_indices[self.permuted_pointers[k] .. self.permuted_pointers[k+1]] {
+			let mut i = row;
+			let mut length = 0;
+			// walk up the elimination tree until a node already in the pattern
+			while mark[i] != k {
+				stack[length] = i;
+				length += 1;
+				mark[i] = k;
+				i = self.parent[i];
+			}
+			while length > 0 {
+				length -= 1;
+				top -= 1;
+				stack[top] = stack[length];
+			}
+		}
+		top
+	}
+}
+
+
+/// sparse Cholesky factorization `P A Pᵀ = L Lᵀ` of a symmetric positive definite matrix
+#[derive(Clone, Debug)]
+pub struct SparseCholesky<T: Element> {
+	symbolic: CholeskySymbolic,
+	factor: Csc<T>,
+	workspace: Workspace<T>,
+}
+impl<T: Scalar + Float> SparseCholesky<T> {
+	/// analyze and factorize the given matrix, only its upper triangle is considered
+	pub fn new(matrix: &Csc<T>, ordering: &Ordering) -> Result<Self, FactorizationError> {
+		Self::from_symbolic(CholeskySymbolic::new(matrix, ordering)?, matrix)
+	}
+	/// factorize the given matrix using a previous symbolic analysis
+	pub fn from_symbolic(symbolic: CholeskySymbolic, matrix: &Csc<T>) -> Result<Self, FactorizationError> {
+		let size = symbolic.size();
+		let nnz = symbolic.nnz();
+		let mut new = Self {
+			factor: Csc {
+				shape: [size, size],
+				pointers: symbolic.factor_pointers.clone(),
+				indices: vec![0; nnz],
+				values: vec![T::zero(); nnz],
+			},
+			workspace: Workspace {
+				values: vec![T::zero(); symbolic.permuted_indices.len()],
+				dense: vec![T::zero(); size],
+				stack: vec![0; size],
+				mark: vec![NONE; size],
+				next: vec![0; size],
+			},
+			symbolic,
+		};
+		new.refactorize(matrix)?;
+		Ok(new)
+	}
+	/**
+		factorize a new matrix with the same sparsity pattern, reusing the symbolic analysis and all memory buffers.
+
+		On failure, the factorization is left in an unspecified state until a successful refactorization
+	*/
+	pub fn refactorize(&mut self, matrix: &Csc<T>) -> Result<(), FactorizationError> {
+		let symbolic = &self.symbolic;
+		if matrix.pointers() != symbolic.pointers || matrix.indices() != symbolic.indices
+			{return Err(FactorizationError::PatternMismatch)}
+		let Workspace {values, dense, stack, mark, next} = &mut self.workspace;
+		let factor = &mut self.factor;
+		let size = symbolic.size();
+
+		// gather values of the permuted upper triangle
+		for (&position, value) in symbolic.map.iter().zip(matrix.values()) {
+			if position != NONE  {values[position] = *value}
+		}
+		mark.fill(NONE);
+		next.copy_from_slice(&factor.pointers[.. size]);
+
+		// up-looking factorization, computing one row of the factor after the other
+		for k in 0 .. size {
+			let top = symbolic.reach(k, stack, mark);
+			dense[k] = T::zero();
+			for p in symbolic.permuted_pointers[k] .. symbolic.permuted_pointers[k+1] {
+				dense[symbolic.permuted_indices[p]] = values[p];
+			}
+			let mut diagonal = dense[k];
+			dense[k] = T::zero();
+			for &i in &stack[top ..] {
+				let lki = dense[i] / factor.values[factor.pointers[i]];
+				dense[i] = T::zero();
+				for p in factor.pointers[i]+1 .. next[i] {
+					dense[factor.indices[p]] = dense[factor.indices[p]] - factor.values[p] * lki;
+				}
+				diagonal = diagonal - lki * lki;
+				factor.indices[next[i]] = k;
+				factor.values[next[i]] = lki;
+				next[i] += 1;
+			}
+			if diagonal.is_nan() || diagonal <= T::zero()
+				{return Err(FactorizationError::NotPositiveDefinite(symbolic.order[k]))}
+			factor.indices[next[k]] = k;
+			factor.values[next[k]] = diagonal.sqrt();
+			next[k] += 1;
+		}
+		Ok(())
+	}
+
+	/// symbolic analysis used by this factorization
+	pub fn symbolic(&self) -> &CholeskySymbolic  {&self.symbolic}
+	/// lower triangular factor `L` of the permuted matrix `P A Pᵀ`
+	pub fn l(&self) -> &Csc<T>  {&self.factor}
+
+	/// solve `A x = b` for each column of `b`, writing `x` in place of `b`
+	pub fn solve_inplace<A: ArrayMut<Element=T>>(&self, b: &mut Matrix<A>) {
+		self.solve_buffer(b, &mut vec![T::zero(); self.symbolic.size()])
+	}
+	/// same as [Self::solve_inplace] but drawing the temporaries from the workspace
+	pub fn solve_inplace_with_workspace<A: ArrayMut<Element=T>>(&self, b: &mut Matrix<A>, workspace: &workspace::Workspace) {
+		let mut y = workspace.zeros::<T, Dyn, Stat<1>>([self.symbolic.size(), 1]);
+		self.solve_buffer(b, y.as_slice_mut().unwrap())
+	}
+	/// bytes of workspace drawn by [Self::solve_with_workspace] for a right-hand side of the given shape
+	pub fn workspace_size(&self, shape: [usize; 2]) -> usize {
+		workspace_size::<T>([self.symbolic.size(), 1]) + workspace_size::<T>(shape)
+	}
+	/// same as [Self::solve] but drawing the temporaries and result from the workspace
+	pub fn solve_with_workspace<'w, A: Array<Element=T>>(&self, b: &Matrix<A>, workspace: &'w workspace::Workspace) -> ScratchMatrix<'w, T, Dyn, A::C> {
+		let mut x = workspace.field(b.shape(), |index|  b[index]);
+		self.solve_inplace_with_workspace(&mut x, workspace);
+		x
+	}
+	/// solve using a buffer `y` with as many elements as the matrix size
+	pub(crate) fn solve_buffer<A: ArrayMut<Element=T>>(&self, b: &mut Matrix<A>, y: &mut [T]) {
+		let size = self.symbolic.size();
+		assert_eq!(b.rows(), size);
+		let order = &self.symbolic.order;
+		let Csc {pointers, indices, values, ..} = &self.factor;
+		for mut column in b.column_iter_mut() {
+			for (k, &i) in order.iter().enumerate()  {y[k] = column[[i,0]]}
+			// L y = P b
+			for j in 0 .. size {
+				y[j] = y[j] / values[pointers[j]];
+				for p in pointers[j]+1 .. pointers[j+1] {
+					y[indices[p]] = y[indices[p]] - values[p] * y[j];
+				}
+			}
+			// Lᵀ P x = y
+			for j in (0 .. size).rev() {
+				for p in pointers[j]+1 .. pointers[j+1] {
+					y[j] = y[j] - values[p] * y[indices[p]];
+				}
+				y[j] = y[j] / values[pointers[j]];
+			}
+			for (k, &i) in order.iter().enumerate()  {column[[i,0]] = y[k]}
+		}
+	}
+	/// solve `A x = b` for each column of `b`
+	pub fn solve<A: Array<Element=T>>(&self, b: &Matrix<A>) -> Matrix<Dynamic<T, Dyn, A::C>> {
+		let mut x = Matrix::from_field(b.shape(), |index|  b[index]);
+		self.solve_inplace(&mut x);
+		x
+	}
+}
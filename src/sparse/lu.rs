@@ -0,0 +1,404 @@
+/*!
+	Sparse LU factorization `P A Q = L U` with partial pivoting, of square unsymmetric matrices
+
+	The column permutation `Q` is a fill-reducing ordering computed by the symbolic analysis, the row permutation `P` is chosen during the numeric factorization for numerical stability. The symbolic analysis can be reused to refactorize any matrix with the same pattern, and the factors memory is reused as well: the first factorization grows the factors as needed, and refactorizations reserve the worst case of any row pivoting so they don't allocate while factorizing.
+*/
+
+use crate::prelude::*;
+use crate::matrix::*;
+use crate::matrices::*;
+use crate::workspace::{Workspace, ScratchMatrix, workspace_size};
+use super::{Csc, FactorizationError};
+use super::ordering::{Ordering, inverse};
+
+use num_traits::Float;
+
+
+const NONE: usize = usize::MAX;
+
+/// symbolic analysis of a sparse LU factorization, it can be reused for every matrix with the same sparsity pattern
+#[derive(Clone, Debug)]
+pub struct LuSymbolic {
+	/// `order[k]` is the original index of the `k`-th eliminated column
+	order: Vec<usize>,
+	/// bound of the number of nonzeros in each factor
+	bound: usize,
+	/// pattern of the analyzed matrix, to check refactorized matrices
+	pointers: Vec<usize>,
+	indices: Vec<usize>,
+}
+impl LuSymbolic {
+	/**
+		analyze the sparsity pattern of the given matrix, `ordering` is applied to the columns to reduce the factors fill-in.
+
+		The factors size is bounded by the Cholesky factor of `(A Q)ᵀ (A Q)` whatever the row pivoting (George and Ng), whose column counts are computed in almost `O(nnz)` without forming the product
+	*/
+	pub fn new<T: Element>(matrix: &Csc<T>, ordering: &Ordering) -> Result<Self, FactorizationError> {
+		if matrix.rows() != matrix.columns()
+			{return Err(FactorizationError::NotSquare)}
+		let order = ordering.compute(matrix)?;
+		let bound = factor_bound(matrix, &order);
+		Ok(Self {
+			order,
+			bound,
+			pointers: matrix.pointers().to_vec(),
+			indices: matrix.indices().to_vec(),
+		})
+	}
+	/// size of the analyzed matrix
+	pub fn size(&self) -> usize  {self.order.len()}
+	/// column elimination order: `order()[k]` is the original index of the `k`-th eliminated column
+	pub fn order(&self) -> &[usize]  {&self.order}
+	/// bound of the number of nonzeros in each of the factors `L` and `U`, reserved by refactorizations. It can be quadratic in the size for matrices with dense rows
+	pub fn nnz_bound(&self) -> usize  {self.bound}
+}
+
+/**
+	number of nonzeros in the Cholesky factor of `Cᵀ C` where `C = A Q`, without forming the product.
+
+	This is the column counts algorithm of CSparse on the column elimination tree, the elimination tree of `Cᵀ C`, visiting the rows of `C` instead of the columns of the product
+*/
+fn factor_bound<T: Element>(matrix: &Csc<T>, order: &[usize]) -> usize {
+	let size = order.len();
+	let inverse = inverse(order);
+
+	// permuted columns of each row
+	let mut row_pointers = vec![0; size+1];
+	for &i in matrix.indices()  {row_pointers[i+1] += 1}
+	for i in 0 .. size  {row_pointers[i+1] += row_pointers[i]}
+	let mut next = row_pointers.clone();
+	let mut row_columns = vec![0; matrix.nnz()];
+	for ([i, j], _) in matrix.iter() {
+		row_columns[next[i]] = inverse[j];
+		next[i] += 1;
+	}
+	let row = |i: usize|  &row_columns[row_pointers[i] .. row_pointers[i+1]];
+
+	// column elimination tree, linking the columns through the last column seen in each row
+	let mut parent = vec![NONE; size];
+	let mut ancestor = vec![NONE; size];
+	let mut previous = vec![NONE; size];
+	for (k, &column) in order.iter().enumerate() {
+		for &r in matrix.column(column).0 {
+			let mut i = previous[r];
+			while i != NONE && i < k {
+				let next = ancestor[i];
+				ancestor[i] = k;
+				if next == NONE  {parent[i] = k; break}
+				i = next;
+			}
+			previous[r] = k;
+		}
+	}
+
+	// postorder of the tree by an iterative depth first search over the children lists
+	let (mut head, mut next) = (vec![NONE; size], vec![NONE; size]);
+	for j in (0 .. size).rev() {
+		if parent[j] != NONE  {next[j] = head[parent[j]]; head[parent[j]] = j}
+	}
+	let mut post = Vec::with_capacity(size);
+	let mut stack = Vec::new();
+	for root in (0 .. size).filter(|&j|  parent[j] == NONE) {
+		stack.push(root);
+		while let Some(&j) = stack.last() {
+			let child = head[j];
+			if child == NONE  {stack.pop(); post.push(j)}
+			else  {head[j] = next[child]; stack.push(child)}
+		}
+	}
+
+	// first descendant of each node in postorder, leaves of the tree start with a count of one
+	let mut first = vec![NONE; size];
+	let mut delta = vec![0isize; size];
+	for (k, &leaf) in post.iter().enumerate() {
+		if first[leaf] == NONE  {delta[leaf] = 1}
+		let mut j = leaf;
+		while j != NONE && first[j] == NONE  {first[j] = k; j = parent[j]}
+	}
+	// each row is visited at the first of its columns in postorder
+	let mut position = vec![0; size];
+	for (k, &j) in post.iter().enumerate()  {position[j] = k}
+	let (mut row_head, mut row_next) = (vec![NONE; size], vec![NONE; size]);
+	for (i, next) in row_next.iter_mut().enumerate() {
+		if let Some(k) = row(i).iter().map(|&j|  position[j]).min()
+			{*next = row_head[k]; row_head[k] = i}
+	}
+
+	// count the skeleton entries of each column, removing their overlaps at the least common ancestors
+	let mut max_first = vec![NONE; size];
+	let mut previous_leaf = vec![NONE; size];
+	let mut ancestor: Vec<usize> = (0 .. size).collect();
+	for (k, &j) in post.iter().enumerate() {
+		if parent[j] != NONE  {delta[parent[j]] -= 1}
+		let mut r = row_head[k];
+		while r != NONE {
+			for &i in row(r) {
+				// j is not a leaf of the subtree of i
+				if i <= j || (max_first[i] != NONE && first[j] <= max_first[i])  {continue}
+				max_first[i] = first[j];
+				delta[j] += 1;
+				let previous = core::mem::replace(&mut previous_leaf[i], j);
+				if previous != NONE {
+					let mut q = previous;
+					while q != ancestor[q]  {q = ancestor[q]}
+					let mut s = previous;
+					while s != q  {s = core::mem::replace(&mut ancestor[s], q)}
+					delta[q] -= 1;
+				}
+			}
+			r = row_next[r];
+		}
+		if parent[j] != NONE  {ancestor[j] = parent[j]}
+	}
+	// parents come after their children
+	for j in 0 .. size {
+		if parent[j] != NONE  {delta[parent[j]] += delta[j]}
+	}
+	delta.iter().sum::<isize>() as usize
+}
+
+/// column compressed triangular factor, with unsorted indices
+#[derive(Clone, Debug)]
+struct Factor<T> {
+	pointers: Vec<usize>,
+	indices: Vec<usize>,
+	values: Vec<T>,
+}
+impl<T> Factor<T> {
+	/**
+		empty the factor, keeping room for the given number of columns and nonzeros so filling it doesn't reallocate.
+
+		The nonzeros are only reserved if the memory is available, otherwise the factor grows while being filled
+	*/
+	fn clear(&mut self, columns: usize, nnz: usize) {
+		self.pointers.clear();
+		self.indices.clear();
+		self.values.clear();
+		self.pointers.reserve(columns+1);
+		if self.indices.try_reserve(nnz).is_err() || self.values.try_reserve(nnz).is_err() {
+			self.indices.shrink_to_fit();
+			self.values.shrink_to_fit();
+		}
+	}
+	fn push(&mut self, index: usize, value: T) {
+		self.indices.push(index);
+		self.values.push(value);
+	}
+}
+
+/// sparse LU factorization `P A Q = L U` of a square matrix
+#[derive(Clone, Debug)]
+pub struct SparseLu<T: Element> {
+	symbolic: LuSymbolic,
+	/// unit lower triangular factor, with the diagonal first in each column
+	lower: Factor<T>,
+	/// upper triangular factor, with the diagonal last in each column
+	upper: Factor<T>,
+	/// `pivots[i]` is the row of the factors where the row `i` of the matrix was eliminated
+	pivots: Vec<usize>,
+	/// buffers used by the numeric factorization
+	dense: Vec<T>,
+	stack: Vec<usize>,
+	nodes: Vec<usize>,
+	cursors: Vec<usize>,
+	mark: Vec<usize>,
+}
+impl<T: Scalar + Float> SparseLu<T> {
+	/// analyze and factorize the given matrix
+	pub fn new(matrix: &Csc<T>, ordering: &Ordering) -> Result<Self, FactorizationError> {
+		Self::from_symbolic(LuSymbolic::new(matrix, ordering)?, matrix)
+	}
+	/// factorize the given matrix using a previous symbolic analysis
+	pub fn from_symbolic(symbolic: LuSymbolic, matrix: &Csc<T>) -> Result<Self, FactorizationError> {
+		let size = symbolic.size();
+		let factor = ||  Factor {pointers: Vec::new(), indices: Vec::new(), values: Vec::new()};
+		let mut new = Self {
+			lower: factor(),
+			upper: factor(),
+			pivots: vec![NONE; size],
+			dense: vec![T::zero(); size],
+			stack: vec![0; size],
+			nodes: vec![0; size],
+			cursors: vec![0; size],
+			mark: vec![NONE; size],
+			symbolic,
+		};
+		new.check(matrix)?;
+		// the factors grow from the usual guess of CSparse, the bound is reserved only for refactorizations
+		let guess = (4*matrix.nnz() + size).min(new.symbolic.bound);
+		new.factorize(matrix, guess)?;
+		Ok(new)
+	}
+	/**
+		factorize a new matrix with the same sparsity pattern, reusing the symbolic analysis and the factors memory.
+
+		The row pivoting is done again, so the factors pattern may differ from the previous factorization, so the memory given by [LuSymbolic::nnz_bound] is reserved for each factor and no allocation happens while factorizing. If that memory is not available the factors grow as needed instead.
+		On failure, the factorization is left in an unspecified state until a successful refactorization
+	*/
+	pub fn refactorize(&mut self, matrix: &Csc<T>) -> Result<(), FactorizationError> {
+		self.check(matrix)?;
+		self.factorize(matrix, self.symbolic.bound)
+	}
+	/// check the matrix has the analyzed sparsity pattern
+	fn check(&self, matrix: &Csc<T>) -> Result<(), FactorizationError> {
+		if matrix.pointers() != self.symbolic.pointers || matrix.indices() != self.symbolic.indices
+			{return Err(FactorizationError::PatternMismatch)}
+		Ok(())
+	}
+	/// numeric factorization of a matrix with the analyzed pattern, with room for `nnz` nonzeros in each factor
+	fn factorize(&mut self, matrix: &Csc<T>, nnz: usize) -> Result<(), FactorizationError> {
+		let size = self.symbolic.size();
+		self.lower.clear(size, nnz);
+		self.upper.clear(size, nnz);
+		self.pivots.fill(NONE);
+		self.mark.fill(NONE);
+
+		// left-looking factorization, computing one column of the factors after the other
+		for k in 0 .. size {
+			self.lower.pointers.push(self.lower.indices.len());
+			self.upper.pointers.push(self.upper.indices.len());
+			let column = self.symbolic.order[k];
+			let (rows, values) = matrix.column(column);
+
+			// solve L x = A[:,column] on the pattern reached from the column
+			let top = self.reach(rows, k);
+			for &i in &self.stack[top ..]  {self.dense[i] = T::zero()}
+			for (&i, &value) in rows.iter().zip(values)  {self.dense[i] = value}
+			for &j in &self.stack[top ..] {
+				let pivot = self.pivots[j];
+				if pivot == NONE  {continue}
+				// the diagonal of L is one and stored first
+				for p in self.lower.pointers[pivot]+1 .. self.lower.pointers[pivot+1] {
+					let i = self.lower.indices[p];
+					self.dense[i] = self.dense[i] - self.lower.values[p] * self.dense[j];
+				}
+			}
+
+			// partial pivoting, preferring the diagonal on ties
+			let mut best = None;
+			for &i in &self.stack[top ..] {
+				if self.pivots[i] == NONE {
+					if best.is_none_or(|(_, magnitude)| self.dense[i].abs() > magnitude)
+						{best = Some((i, self.dense[i].abs()))}
+				}
+				else {
+					self.upper.push(self.pivots[i], self.dense[i]);
+				}
+			}
+			let Some((mut pivot, magnitude)) = best.filter(|&(_, magnitude)| magnitude > T::zero())
+				else {return Err(FactorizationError::Singular(column))};
+			if self.pivots[column] == NONE && self.dense[column].abs() >= magnitude
+				{pivot = column}
+
+			let value = self.dense[pivot];
+			self.upper.push(k, value);
+			self.pivots[pivot] = k;
+			self.lower.push(pivot, T::one());
+			for &i in &self.stack[top ..] {
+				if self.pivots[i] == NONE  {self.lower.push(i, self.dense[i] / value)}
+				self.dense[i] = T::zero();
+			}
+		}
+		self.lower.pointers.push(self.lower.indices.len());
+		self.upper.pointers.push(self.upper.indices.len());
+		// rows of L were stored with their original index
+		for i in self.lower.indices.iter_mut()  {*i = self.pivots[*i]}
+		Ok(())
+	}
+
+	/**
+		rows reachable in the graph of L from the given rows, in topological order. This is the pattern of the solution of `L x = A[:,k]`.
+
+		It is written in `self.stack[top..]` and `top` is returned
+	*/
+	fn reach(&mut self, rows: &[usize], k: usize) -> usize {
+		let mut top = self.symbolic.size();
+		// iterative depth first search, `self.nodes[..=head]` are the nodes being explored and `self.cursors` the next child to explore for each
+		for &start in rows {
+			if self.mark[start] == k  {continue}
+			let mut head = 0;
+			self.nodes[0] = start;
+			loop {
+				let j = self.nodes[head];
+				let pivot = self.pivots[j];
+				let end = if pivot == NONE {0} else {self.lower.pointers[pivot+1]};
+				if self.mark[j] != k {
+					self.mark[j] = k;
+					// the first element of the column is the diagonal
+					self.cursors[head] = if pivot == NONE {0} else {self.lower.pointers[pivot]+1};
+				}
+				let child = (self.cursors[head] .. end)
+					.find(|&p|  self.mark[self.lower.indices[p]] != k);
+				if let Some(p) = child {
+					self.cursors[head] = p+1;
+					head += 1;
+					self.nodes[head] = self.lower.indices[p];
+				}
+				else {
+					top -= 1;
+					self.stack[top] = j;
+					if head == 0  {break}
+					head -= 1;
+				}
+			}
+		}
+		top
+	}
+
+	/// symbolic analysis used by this factorization
+	pub fn symbolic(&self) -> &LuSymbolic  {&self.symbolic}
+	/// number of nonzeros in the factors `L` and `U`
+	pub fn nnz(&self) -> [usize; 2]  {[self.lower.indices.len(), self.upper.indices.len()]}
+	/// row permutation: `pivots()[i]` is the row of the factors where the row `i` of the matrix was eliminated
+	pub fn pivots(&self) -> &[usize]  {&self.pivots}
+
+	/// solve `A x = b` for each column of `b`, writing `x` in place of `b`
+	pub fn solve_inplace<A: ArrayMut<Element=T>>(&self, b: &mut Matrix<A>) {
+		self.solve_buffer(b, &mut vec![T::zero(); self.symbolic.size()])
+	}
+	/// same as [Self::solve_inplace] but drawing the temporaries from the workspace
+	pub fn solve_inplace_with_workspace<A: ArrayMut<Element=T>>(&self, b: &mut Matrix<A>, workspace: &Workspace) {
+		let mut y = workspace.zeros::<T, Dyn, Stat<1>>([self.symbolic.size(), 1]);
+		self.solve_buffer(b, y.as_slice_mut().unwrap())
+	}
+	/// bytes of workspace drawn by [Self::solve_with_workspace] for a right-hand side of the given shape
+	pub fn workspace_size(&self, shape: [usize; 2]) -> usize {
+		workspace_size::<T>([self.symbolic.size(), 1]) + workspace_size::<T>(shape)
+	}
+	/// same as [Self::solve] but drawing the temporaries and result from the workspace
+	pub fn solve_with_workspace<'w, A: Array<Element=T>>(&self, b: &Matrix<A>, workspace: &'w Workspace) -> ScratchMatrix<'w, T, Dyn, A::C> {
+		let mut x = workspace.field(b.shape(), |index|  b[index]);
+		self.solve_inplace_with_workspace(&mut x, workspace);
+		x
+	}
+	/// solve using a buffer `y` with as many elements as the matrix size
+	pub(crate) fn solve_buffer<A: ArrayMut<Element=T>>(&self, b: &mut Matrix<A>, y: &mut [T]) {
+		let size = self.symbolic.size();
+		assert_eq!(b.rows(), size);
+		let (lower, upper) = (&self.lower, &self.upper);
+		for mut column in b.column_iter_mut() {
+			for (i, &k) in self.pivots.iter().enumerate()  {y[k] = column[[i,0]]}
+			// L y = P b
+			for j in 0 .. size {
+				for p in lower.pointers[j]+1 .. lower.pointers[j+1] {
+					y[lower.indices[p]] = y[lower.indices[p]] - lower.values[p] * y[j];
+				}
+			}
+			// U Q⁻¹ x = y
+			for j in (0 .. size).rev() {
+				let diagonal = upper.pointers[j+1]-1;
+				y[j] = y[j] / upper.values[diagonal];
+				for p in upper.pointers[j] .. diagonal {
+					y[upper.indices[p]] = y[upper.indices[p]] - upper.values[p] * y[j];
+				}
+			}
+			for (k, &j) in self.symbolic.order.iter().enumerate()  {column[[j,0]] = y[k]}
+		}
+	}
+	/// solve `A x = b` for each column of `b`
+	pub fn solve<A: Array<Element=T>>(&self, b: &Matrix<A>) -> Matrix<Dynamic<T, Dyn, A::C>> {
+		let mut x = Matrix::from_field(b.shape(), |index|  b[index]);
+		self.solve_inplace(&mut x);
+		x
+	}
+}
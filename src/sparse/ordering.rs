@@ -0,0 +1,223 @@
+/*!
+	Fill-reducing orderings for sparse factorizations
+
+	Eliminating rows and columns of a sparse matrix in a good order greatly reduces the number of nonzeros created in its factors
+*/
+
+use crate::prelude::*;
+use super::{Csc, FactorizationError};
+
+use core::cmp::Reverse;
+use std::collections::BinaryHeap;
+
+
+/// choice of the elimination order for a sparse factorization
+#[derive(Clone, Debug, Eq, PartialEq, Default)]
+pub enum Ordering {
+	/// keep the matrix order
+	Natural,
+	/**
+		approximate minimum degree (AMD) on the symmetric pattern `A + Aᵀ`.
+
+		Eliminations are tracked in a quotient graph, whose size never exceeds the matrix pattern, with approximate external degrees, element absorption and merging of indistinguishable nodes into supervariables. It is well suited for meshes and most finite-element matrices
+	*/
+	#[default]
+	MinimumDegree,
+	/**
+		order given by the user, like a nested-dissection computed by an external partitioner.
+
+		`order[k]` is the original index of the `k`-th eliminated row/column
+	*/
+	Given(Vec<usize>),
+}
+impl Ordering {
+	/// compute the elimination order for the given square matrix, `order[k]` is the original index of the `k`-th eliminated row/column
+	pub fn compute<T: Element>(&self, matrix: &Csc<T>) -> Result<Vec<usize>, FactorizationError> {
+		if matrix.rows() != matrix.columns()
+			{return Err(FactorizationError::NotSquare)}
+		let size = matrix.columns();
+		match self {
+			Self::Natural => Ok((0 .. size).collect()),
+			Self::MinimumDegree => Ok(minimum_degree(matrix)),
+			Self::Given(order) => {
+				if order.len() != size || !is_permutation(order)
+					{return Err(FactorizationError::InvalidOrdering)}
+				Ok(order.clone())
+			},
+		}
+	}
+}
+
+/// `true` if the indices are all different and in range
+pub(crate) fn is_permutation(order: &[usize]) -> bool {
+	let mut seen = vec![false; order.len()];
+	order.iter().all(|&i|  i < seen.len() && !core::mem::replace(&mut seen[i], true))
+}
+
+/// inverse of a permutation
+pub(crate) fn inverse(order: &[usize]) -> Vec<usize> {
+	let mut inverse = vec![0; order.len()];
+	for (k, &i) in order.iter().enumerate() {
+		inverse[i] = k;
+	}
+	inverse
+}
+
+/// state of a node in the quotient graph
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+enum Node {
+	/// not yet eliminated, and representing its supervariable
+	Variable,
+	/// merged in the supervariable of another variable
+	Merged,
+	/// eliminated, its neighbors are the members of the element it formed
+	Element,
+	/// eliminated, and its element is included in a newer one
+	Absorbed,
+}
+
+/**
+	approximate minimum degree ordering, after Amestoy, Davis and Duff (1996)
+
+	Instead of forming cliques, each eliminated node becomes an element listing its uneliminated neighbors, so neighbors of a variable are the variables it is directly adjacent to and the members of its adjacent elements. Elements included in a newer one are absorbed, and variables with the same adjacency are merged in supervariables eliminated together. The external degree of each variable is bounded instead of computed exactly
+*/
+fn minimum_degree<T: Element>(matrix: &Csc<T>) -> Vec<usize> {
+	let size = matrix.columns();
+	// variables directly adjacent to each variable, in the symmetric pattern without diagonal
+	let mut variables = vec![Vec::new(); size];
+	for ([i, j], _) in matrix.iter() {
+		if i != j {
+			variables[i].push(j);
+			variables[j].push(i);
+		}
+	}
+	for neighbors in variables.iter_mut() {
+		neighbors.sort_unstable();
+		neighbors.dedup();
+	}
+	// elements adjacent to each variable
+	let mut elements: Vec<Vec<usize>> = vec![Vec::new(); size];
+	// variables in each element, and their total weight
+	let mut members: Vec<Vec<usize>> = vec![Vec::new(); size];
+	let mut element_weight = vec![0; size];
+	let mut status = vec![Node::Variable; size];
+	// number of original variables in each supervariable, and these variables
+	let mut weight = vec![1; size];
+	let mut group = (0 .. size).map(|i|  vec![i]).collect::<Vec<_>>();
+	let mut degree = variables.iter().map(Vec::len).collect::<Vec<_>>();
+	let mut remaining = size;
+
+	// mark of nodes visited during the current elimination
+	let mut mark = vec![usize::MAX; size];
+	// weight of each element outside the current pivot element, valid when marked
+	let mut outside = vec![0; size];
+	let mut seen = vec![usize::MAX; size];
+
+	// degrees are updated lazily: outdated heap entries are skipped
+	let mut heap = degree.iter().enumerate()
+		.map(|(node, &degree)|  Reverse((degree, node)))
+		.collect::<BinaryHeap<_>>();
+	let mut order = Vec::with_capacity(size);
+	while let Some(Reverse((current, pivot))) = heap.pop() {
+		if status[pivot] != Node::Variable || degree[pivot] != current
+			{continue}
+		order.extend_from_slice(&group[pivot]);
+		remaining -= weight[pivot];
+
+		// the pivot becomes an element gathering its neighbors, absorbing its adjacent elements
+		let mut element = Vec::new();
+		mark[pivot] = pivot;
+		for e in core::mem::take(&mut elements[pivot]) {
+			if status[e] != Node::Element  {continue}
+			for &i in &members[e] {
+				if status[i] == Node::Variable && mark[i] != pivot {
+					mark[i] = pivot;
+					element.push(i);
+				}
+			}
+			status[e] = Node::Absorbed;
+			members[e] = Vec::new();
+		}
+		for i in core::mem::take(&mut variables[pivot]) {
+			if status[i] == Node::Variable && mark[i] != pivot {
+				mark[i] = pivot;
+				element.push(i);
+			}
+		}
+		status[pivot] = Node::Element;
+
+		// edges between members are now represented by the element
+		for &i in &element {
+			elements[i].retain(|&e|  status[e] == Node::Element);
+			elements[i].push(pivot);
+			variables[i].retain(|&k|  status[k] == Node::Variable && mark[k] != pivot);
+		}
+		// weight of other elements outside the new one
+		for &i in &element {
+			for &e in &elements[i] {
+				if e == pivot  {continue}
+				if seen[e] != pivot {
+					seen[e] = pivot;
+					outside[e] = element_weight[e];
+				}
+				outside[e] -= weight[i];
+			}
+		}
+		// elements included in the new one are absorbed
+		for &i in &element {
+			elements[i].retain(|&e|  {
+				if e != pivot && outside[e] == 0 && status[e] == Node::Element {
+					status[e] = Node::Absorbed;
+					members[e] = Vec::new();
+				}
+				status[e] == Node::Element
+			});
+		}
+
+		// merge indistinguishable variables, having the same adjacent elements and variables
+		let mut hashes = element.iter()
+			.map(|&i|  {
+				elements[i].sort_unstable();
+				variables[i].sort_unstable();
+				let hash = elements[i].iter().chain(&variables[i])
+					.fold(0usize, |hash, &k|  hash.wrapping_add(k));
+				(hash, i)
+			})
+			.collect::<Vec<_>>();
+		hashes.sort_unstable();
+		for (start, &(hash, i)) in hashes.iter().enumerate() {
+			if status[i] != Node::Variable  {continue}
+			for &(_, j) in hashes[start+1 ..].iter().take_while(|&&(other, _)|  other == hash) {
+				if status[j] == Node::Variable && elements[i] == elements[j] && variables[i] == variables[j] {
+					status[j] = Node::Merged;
+					weight[i] += core::mem::take(&mut weight[j]);
+					let merged = core::mem::take(&mut group[j]);
+					group[i].extend(merged);
+					variables[j] = Vec::new();
+					elements[j] = Vec::new();
+				}
+			}
+		}
+		element.retain(|&i|  status[i] == Node::Variable);
+		element_weight[pivot] = element.iter().map(|&i|  weight[i]).sum();
+
+		// approximate external degrees of the element members
+		for &i in &element {
+			let inside = element_weight[pivot] - weight[i];
+			let direct = variables[i].iter()
+				.filter(|&&k|  status[k] == Node::Variable)
+				.map(|&k|  weight[k])
+				.sum::<usize>();
+			let through = elements[i].iter()
+				.filter(|&&e|  e != pivot)
+				.map(|&e|  outside[e])
+				.sum::<usize>();
+			degree[i] = (remaining - weight[i])
+				.min(degree[i] + inside)
+				.min(direct + inside + through);
+			heap.push(Reverse((degree[i], i)));
+		}
+		members[pivot] = element;
+	}
+	order
+}
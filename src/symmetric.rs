@@ -0,0 +1,288 @@
+/*!
+	Symmetric matrices in packed storage, keeping only `n(n+1)/2` elements
+
+	The upper triangle is packed column by column (the LAPACK `'U'` packed layout), so element `[i,j]` with `i <= j` is at position `i + j(j+1)/2` and `[j,i]` refers to the same element.
+
+	Packed storage cannot be expressed by a pointer and strides, so [Symmetric] is not an [Array]. Instead, elements are accessed by index, and [Symmetric::to_dense] or [Symmetric::materialize_to] give a full [Matrix] to use with the rest of the crate. The products with dense matrices ([MulTo] and [Mul]) and the symmetric rank-k update ([Symmetric::syrk]) work directly on the packed storage.
+
+	Since this crate has no complex scalars, symmetric and hermitian matrices are the same.
+*/
+
+use crate::prelude::*;
+use crate::matrix::*;
+use crate::matrices::*;
+use crate::operators::*;
+use crate::krylov::LinearOperator;
+use crate::workspace::{self, Workspace, ScratchMatrix, MulWithWorkspace};
+
+use core::{
+	marker::PhantomData,
+	ops::{Index, IndexMut, Mul},
+	};
+
+
+/**
+	symmetric matrix storing only its upper triangle, packed column by column in `S`
+
+	Prefer the type aliases [SymmetricPacked] and [DynamicSymmetric]
+*/
+#[derive(Clone, PartialEq)]
+pub struct Symmetric<T, D: Dim, S> {
+	size: D,
+	data: S,
+	element: PhantomData<T>,
+}
+/**
+	statically allocated symmetric packed matrix of size `N`
+
+	`L` must be `N(N+1)/2`, it has to be given because rust cannot yet compute it from `N`. For instance `SymmetricPacked<f64, 3, 6>`
+*/
+pub type SymmetricPacked<T, const N: usize, const L: usize> = Symmetric<T, Stat<N>, [T; L]>;
+/// dynamically allocated symmetric packed matrix
+pub type DynamicSymmetric<T> = Symmetric<T, Dyn, Vec<T>>;
+
+/// number of elements needed to pack a symmetric matrix of the given size
+pub const fn packed_len(size: usize) -> usize  {size*(size+1)/2}
+/// position of the given index in the packed storage
+fn position([i, j]: [usize; 2]) -> usize {
+	let (i, j) = if i <= j {(i, j)} else {(j, i)};
+	i + j*(j+1)/2
+}
+
+impl<T: Element, D: Dim, S: AsRef<[T]>> Symmetric<T, D, S> {
+	/// pack the given data, it must hold `packed_len(size)` elements
+	pub fn try_from_packed(size: usize, data: S) -> Option<Self> {
+		if data.as_ref().len() != packed_len(size)  {return None}
+		Some(Self {size: D::check(size)?, data, element: PhantomData})
+	}
+	/// number of rows and columns
+	pub fn size(&self) -> usize  {self.size.value()}
+	/// shape of the matrix
+	pub fn shape(&self) -> [usize; 2]  {[self.size(), self.size()]}
+	/// packed upper triangle
+	pub fn packed(&self) -> &[T]  {self.data.as_ref()}
+	/// retreive the packed storage
+	pub fn into_packed(self) -> S  {self.data}
+	/// element at the given index, if in the matrix
+	pub fn get(&self, index: [usize; 2]) -> Option<&T> {
+		if index[0] < self.size() && index[1] < self.size()  {Some(&self.packed()[position(index)])}
+		else {None}
+	}
+}
+impl<T: Element, D: Dim, S: AsRef<[T]> + AsMut<[T]>> Symmetric<T, D, S> {
+	/// packed upper triangle, mutable
+	pub fn packed_mut(&mut self) -> &mut [T]  {self.data.as_mut()}
+	/// mutable element at the given index, it is also the element at the transposed index
+	pub fn get_mut(&mut self, index: [usize; 2]) -> Option<&mut T> {
+		if index[0] < self.size() && index[1] < self.size()  {Some(&mut self.packed_mut()[position(index)])}
+		else {None}
+	}
+	/// set all elements by a function of their index, it is only called for the upper triangle `i <= j`
+	pub fn set_field<F>(&mut self, mut field: F) -> &mut Self
+	where F: FnMut([usize; 2]) -> T
+	{
+		let size = self.size();
+		let mut packed = self.packed_mut().iter_mut();
+		for j in 0 .. size {
+			for i in 0 ..= j {
+				*packed.next().unwrap() = field([i, j]);
+			}
+		}
+		self
+	}
+	/// copy the upper triangle of the given square matrix
+	pub fn set_upper<A: Array<Element=T>>(&mut self, matrix: &Matrix<A>) -> &mut Self {
+		assert_eq!(matrix.shape(), self.shape());
+		self.set_field(|index|  matrix[index].clone())
+	}
+	/// copy the lower triangle of the given square matrix
+	pub fn set_lower<A: Array<Element=T>>(&mut self, matrix: &Matrix<A>) -> &mut Self {
+		assert_eq!(matrix.shape(), self.shape());
+		self.set_field(|[i, j]|  matrix[[j, i]].clone())
+	}
+}
+impl<T: Scalar, D: Dim, S: AsRef<[T]> + AsMut<[T]>> Symmetric<T, D, S> {
+	/**
+		symmetric rank-k update `self = alpha a aᵀ + beta self`, only computing the packed triangle
+
+		`a` can have any number of columns
+	*/
+	pub fn syrk<A: Array<Element=T>>(&mut self, alpha: T, a: &Matrix<A>, beta: T) -> &mut Self {
+		assert_eq!(a.rows(), self.size());
+		let size = self.size();
+		let mut packed = self.packed_mut().iter_mut();
+		for j in 0 .. size {
+			for i in 0 ..= j {
+				let element = packed.next().unwrap();
+				// safety: shape has been checked above
+				let product = (0 .. a.columns())
+					.map(|k|  unsafe {a.get_unchecked([i,k]).clone() * a.get_unchecked([j,k]).clone()})
+					.fold(T::zero(), |sum, x|  sum + x);
+				*element = alpha.clone() * product + beta.clone() * element.clone();
+			}
+		}
+		self
+	}
+	/// write the full matrix in the given array
+	pub fn materialize_to<'o, O: ArrayMut<Element=T>>(&self, out: &'o mut Matrix<O>) -> &'o mut Matrix<O> {
+		assert_eq!(out.shape(), self.shape());
+		out.set_field(|index|  self.packed()[position(index)].clone())
+	}
+}
+impl<T: Scalar, D: Dim, S: AsRef<[T]>> Symmetric<T, D, S> {
+	/// copy into a full dense matrix, so it can be used as any other matrix
+	pub fn to_dense(&self) -> Matrix<Dynamic<T, D, D>> {
+		Matrix::from_field(self.shape(), |index|  self.packed()[position(index)].clone())
+	}
+}
+
+/// constructors
+impl<T: Scalar, const N: usize, const L: usize> SymmetricPacked<T, N, L> {
+	pub fn zeros() -> Self  {Self::field(|_|  T::zero())}
+	pub fn identity() -> Self  {Self::field(|[i, j]|  if i == j {T::one()} else {T::zero()})}
+	/// build from a function of the index, it is only called for the upper triangle `i <= j`
+	pub fn field<F>(field: F) -> Self
+	where F: FnMut([usize; 2]) -> T
+	{
+		const {assert!(L == packed_len(N), "packed length must be N(N+1)/2")};
+		let mut new = Self {size: Stat{}, data: core::array::from_fn(|_|  T::zero()), element: PhantomData};
+		new.set_field(field);
+		new
+	}
+	/// copy the upper triangle of the given square matrix
+	pub fn from_upper<A: Array<Element=T>>(matrix: &Matrix<A>) -> Self {
+		assert_eq!(matrix.shape(), [N, N]);
+		Self::field(|index|  matrix[index].clone())
+	}
+}
+/// constructors
+impl<T: Scalar> DynamicSymmetric<T> {
+	pub fn zeros(size: usize) -> Self  {Self::field(size, |_|  T::zero())}
+	pub fn identity(size: usize) -> Self  {Self::field(size, |[i, j]|  if i == j {T::one()} else {T::zero()})}
+	/// build from a function of the index, it is only called for the upper triangle `i <= j`
+	pub fn field<F>(size: usize, field: F) -> Self
+	where F: FnMut([usize; 2]) -> T
+	{
+		let mut new = Self {size: Dyn(size), data: vec![T::zero(); packed_len(size)], element: PhantomData};
+		new.set_field(field);
+		new
+	}
+	/// copy the upper triangle of the given square matrix
+	pub fn from_upper<A: Array<Element=T>>(matrix: &Matrix<A>) -> Self {
+		assert_eq!(matrix.rows(), matrix.columns());
+		Self::field(matrix.rows(), |index|  matrix[index].clone())
+	}
+}
+
+impl<T: Element, D: Dim, S: AsRef<[T]>> Index<[usize; 2]> for Symmetric<T, D, S> {
+	type Output = T;
+	fn index(&self, index: [usize; 2]) -> &T  {self.get(index).expect("index out of bounds")}
+}
+impl<T: Element, D: Dim, S: AsRef<[T]> + AsMut<[T]>> IndexMut<[usize; 2]> for Symmetric<T, D, S> {
+	fn index_mut(&mut self, index: [usize; 2]) -> &mut T  {self.get_mut(index).expect("index out of bounds")}
+}
+
+impl<T, D, S, R, O>
+	MulTo<Matrix<R>, Matrix<O>> for Symmetric<T, D, S>
+where
+	T: Scalar,
+	D: Dim,
+	S: AsRef<[T]>,
+	R: Array<Element=T, C=O::C>,
+	O: ArrayMut<Element=T, R=D>,
+{
+	/// symmetric-dense product without dynamic allocation, reading each packed element once
+	fn mul_to<'o>(&self, right: &Matrix<R>, out: &'o mut Matrix<O>) -> &'o mut Matrix<O> {
+		let size = self.size();
+		assert_eq!(right.rows(), size);
+		assert_eq!(out.shape(), [size, right.columns()]);
+		out.set_zero();
+		for c in 0 .. right.columns() {
+			let mut packed = self.packed().iter();
+			for j in 0 .. size {
+				for i in 0 ..= j {
+					let element = packed.next().unwrap().clone();
+					// safety: shapes have been checked above
+					unsafe {
+						let value = out.get_unchecked([i,c]).clone() + element.clone() * right.get_unchecked([j,c]).clone();
+						*out.get_unchecked_mut([i,c]) = value;
+						if i != j {
+							let value = out.get_unchecked([j,c]).clone() + element * right.get_unchecked([i,c]).clone();
+							*out.get_unchecked_mut([j,c]) = value;
+						}
+					}
+				}
+			}
+		}
+		out
+	}
+}
+impl<T, D, S, R>
+	Mul<&Matrix<R>> for &Symmetric<T, D, S>
+where
+	T: Scalar,
+	D: Dim,
+	S: AsRef<[T]>,
+	R: Array<Element=T>,
+{
+	type Output = Matrix<Dynamic<T, D, R::C>>;
+	/// symmetric-dense product
+	fn mul(self, right: &Matrix<R>) -> Self::Output {
+		// the product accumulates in zeros
+		let mut new = Matrix::from_field([self.size(), right.columns()], |_|  T::zero());
+		self.mul_to(right, &mut new);
+		new
+	}
+}
+impl<'w, T, D, S, R>
+	MulWithWorkspace<'w, Matrix<R>> for Symmetric<T, D, S>
+where
+	T: Scalar,
+	D: Dim,
+	S: AsRef<[T]>,
+	R: Array<Element=T>,
+{
+	type Output = ScratchMatrix<'w, T, D, R::C>;
+	/// symmetric-dense product
+	fn mul_with_workspace(&self, right: &Matrix<R>, workspace: &'w Workspace) -> Self::Output {
+		workspace::product(self, right, [self.size(), right.columns()], workspace)
+	}
+}
+impl<T: Scalar, S: AsRef<[T]>> LinearOperator<T> for Symmetric<T, Dyn, S> {
+	fn shape(&self) -> [usize; 2]  {Symmetric::shape(self)}
+	fn apply(&self, x: &VectorView<'_, T>, y: &mut VectorViewMut<'_, T>)  {self.mul_to(x, y);}
+}
+
+
+#[test]
+fn test_symmetric() {
+	let dense = SMatrix::<f64,3,3>::field(|[i,j]|  (i*j + i + j) as f64);
+	let packed = SymmetricPacked::<f64, 3, 6>::from_upper(&dense);
+	assert_eq!(packed.packed(), [0., 1., 3., 2., 5., 8.]);
+	assert_eq!(packed[[2,1]], packed[[1,2]]);
+	assert_eq!(packed.to_dense().as_slice(), dense.as_slice());
+	assert_eq!(packed.get([3,0]), None);
+
+	let mut dynamic = DynamicSymmetric::from_upper(&dense);
+	dynamic[[0,2]] = 10.;
+	assert_eq!(dynamic[[2,0]], 10.);
+	assert_eq!(dynamic.packed().len(), packed_len(3));
+	let mut out = SMatrix::<f64,3,3>::zeros();
+	assert_eq!(dynamic.materialize_to(&mut out)[[2,0]], 10.);
+
+	let x = SMatrix::<f64,3,2>::field(|[i,j]|  (i + 3*j) as f64 - 1.);
+	assert_eq!((&packed * &x).as_slice(), (&dense * &x).as_slice());
+	let mut out = SMatrix::<f64,3,2>::zeros();
+	packed.mul_to(&x, &mut out);
+	assert_eq!(out.as_slice(), (&dense * &x).as_slice());
+
+	// covariance update
+	let samples = DMatrix::<f64>::field([3, 4], |[i,j]|  (i + 2*j) as f64 * 0.5);
+	let mut covariance = DynamicSymmetric::<f64>::identity(3);
+	covariance.syrk(2., &samples, 3.);
+	let expected = &(&(&samples * &samples.transpose()) * 2.) + &(&DMatrix::<f64>::identity([3,3]) * 3.);
+	assert_eq!(covariance.to_dense().as_slice(), expected.as_slice());
+
+	assert!(DynamicSymmetric::try_from_packed(3, vec![0.; 5]).is_none());
+	assert!(DynamicSymmetric::try_from_packed(3, vec![0.; 6]).is_some());
+}
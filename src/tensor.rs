@@ -0,0 +1,763 @@
+/*!
+	Dense tensors of any number of dimensions, generalizing [Array] and [Matrix] beyond two dimensions
+
+	The number of dimensions `D` is a compile-time constant of the [ArrayN] storage, while each dimension size is dynamic. As for matrices, [Tensor] is only a wrapper adding the operations to any strided storage, and the default memory layout is column-major: the first axis is contiguous.
+
+	A [Matrix] is reachable as the `D = 2` case, either through [Matrix::tensor] or by wrapping the matrix itself since it implements [ArrayN]`<2>`.
+*/
+
+use crate::prelude::*;
+use crate::matrix::*;
+use crate::matrices::*;
+use crate::operators::*;
+
+use core::{
+	fmt,
+	iter::zip,
+	marker::PhantomData,
+	ops::*,
+	};
+use num_traits::Zero;
+
+
+/**
+	Expose a dense strided layout of `D` dimensions, this is the equivalent of [Array] for tensors
+
+	The indexing convention is always `[axis0, axis1, ...]` whatever the underlying memory layout is
+*/
+pub trait ArrayN<const D: usize>: Sized {
+	/// array element type
+	type Element: Element;
+
+	/// size of the array in each dimension
+	fn shape(&self) -> [usize; D];
+	/// step between values in each dimension
+	fn strides(&self) -> [isize; D];
+	/// pointer to element `[0, ..., 0]` in the array
+	fn as_ptr(&self) -> *const Self::Element;
+}
+/// Add the mutability to tensor contents, as [ArrayMut] does for matrices
+pub trait ArrayNMut<const D: usize>: ArrayN<D> {
+	/// MUST be the same pointer as [ArrayN::as_ptr] but mutable
+	fn as_mut_ptr(&mut self) -> *mut Self::Element;
+}
+/// Allow to instantiate an array and thus any tensor based on it, as [ArrayOwned] does for matrices
+pub trait ArrayNOwned<const D: usize>: ArrayNMut<D> {
+	/// instantiate a new array with the given shape
+	fn empty(shape: [usize; D]) -> Self;
+}
+
+/// The fundamental dense tensor type, wrapping an [ArrayN] storage and adding all the tensor operations
+#[derive(Clone)]
+pub struct Tensor<A: ArrayN<D>, const D: usize> (pub A);
+
+/// tensor with dynamically allocated column-major storage
+pub type DTensor<T, const D: usize> = Tensor<DynamicN<T,D>, D>;
+/// tensor referencing an immutable borrowed memory buffer
+pub type TensorView<'t, T, const D: usize> = Tensor<ViewN<'t,T,D>, D>;
+/// tensor referencing a mutable borrowed memory buffer
+pub type TensorViewMut<'t, T, const D: usize> = Tensor<ViewMutN<'t,T,D>, D>;
+
+
+
+/// column-major dynamically allocated owned array of `D` dimensions
+#[derive(Clone, Debug)]
+pub struct DynamicN<T: Element, const D: usize> {
+	shape: [usize; D],
+	data: Vec<T>,
+}
+impl<T: Element, const D: usize>
+	ArrayN<D> for DynamicN<T,D>
+{
+	type Element = T;
+	fn shape(&self) -> [usize; D]    {self.shape}
+	fn strides(&self) -> [isize; D]  {compact_strides(self.shape)}
+	fn as_ptr(&self) -> *const T     {self.data.as_ptr()}
+}
+impl<T: Element, const D: usize>
+	ArrayNMut<D> for DynamicN<T,D>
+{
+	fn as_mut_ptr(&mut self) -> *mut T  {self.data.as_mut_ptr()}
+}
+impl<T: Element + Default, const D: usize>
+	ArrayNOwned<D> for DynamicN<T,D>
+{
+	fn empty(shape: [usize; D]) -> Self {
+		Self {
+			shape,
+			data: vec![T::default(); shape.iter().product()],
+		}
+	}
+}
+impl<T: Element, const D: usize>
+	Tensor<DynamicN<T,D>, D>
+{
+	/// tensor with the given shape, taking its elements in column-major order from the given vector, or `None` if the vector has not exactly the right length
+	pub fn try_from_vec(shape: [usize; D], src: Vec<T>) -> Option<Self> {
+		if src.len() != shape.iter().product::<usize>()  {return None}
+		Some(Tensor(DynamicN {shape, data: src}))
+	}
+	/// tensor with every element set by the given closure
+	pub fn field<F>(shape: [usize; D], mut field: F) -> Self
+	where F: FnMut([usize; D]) -> T
+	{
+		let mut data = Vec::with_capacity(shape.iter().product());
+		for_each_index(shape, |index|  data.push(field(index)));
+		Tensor(DynamicN {shape, data})
+	}
+	/// retreive the column-major elements
+	pub fn into_vec(self) -> Vec<T>  {self.0.data}
+}
+impl<T: Scalar, const D: usize>
+	Tensor<DynamicN<T,D>, D>
+{
+	pub fn zeros(shape: [usize; D]) -> Self           {Self::field(shape, |_| T::zero())}
+	pub fn ones(shape: [usize; D]) -> Self            {Self::field(shape, |_| T::one())}
+	pub fn full(shape: [usize; D], value: T) -> Self  {Self::field(shape, |_| value.clone())}
+}
+
+
+/// array of `D` dimensions referencing an immutable borrowed memory buffer
+#[derive(Copy, Clone, Debug)]
+pub struct ViewN<'t, T: Element, const D: usize> {
+	pub(crate) shape: [usize; D],
+	pub(crate) strides: [isize; D],
+	pub(crate) data: *const T,
+	pub(crate) lifetime: PhantomData<&'t T>,
+}
+impl<T: Element, const D: usize>
+	ArrayN<D> for ViewN<'_,T,D>
+{
+	type Element = T;
+	fn shape(&self) -> [usize; D]    {self.shape}
+	fn strides(&self) -> [isize; D]  {self.strides}
+	fn as_ptr(&self) -> *const T     {self.data}
+}
+impl<'t, T: Element, const D: usize>
+	Tensor<ViewN<'t,T,D>, D>
+{
+	/// immutable column-major view in a slice buffer, or `None` if the slice has not exactly the right length
+	pub fn try_from_slice(shape: [usize; D], src: &'t [T]) -> Option<Self> {
+		if src.len() != shape.iter().product::<usize>()  {return None}
+		Some(Tensor(ViewN {
+			shape,
+			strides: compact_strides(shape),
+			data: src.as_ptr(),
+			lifetime: PhantomData,
+		}))
+	}
+}
+
+/// array of `D` dimensions referencing a mutable borrowed memory buffer
+#[derive(Debug)]
+pub struct ViewMutN<'t, T: Element, const D: usize> {
+	pub(crate) shape: [usize; D],
+	pub(crate) strides: [isize; D],
+	pub(crate) data: *mut T,
+	pub(crate) lifetime: PhantomData<&'t mut T>,
+}
+impl<T: Element, const D: usize>
+	ArrayN<D> for ViewMutN<'_,T,D>
+{
+	type Element = T;
+	fn shape(&self) -> [usize; D]    {self.shape}
+	fn strides(&self) -> [isize; D]  {self.strides}
+	fn as_ptr(&self) -> *const T     {self.data as _}
+}
+impl<T: Element, const D: usize>
+	ArrayNMut<D> for ViewMutN<'_,T,D>
+{
+	fn as_mut_ptr(&mut self) -> *mut T  {self.data}
+}
+impl<'t, T: Element, const D: usize>
+	Tensor<ViewMutN<'t,T,D>, D>
+{
+	/// mutable column-major view in a slice buffer, or `None` if the slice has not exactly the right length
+	pub fn try_from_slice(shape: [usize; D], src: &'t mut [T]) -> Option<Self> {
+		if src.len() != shape.iter().product::<usize>()  {return None}
+		Some(Tensor(ViewMutN {
+			shape,
+			strides: compact_strides(shape),
+			data: src.as_mut_ptr(),
+			lifetime: PhantomData,
+		}))
+	}
+}
+
+
+/// a matrix is a tensor of 2 dimensions
+impl<A: Array>
+	ArrayN<2> for Matrix<A>
+{
+	type Element = A::Element;
+	fn shape(&self) -> [usize; 2]    {Matrix::shape(self)}
+	fn strides(&self) -> [isize; 2]  {Matrix::strides(self)}
+	fn as_ptr(&self) -> *const A::Element  {Matrix::as_ptr(self)}
+}
+impl<A: ArrayMut>
+	ArrayNMut<2> for Matrix<A>
+{
+	fn as_mut_ptr(&mut self) -> *mut A::Element  {Matrix::as_mut_ptr(self)}
+}
+impl<A: Array> Matrix<A> {
+	/// view on this matrix as a tensor of 2 dimensions
+	pub fn tensor(&self) -> Tensor<ViewN<'_, A::Element, 2>, 2> {
+		Tensor(ViewN {
+			shape: self.shape(),
+			strides: self.strides(),
+			data: self.as_ptr(),
+			lifetime: PhantomData,
+		})
+	}
+}
+impl<A: ArrayMut> Matrix<A> {
+	/// same as [Self::tensor] but mutable
+	pub fn tensor_mut(&mut self) -> Tensor<ViewMutN<'_, A::Element, 2>, 2> {
+		Tensor(ViewMutN {
+			shape: self.shape(),
+			strides: self.strides(),
+			data: self.as_mut_ptr(),
+			lifetime: PhantomData,
+		})
+	}
+}
+impl<A: ArrayN<2>> Tensor<A, 2> {
+	/// view on this tensor of 2 dimensions as a matrix
+	pub fn matrix(&self) -> Matrix<View<'_, A::Element>> {
+		let [rows, columns] = self.shape();
+		let strides = self.strides();
+		Matrix(View {
+			shape: (Dyn(rows), Dyn(columns)),
+			strides: (strides[0], strides[1]),
+			data: self.as_ptr(),
+			lifetime: PhantomData,
+		})
+	}
+}
+impl<A: ArrayNMut<2>> Tensor<A, 2> {
+	/// same as [Self::matrix] but mutable
+	pub fn matrix_mut(&mut self) -> Matrix<ViewMut<'_, A::Element>> {
+		let [rows, columns] = self.shape();
+		let strides = self.strides();
+		Matrix(ViewMut {
+			shape: (Dyn(rows), Dyn(columns)),
+			strides: (strides[0], strides[1]),
+			data: self.as_mut_ptr(),
+			lifetime: PhantomData,
+		})
+	}
+}
+
+
+
+impl<A: ArrayN<D>, const D: usize>
+	Index<[usize; D]> for Tensor<A,D>
+{
+	type Output = A::Element;
+	fn index(&self, index: [usize; D]) -> &A::Element {
+		assert!(zip(index, self.shape()).all(|(i,l)|  i<l));
+		// safety: it is the responsibility of the array trait implementor to ensure any acces to values at these strides can be read
+		unsafe { &* self.as_ptr().offset(offset(index, self.strides())) }
+	}
+}
+impl<A: ArrayNMut<D>, const D: usize>
+	IndexMut<[usize; D]> for Tensor<A,D>
+{
+	fn index_mut(&mut self, index: [usize; D]) -> &mut A::Element {
+		assert!(zip(index, self.shape()).all(|(i,l)|  i<l));
+		// safety: same as for Index, and we own the array so nobody else can have this mutable ref
+		unsafe { &mut* self.as_mut_ptr().offset(offset(index, self.strides())) }
+	}
+}
+
+impl<A: ArrayN<D>, const D: usize> Tensor<A,D> {
+	/// underlying array
+	pub fn array(&self) -> &A  {&self.0}
+	/// tensor size in each dimension
+	pub fn shape(&self) -> [usize; D]  {self.0.shape()}
+	/// memory step between tensor elements in each dimension
+	pub fn strides(&self) -> [isize; D]  {self.0.strides()}
+	/// pointer to element `[0, ..., 0]`
+	pub fn as_ptr(&self) -> *const A::Element  {self.0.as_ptr()}
+	/// number of dimensions
+	pub fn ndim(&self) -> usize  {D}
+	/// number of elements in the tensor
+	pub fn size(&self) -> usize  {self.shape().iter().product()}
+	/// number of elements in memory spanned by the tensor
+	pub fn area(&self) -> usize {
+		if self.size() == 0  {return 0}
+		zip(self.shape(), self.strides()).map(|(l,s)|  (l-1)*s.unsigned_abs()).sum::<usize>() + 1
+	}
+	/// `True` if the indexed memory is contiguous, whatever the order of axes in memory
+	pub fn is_contiguous(&self) -> bool {
+		let mut axes: Vec<_> = zip(self.shape(), self.strides().map(isize::unsigned_abs))
+			.filter(|&(l,_)|  l > 1)
+			.collect();
+		axes.sort_by_key(|&(_,s)|  s);
+		let mut step = 1;
+		for (l,s) in axes {
+			if s != step  {return false}
+			step *= l;
+		}
+		true
+	}
+	/// cast as a slice in memory order if the buffer is contiguous
+	pub fn as_slice(&self) -> Option<&[A::Element]> {
+		let start = lowest(self.shape(), self.strides());
+		if self.is_contiguous() {Some(unsafe {core::slice::from_raw_parts(self.as_ptr().sub(start), self.area())})}
+		else {None}
+	}
+	/**
+		indexing without bounds checking
+
+		# Safety
+		the index must be in the tensor shape
+	*/
+	pub unsafe fn get_unchecked(&self, index: [usize; D]) -> &A::Element {
+		&* self.as_ptr().offset(offset(index, self.strides()))
+	}
+	/// element at the given index, or `None` if out of the tensor shape
+	pub fn get(&self, index: [usize; D]) -> Option<&A::Element> {
+		if zip(index, self.shape()).all(|(i,l)|  i<l)
+			{Some(unsafe {self.get_unchecked(index)})}
+		else {None}
+	}
+
+	/// create an immutable view on this tensor
+	pub fn view(&self) -> Tensor<ViewN<'_, A::Element, D>, D> {
+		Tensor(ViewN {
+			shape: self.shape(),
+			strides: self.strides(),
+			data: self.as_ptr(),
+			lifetime: PhantomData,
+		})
+	}
+	/// view on the given range of indices in each dimension
+	pub fn slice(&self, ranges: [Range<usize>; D]) -> Tensor<ViewN<'_, A::Element, D>, D> {
+		let (start, shape) = slicing(self.shape(), &ranges);
+		Tensor(ViewN {
+			shape,
+			strides: self.strides(),
+			// safety: ranges have been checked in the tensor shape
+			data: unsafe {self.as_ptr().offset(offset(start, self.strides()))},
+			lifetime: PhantomData,
+		})
+	}
+	/**
+		view on the subtensor at the given index along an axis, removing this axis
+
+		`E` must be `D-1`, this is checked at compile time. For instance selecting in a batch of matrices gives a tensor of 2 dimensions, which [Tensor::matrix] turns into a matrix
+	*/
+	pub fn select<const E: usize>(&self, axis: usize, index: usize) -> Tensor<ViewN<'_, A::Element, E>, E> {
+		const {assert!(E + 1 == D, "selection must remove exactly one dimension")};
+		assert!(index < self.shape()[axis]);
+		let strides = self.strides();
+		Tensor(ViewN {
+			shape: remove_axis(self.shape(), axis),
+			strides: remove_axis(strides, axis),
+			data: unsafe {self.as_ptr().offset(index as isize * strides[axis])},
+			lifetime: PhantomData,
+		})
+	}
+	/// view with axes reordered: axis `i` of the result is axis `axes[i]` of this tensor
+	pub fn permute_axes(&self, axes: [usize; D]) -> Tensor<ViewN<'_, A::Element, D>, D> {
+		let (shape, strides) = permutation(self.shape(), self.strides(), axes);
+		Tensor(ViewN {
+			shape,
+			strides,
+			data: self.as_ptr(),
+			lifetime: PhantomData,
+		})
+	}
+	/// view with two axes swapped
+	pub fn swap_axes(&self, a: usize, b: usize) -> Tensor<ViewN<'_, A::Element, D>, D> {
+		let mut axes = core::array::from_fn(|i| i);
+		axes.swap(a, b);
+		self.permute_axes(axes)
+	}
+	/**
+		view with a new shape and number of dimensions, keeping the column-major element order
+
+		Returns `None` if the number of elements differs or if the tensor is not contiguous in column-major order
+	*/
+	pub fn reshape<const E: usize>(&self, shape: [usize; E]) -> Option<Tensor<ViewN<'_, A::Element, E>, E>> {
+		if shape.iter().product::<usize>() != self.size() || !self.is_column_major()
+			{return None}
+		Some(Tensor(ViewN {
+			shape,
+			strides: compact_strides(shape),
+			data: self.as_ptr(),
+			lifetime: PhantomData,
+		}))
+	}
+	/// true if the elements are contiguous in column-major order
+	fn is_column_major(&self) -> bool {
+		let mut step = 1;
+		for (l,s) in zip(self.shape(), self.strides()) {
+			if l > 1 && s != step as isize  {return false}
+			step *= l;
+		}
+		true
+	}
+
+	/// apply a function over all elements in the tensor
+	pub fn map<T: Element, F>(&self, mut f: F) -> DTensor<T, D>
+	where F: FnMut(&A::Element) -> T
+	{
+		// safety: indices are in the tensor shape
+		Tensor::field(self.shape(), |index|  f(unsafe {self.get_unchecked(index)}))
+	}
+	/// copy this tensor data into a new column-major tensor
+	pub fn owned(&self) -> DTensor<A::Element, D> {
+		self.map(Clone::clone)
+	}
+}
+
+impl<A: ArrayNMut<D>, const D: usize> Tensor<A,D> {
+	/// same as [Self::as_ptr] but mutable
+	pub fn as_mut_ptr(&mut self) -> *mut A::Element  {self.0.as_mut_ptr()}
+	/// same as [Self::as_slice] but mutable
+	pub fn as_slice_mut(&mut self) -> Option<&mut [A::Element]> {
+		let start = lowest(self.shape(), self.strides());
+		if self.is_contiguous() {Some(unsafe {core::slice::from_raw_parts_mut(self.as_mut_ptr().sub(start), self.area())})}
+		else {None}
+	}
+	/**
+		same as [Self::get_unchecked] but mutable
+
+		# Safety
+		the index must be in the tensor shape
+	*/
+	pub unsafe fn get_unchecked_mut(&mut self, index: [usize; D]) -> &mut A::Element {
+		&mut* self.as_mut_ptr().offset(offset(index, self.strides()))
+	}
+	/// same as [Self::get] but mutable
+	pub fn get_mut(&mut self, index: [usize; D]) -> Option<&mut A::Element> {
+		if zip(index, self.shape()).all(|(i,l)|  i<l)
+			{Some(unsafe {self.get_unchecked_mut(index)})}
+		else {None}
+	}
+
+	/// same as [Self::view] but mutable
+	pub fn view_mut(&mut self) -> Tensor<ViewMutN<'_, A::Element, D>, D> {
+		Tensor(ViewMutN {
+			shape: self.shape(),
+			strides: self.strides(),
+			data: self.as_mut_ptr(),
+			lifetime: PhantomData,
+		})
+	}
+	/// same as [Self::slice] but mutable
+	pub fn slice_mut(&mut self, ranges: [Range<usize>; D]) -> Tensor<ViewMutN<'_, A::Element, D>, D> {
+		let (start, shape) = slicing(self.shape(), &ranges);
+		let strides = self.strides();
+		Tensor(ViewMutN {
+			shape,
+			strides,
+			// safety: ranges have been checked in the tensor shape
+			data: unsafe {self.as_mut_ptr().offset(offset(start, strides))},
+			lifetime: PhantomData,
+		})
+	}
+	/// same as [Self::select] but mutable
+	pub fn select_mut<const E: usize>(&mut self, axis: usize, index: usize) -> Tensor<ViewMutN<'_, A::Element, E>, E> {
+		const {assert!(E + 1 == D, "selection must remove exactly one dimension")};
+		assert!(index < self.shape()[axis]);
+		let strides = self.strides();
+		Tensor(ViewMutN {
+			shape: remove_axis(self.shape(), axis),
+			strides: remove_axis(strides, axis),
+			data: unsafe {self.as_mut_ptr().offset(index as isize * strides[axis])},
+			lifetime: PhantomData,
+		})
+	}
+	/// same as [Self::permute_axes] but mutable
+	pub fn permute_axes_mut(&mut self, axes: [usize; D]) -> Tensor<ViewMutN<'_, A::Element, D>, D> {
+		let (shape, strides) = permutation(self.shape(), self.strides(), axes);
+		Tensor(ViewMutN {
+			shape,
+			strides,
+			data: self.as_mut_ptr(),
+			lifetime: PhantomData,
+		})
+	}
+	/// same as [Self::reshape] but mutable
+	pub fn reshape_mut<const E: usize>(&mut self, shape: [usize; E]) -> Option<Tensor<ViewMutN<'_, A::Element, E>, E>> {
+		if shape.iter().product::<usize>() != self.size() || !self.is_column_major()
+			{return None}
+		Some(Tensor(ViewMutN {
+			shape,
+			strides: compact_strides(shape),
+			data: self.as_mut_ptr(),
+			lifetime: PhantomData,
+		}))
+	}
+
+	/// set every element in the tensor using the given closure
+	pub fn set_field<F>(&mut self, mut field: F) -> &mut Self
+	where F: FnMut([usize; D]) -> A::Element
+	{
+		self.update_field(|index, value|  *value = field(index))
+	}
+	/// set every element in the tensor to the given value
+	pub fn set_full(&mut self, value: A::Element) -> &mut Self {
+		if let Some(slice) = self.as_slice_mut() {
+			slice.fill(value);
+			return self
+		}
+		self.set_field(|_| value.clone())
+	}
+	/// call the given closure on every element in the tensor with its index, in column-major order
+	pub(crate) fn update_field<F>(&mut self, mut field: F) -> &mut Self
+	where F: FnMut([usize; D], &mut A::Element)
+	{
+		let strides = self.strides();
+		let ptr = self.as_mut_ptr();
+		// safety: it is the responsibility of the array trait implementor to ensure any acces to values at these strides can be written, and indices stay in the tensor shape
+		for_each_index(self.shape(), |index|  field(index, unsafe {&mut *ptr.offset(offset(index, strides))}));
+		self
+	}
+}
+
+impl<A: ArrayN<D>, const D: usize> Tensor<A,D>
+where A::Element: Scalar
+{
+	/**
+		contraction of this tensor with an other one over pairs of axes `[axis of self, axis of other]`, like numpy's `tensordot`
+
+		The result has the remaining axes of `self` followed by the remaining axes of `other`, so `F` must be `D + E - 2 K`, this is checked at compile time.
+		A matrix product is the contraction of two tensors of 2 dimensions over `[[1, 0]]`
+	*/
+	pub fn tensordot<B, const E: usize, const K: usize, const F: usize>(&self, other: &Tensor<B,E>, axes: [[usize; 2]; K]) -> DTensor<A::Element, F>
+	where B: ArrayN<E, Element=A::Element>
+	{
+		const {assert!(F + 2*K == D + E, "output dimensions must be the remaining dimensions of both operands")};
+		let (left, right) = (self.shape(), other.shape());
+		let mut contracted = [0; K];
+		for (k, [l, r]) in axes.into_iter().enumerate() {
+			assert_eq!(left[l], right[r], "contracted axes must have the same size");
+			assert!(axes[.. k].iter().all(|a|  a[0] != l && a[1] != r), "axes can only be contracted once");
+			contracted[k] = left[l];
+		}
+		let free_left: Vec<usize> = (0 .. D).filter(|&i|  axes.iter().all(|a|  a[0] != i)).collect();
+		let free_right: Vec<usize> = (0 .. E).filter(|&i|  axes.iter().all(|a|  a[1] != i)).collect();
+		let shape = core::array::from_fn(|i|
+			if i < free_left.len() {left[free_left[i]]}
+			else {right[free_right[i - free_left.len()]]});
+
+		Tensor::field(shape, |index| {
+			let mut l = [0; D];
+			let mut r = [0; E];
+			for (i, &axis) in free_left.iter().enumerate()  {l[axis] = index[i]}
+			for (i, &axis) in free_right.iter().enumerate()  {r[axis] = index[free_left.len() + i]}
+			let mut sum = A::Element::zero();
+			for_each_index(contracted, |k| {
+				for (&k, [a, b]) in zip(&k, axes) {
+					l[a] = k;
+					r[b] = k;
+				}
+				// safety: all indices are in the operands shape
+				sum = sum.clone() + unsafe {self.get_unchecked(l).clone() * other.get_unchecked(r).clone()};
+			});
+			sum
+		})
+	}
+}
+
+impl<A: ArrayN<D>, const D: usize>
+	fmt::Debug for Tensor<A,D>
+{
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		write!(f, "<Tensor at {:?} shape:{:?} strides:{:?}>",
+			self.as_ptr(), self.shape(), self.strides())
+	}
+}
+
+
+
+macro_rules! elementwise_binop {
+	($trait:ident, $method:ident, $traitto:ident, $methodto:ident, $traitassign:ident, $methodassign:ident) => {
+		impl<L, R, const D: usize>
+			$trait<&Tensor<R,D>> for &Tensor<L,D>
+		where
+			L: ArrayN<D>,
+			R: ArrayN<D, Element=L::Element>,
+			L::Element: Scalar,
+		{
+			type Output = DTensor<L::Element, D>;
+			/// elementwise operation
+			fn $method(self, right: &Tensor<R,D>) -> Self::Output {
+				assert_eq!(self.shape(), right.shape());
+				// safety: shapes have been checked above
+				Tensor::field(self.shape(), |i|  unsafe {self.get_unchecked(i).clone().$method(right.get_unchecked(i).clone())})
+			}
+		}
+		impl<L, R, O, const D: usize>
+			$traitto<Tensor<R,D>, Tensor<O,D>> for Tensor<L,D>
+		where
+			L: ArrayN<D, Element=O::Element>,
+			R: ArrayN<D, Element=O::Element>,
+			O: ArrayNMut<D>,
+			O::Element: Scalar,
+		{
+			/// elementwise operation without dynamic allocation
+			fn $methodto<'o>(&self, right: &Tensor<R,D>, out: &'o mut Tensor<O,D>) -> &'o mut Tensor<O,D> {
+				assert_eq!(self.shape(), right.shape());
+				assert_eq!(self.shape(), out.shape());
+				// safety: shapes have been checked above
+				out.set_field(|i|  unsafe {self.get_unchecked(i).clone().$method(right.get_unchecked(i).clone())})
+			}
+		}
+		impl<L, R, const D: usize>
+			$traitassign<&Tensor<R,D>> for Tensor<L,D>
+		where
+			L: ArrayNMut<D>,
+			R: ArrayN<D, Element=L::Element>,
+			L::Element: Scalar,
+		{
+			/// inplace elementwise operation
+			fn $methodassign(&mut self, right: &Tensor<R,D>) {
+				assert_eq!(self.shape(), right.shape());
+				// safety: shapes have been checked above
+				self.update_field(|i, l|  *l = l.clone().$method(unsafe {right.get_unchecked(i)}.clone()));
+			}
+		}
+		impl<L, T, const D: usize>
+			$trait<T> for &Tensor<L,D>
+		where
+			L: ArrayN<D, Element=T>,
+			T: Scalar,
+		{
+			type Output = DTensor<T, D>;
+			/// scalar operation
+			fn $method(self, right: T) -> Self::Output {
+				self.map(|l|  l.clone().$method(right.clone()))
+			}
+		}
+		impl<L, T, const D: usize>
+			$traitassign<T> for Tensor<L,D>
+		where
+			L: ArrayNMut<D, Element=T>,
+			T: Scalar,
+		{
+			/// inplace scalar operation
+			fn $methodassign(&mut self, right: T) {
+				self.update_field(|_, l|  *l = l.clone().$method(right.clone()));
+			}
+		}
+	}
+}
+elementwise_binop!(Add, add, AddTo, add_to, AddAssign, add_assign);
+elementwise_binop!(Sub, sub, SubTo, sub_to, SubAssign, sub_assign);
+elementwise_binop!(Mul, mul, MulTo, mul_to, MulAssign, mul_assign);
+elementwise_binop!(Div, div, DivTo, div_to, DivAssign, div_assign);
+
+
+
+/// column-major strides of a compact buffer
+fn compact_strides<const D: usize>(shape: [usize; D]) -> [isize; D] {
+	let mut step = 1;
+	shape.map(|l|  {let stride = step; step *= l; stride as isize})
+}
+/// memory offset of an element from element `[0, ..., 0]`
+fn offset<const D: usize>(index: [usize; D], strides: [isize; D]) -> isize {
+	zip(index, strides).map(|(i,s)|  i as isize * s).sum()
+}
+/// number of elements in memory before element `[0, ..., 0]`, nonzero only with negative strides
+fn lowest<const D: usize>(shape: [usize; D], strides: [isize; D]) -> usize {
+	if shape.contains(&0)  {return 0}
+	zip(shape, strides)
+		.filter(|&(_,s)|  s < 0)
+		.map(|(l,s)|  (l-1) * s.unsigned_abs())
+		.sum()
+}
+/// call the closure for every index in the shape, in column-major order
+fn for_each_index<const D: usize>(shape: [usize; D], mut f: impl FnMut([usize; D])) {
+	if shape.contains(&0)  {return}
+	let mut index = [0; D];
+	'walk: loop {
+		f(index);
+		for axis in 0 .. D {
+			index[axis] += 1;
+			if index[axis] < shape[axis]  {continue 'walk}
+			index[axis] = 0;
+		}
+		break
+	}
+}
+/// start index and shape of a slicing, checked in the given shape
+fn slicing<const D: usize>(shape: [usize; D], ranges: &[Range<usize>; D]) -> ([usize; D], [usize; D]) {
+	for (range, &l) in zip(ranges, &shape) {
+		assert!(range.start <= range.end && range.end <= l, "slice {:?} out of dimension {}", range, l);
+	}
+	(ranges.clone().map(|r| r.start), ranges.clone().map(|r| r.end - r.start))
+}
+/// array without the element at the given axis
+fn remove_axis<T: Copy, const D: usize, const E: usize>(array: [T; D], axis: usize) -> [T; E] {
+	assert!(axis < D);
+	core::array::from_fn(|i|  if i < axis {array[i]} else {array[i+1]})
+}
+/// shape and strides with axes reordered, checking the axes are a permutation
+fn permutation<const D: usize>(shape: [usize; D], strides: [isize; D], axes: [usize; D]) -> ([usize; D], [isize; D]) {
+	let mut seen = [false; D];
+	for &axis in &axes {
+		assert!(axis < D && !seen[axis], "axes must be a permutation");
+		seen[axis] = true;
+	}
+	(axes.map(|a| shape[a]), axes.map(|a| strides[a]))
+}
+
+
+
+#[test]
+fn test_tensor() {
+	// batch of 3 matrices 2x4
+	let batch = DTensor::<f64, 3>::field([2, 4, 3], |[i,j,k]|  (i + 2*j + 8*k) as f64);
+	assert_eq!(batch.as_slice().unwrap(), (0 .. 24).map(|v| v as f64).collect::<Vec<_>>());
+	assert_eq!(batch[[1,2,1]], 13.);
+	assert_eq!(batch.get([2,0,0]), None);
+
+	// matrices in the batch
+	let matrix = batch.select::<2>(2, 1);
+	assert_eq!(matrix.matrix().as_slice(), Some(&[8.,9.,10.,11.,12.,13.,14.,15.][..]));
+	let dense = DMatrix::<f64>::field([4, 2], |[i,j]|  (i + 4*j) as f64);
+	let product = &matrix.matrix() * &dense;
+	let contracted: DTensor<f64,2> = matrix.tensordot(&dense.tensor(), [[1, 0]]);
+	assert_eq!(contracted.matrix().as_slice(), product.as_slice());
+	assert_eq!(Tensor(dense.clone()).shape(), [4, 2]);
+
+	// slicing and axes
+	let slice = batch.slice([0 .. 2, 1 .. 3, 2 .. 3]);
+	assert_eq!(slice.shape(), [2, 2, 1]);
+	assert_eq!(slice.owned().into_vec(), [18., 19., 20., 21.]);
+	let permuted = batch.permute_axes([2, 0, 1]);
+	assert_eq!(permuted.shape(), [3, 2, 4]);
+	assert_eq!(permuted[[1,1,2]], batch[[1,2,1]]);
+	assert!(permuted.is_contiguous() && permuted.reshape([24]).is_none());
+	assert!(slice.is_contiguous() && !batch.slice([0 .. 1, 0 .. 4, 0 .. 3]).is_contiguous());
+	let flat = batch.reshape::<2>([8, 3]).unwrap();
+	assert_eq!(flat[[5,2]], 21.);
+	assert_eq!(batch.swap_axes(0, 1).owned().reshape::<1>([24]).unwrap()[[1]], 2.);
+
+	// elementwise operations
+	let mut sum = &batch + &batch;
+	sum -= &batch;
+	sum *= 2.;
+	assert_eq!(sum.as_slice(), (&batch * 2.).as_slice());
+	let mut inplace = batch.clone();
+	inplace.slice_mut([0 .. 1, 0 .. 4, 0 .. 3]).set_full(0.);
+	assert_eq!(inplace[[0,3,2]], 0.);
+	assert_eq!(inplace[[1,3,2]], batch[[1,3,2]]);
+
+	// contraction over several axes
+	let a = DTensor::<f64, 3>::field([2, 3, 4], |[i,j,k]|  (i + j*k) as f64);
+	let b = DTensor::<f64, 2>::field([4, 3], |[k,j]|  (k + j) as f64);
+	let c: DTensor<f64,1> = a.tensordot(&b, [[1, 1], [2, 0]]);
+	for i in 0 .. 2 {
+		let expected: f64 = (0 .. 3).flat_map(|j|  (0 .. 4).map(move |k|  ((i + j*k) * (k + j)) as f64)).sum();
+		assert_eq!(c[[i]], expected);
+	}
+	let outer: DTensor<f64,5> = a.tensordot(&b, []);
+	assert_eq!(outer.shape(), [2, 3, 4, 4, 3]);
+	assert_eq!(outer[[1,2,3,1,2]], a[[1,2,3]] * b[[1,2]]);
+}
@@ -0,0 +1,38 @@
+/*!
+	Reading and writing matrices in common text formats
+
+	Readers produce [DMatrix](crate::DMatrix) and report the line and column of any unparsable text. Writers accept any matrix, including transposed or strided views
+*/
+
+use std::{fmt, io};
+
+pub mod market;
+pub mod csv;
+
+
+/// error while reading a text format
+#[derive(Debug)]
+pub enum ParseError {
+	/// the underlying reader failed
+	Io(io::Error),
+	/// the text could not be parsed at the given position (1-based line and column)
+	Syntax {line: usize, column: usize, reason: String},
+}
+impl fmt::Display for ParseError {
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		match self {
+			Self::Io(error) => write!(f, "{}", error),
+			Self::Syntax {line, column, reason} => write!(f, "line {}, column {}: {}", line, column, reason),
+		}
+	}
+}
+impl std::error::Error for ParseError {}
+impl From<io::Error> for ParseError {
+	fn from(error: io::Error) -> Self  {Self::Io(error)}
+}
+
+/// whitespace separated words in a line, with their 1-based column
+fn words(line: &str) -> impl Iterator<Item=(usize, &str)> {
+	line.split_whitespace()
+		.map(move |word|  (word.as_ptr() as usize - line.as_ptr() as usize + 1, word))
+}
@@ -0,0 +1,187 @@
+/*!
+	Delimiter separated values, like CSV or TSV files exported by spreadsheets
+
+	Each line of the file is a row of the matrix. An optional first line can hold column labels. Fields can be quoted with `"`, in which case a `""` in the field stands for a quote character.
+*/
+
+use crate::prelude::*;
+use crate::matrix::*;
+use crate::matrices::*;
+use super::ParseError;
+
+use core::{fmt::Display, str::FromStr};
+use std::io::{BufRead, Write, self};
+
+
+/// description of the text layout
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub struct Dialect {
+	/// character separating fields in a line
+	pub delimiter: char,
+	/// whether the first line holds column labels
+	pub header: bool,
+}
+impl Dialect {
+	/// comma separated values, without header
+	pub const CSV: Self = Self {delimiter: ',', header: false};
+	/// tab separated values, without header
+	pub const TSV: Self = Self {delimiter: '\t', header: false};
+
+	/// same dialect with a different delimiter
+	pub fn delimiter(self, delimiter: char) -> Self  {Self {delimiter, ..self}}
+	/// same dialect with or without header line
+	pub fn header(self, header: bool) -> Self  {Self {header, ..self}}
+}
+impl Default for Dialect {
+	fn default() -> Self  {Self::CSV}
+}
+
+/// split a line into its unquoted fields, with their 1-based column
+fn fields(line: &str, delimiter: char, number: usize) -> Result<Vec<(usize, String)>, ParseError> {
+	let mut fields = Vec::new();
+	let mut chars = line.char_indices().peekable();
+	loop {
+		let start = chars.peek().map(|&(position, _)| position).unwrap_or(line.len());
+		let mut field = String::new();
+		if let Some((_, '"')) = chars.peek() {
+			chars.next();
+			loop {
+				match chars.next() {
+					Some((_, '"')) => match chars.peek() {
+						Some((_, '"')) => {chars.next(); field.push('"')},
+						_ => break,
+					},
+					Some((_, c)) => field.push(c),
+					None => return Err(ParseError::Syntax {line: number, column: start+1, reason: "unterminated quoted field".into()}),
+				}
+			}
+			match chars.next() {
+				Some((_, c)) if c == delimiter => {},
+				None => {fields.push((start+1, field)); break},
+				Some((position, _)) => return Err(ParseError::Syntax {line: number, column: position+1, reason: "expected delimiter after quoted field".into()}),
+			}
+		}
+		else {
+			let mut end = true;
+			for (_, c) in chars.by_ref() {
+				if c == delimiter  {end = false; break}
+				field.push(c);
+			}
+			if end  {fields.push((start+1, field)); break}
+		}
+		fields.push((start+1, field));
+	}
+	Ok(fields)
+}
+
+/// quote a field if necessary
+fn quote(field: &str, delimiter: char) -> String {
+	if field.contains([delimiter, '"', '\n', '\r'])
+		{format!("\"{}\"", field.replace('"', "\"\""))}
+	else
+		{field.to_owned()}
+}
+
+/// read a matrix and its column labels, labels are empty if the dialect has no header
+pub fn read_labeled<T, R>(mut reader: R, dialect: Dialect) -> Result<(Vec<String>, DMatrix<T>), ParseError>
+where
+	T: Element + FromStr,
+	R: BufRead,
+{
+	let mut labels = Vec::new();
+	let mut values = Vec::<T>::new();
+	let mut columns = None;
+	let mut text = String::new();
+	let mut number = 0;
+	loop {
+		text.clear();
+		number += 1;
+		if reader.read_line(&mut text)? == 0
+			{break}
+		let line = text.trim_end_matches(['\n', '\r']);
+		if line.trim().is_empty()
+			{continue}
+		let fields = fields(line, dialect.delimiter, number)?;
+		if let Some(columns) = columns {
+			if fields.len() != columns
+				{return Err(ParseError::Syntax {line: number, column: line.len()+1, reason: format!("expected {} fields, found {}", columns, fields.len())})}
+		}
+		columns = Some(fields.len());
+		if dialect.header && labels.is_empty() {
+			labels = fields.into_iter().map(|(_, field)| field).collect();
+			continue
+		}
+		for (column, field) in fields {
+			values.push(field.trim().parse().map_err(|_| ParseError::Syntax {
+				line: number, 
+				column, 
+				reason: format!("invalid number {:?}", field),
+				})?);
+		}
+	}
+	let columns = columns.unwrap_or(0);
+	let rows = values.len().checked_div(columns).unwrap_or(0);
+	Ok((labels, Matrix::from_field([rows, columns], |[i,j]|  values[i*columns + j].clone())))
+}
+
+/// read a matrix, skipping the header line if the dialect has one
+pub fn read<T, R>(reader: R, dialect: Dialect) -> Result<DMatrix<T>, ParseError>
+where
+	T: Element + FromStr,
+	R: BufRead,
+{
+	read_labeled(reader, dialect).map(|(_, matrix)| matrix)
+}
+
+/// write a matrix, with the given column labels if the dialect has a header line
+pub fn write<A, W>(matrix: &Matrix<A>, mut writer: W, dialect: Dialect, labels: &[&str]) -> io::Result<()>
+where
+	A: Array,
+	A::Element: Display,
+	W: Write,
+{
+	let mut separator = String::new();
+	separator.push(dialect.delimiter);
+	if dialect.header {
+		if labels.len() != matrix.columns()
+			{return Err(io::Error::new(io::ErrorKind::InvalidInput, "there must be one label per column"))}
+		let labels = labels.iter().map(|label| quote(label, dialect.delimiter)).collect::<Vec<_>>();
+		writeln!(writer, "{}", labels.join(&separator))?;
+	}
+	for row in matrix.row_iter() {
+		let mut first = true;
+		for value in row.iter() {
+			if !first  {write!(writer, "{}", separator)?}
+			write!(writer, "{}", value)?;
+			first = false;
+		}
+		writeln!(writer)?;
+	}
+	Ok(())
+}
+
+
+
+#[test]
+fn test_csv() {
+	let text = "x,\"y, in \"\"mm\"\"\",z\n1, 2,3\n\n4,5,6.5\n";
+	let (labels, m) = read_labeled::<f32, _>(text.as_bytes(), Dialect::CSV.header(true)).unwrap();
+	assert_eq!(labels, ["x", "y, in \"mm\"", "z"]);
+	assert_eq!(m.shape(), [2,3]);
+	assert_eq!(m.as_slice(), Some(&[1.,4.,  2.,5.,  3.,6.5][..]));
+
+	match read::<f32, _>("1\t2\n3\tx\n".as_bytes(), Dialect::TSV) {
+		Err(ParseError::Syntax {line: 2, column: 3, ..}) => {},
+		other => panic!("unexpected {:?}", other.map(|m| m.shape())),
+	}
+	match read::<f32, _>("1;2\n3\n".as_bytes(), Dialect::CSV.delimiter(';')) {
+		Err(ParseError::Syntax {line: 2, ..}) => {},
+		other => panic!("unexpected {:?}", other.map(|m| m.shape())),
+	}
+
+	let mut text = Vec::new();
+	write(&m.transpose(), &mut text, Dialect::TSV.header(true), &["a", "b\tc"]).unwrap();
+	assert_eq!(String::from_utf8(text.clone()).unwrap(), "a\t\"b\tc\"\n1\t4\n2\t5\n3\t6.5\n");
+	let back = read::<f32, _>(text.as_slice(), Dialect::TSV.header(true)).unwrap();
+	assert_eq!(back.as_slice(), m.transpose().owned().as_slice());
+}
@@ -0,0 +1,410 @@
+/*!
+	[Matrix Market](https://math.nist.gov/MatrixMarket/formats.html) exchange format, as used by the [SuiteSparse](https://sparse.tamu.edu) collection
+
+	Both `coordinate` and `array` formats are supported, with `real`, `integer` or `pattern` fields and `general`, `symmetric` or `skew-symmetric` symmetries. Complex matrices are not supported.
+*/
+
+use crate::prelude::*;
+use crate::matrix::*;
+use crate::matrices::*;
+use crate::sparse::Coo;
+use super::{ParseError, words};
+
+use core::{fmt::Display, str::FromStr};
+use num_traits::Zero;
+use std::io::{BufRead, Write, self};
+
+
+/// matrix element that can be written in the matrix market format
+pub trait MarketElement: Scalar + FromStr + Display {
+	/// field name in the matrix market header
+	const FIELD: &'static str;
+}
+impl MarketElement for f32 {const FIELD: &'static str = "real";}
+impl MarketElement for f64 {const FIELD: &'static str = "real";}
+macro_rules! impl_integer {
+	($($t:ty),*) => { $(impl MarketElement for $t {const FIELD: &'static str = "integer";})* }
+}
+impl_integer!(u8, u16, u32, u64, u128, usize, i8, i16, i32, i64, i128, isize);
+
+
+/// storage format of the matrix market file
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub enum Format {
+	/// only nonzero entries are listed with their indices
+	Coordinate,
+	/// all entries are listed in column-major order
+	Array,
+}
+/// element kind of the matrix market file
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub enum Field {
+	Real,
+	Integer,
+	/// entries have no value, their presence means one
+	Pattern,
+}
+/// symmetry of the matrix market file, only the lower triangle is stored when it is not general
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub enum Symmetry {
+	General,
+	Symmetric,
+	SkewSymmetric,
+}
+/// content of a matrix market header and size line
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub struct Header {
+	pub format: Format,
+	pub field: Field,
+	pub symmetry: Symmetry,
+	pub shape: [usize; 2],
+	/// number of entries listed in the file
+	pub entries: usize,
+}
+
+/// read all lines, skipping comments and blank lines
+struct Lines<R> {
+	reader: R,
+	line: usize,
+	text: String,
+}
+impl<R: BufRead> Lines<R> {
+	/// move to the next meaningful line, return `false` at the end of the file
+	fn next(&mut self) -> Result<bool, ParseError> {
+		loop {
+			self.text.clear();
+			self.line += 1;
+			if self.reader.read_line(&mut self.text)? == 0
+				{return Ok(false)}
+			let text = self.text.trim();
+			if !text.is_empty() && !text.starts_with('%')
+				{return Ok(true)}
+		}
+	}
+	fn error<T>(&self, column: usize, reason: impl Into<String>) -> Result<T, ParseError> {
+		Err(ParseError::Syntax {line: self.line, column, reason: reason.into()})
+	}
+}
+
+/// read the header and size line, leaving the lines iterator on the first entry
+fn header<R: BufRead>(lines: &mut Lines<R>) -> Result<Header, ParseError> {
+	lines.line += 1;
+	lines.reader.read_line(&mut lines.text)?;
+	let mut banner = words(&lines.text);
+	match banner.next() {
+		Some((_, "%%MatrixMarket")) => {},
+		_ => return lines.error(1, "expected %%MatrixMarket banner"),
+	}
+	match banner.next() {
+		Some((_, object)) if object.eq_ignore_ascii_case("matrix") => {},
+		Some((column, _)) => return lines.error(column, "only matrix objects are supported"),
+		None => return lines.error(lines.text.len(), "missing object"),
+	}
+	let format = match banner.next() {
+		Some((_, word)) if word.eq_ignore_ascii_case("coordinate") => Format::Coordinate,
+		Some((_, word)) if word.eq_ignore_ascii_case("array") => Format::Array,
+		Some((column, _)) => return lines.error(column, "unknown format"),
+		None => return lines.error(lines.text.len(), "missing format"),
+	};
+	let field = match banner.next() {
+		Some((_, word)) if word.eq_ignore_ascii_case("real") => Field::Real,
+		Some((_, word)) if word.eq_ignore_ascii_case("double") => Field::Real,
+		Some((_, word)) if word.eq_ignore_ascii_case("integer") => Field::Integer,
+		Some((_, word)) if word.eq_ignore_ascii_case("pattern") => Field::Pattern,
+		Some((column, _)) => return lines.error(column, "unsupported field"),
+		None => return lines.error(lines.text.len(), "missing field"),
+	};
+	let symmetry = match banner.next() {
+		Some((_, word)) if word.eq_ignore_ascii_case("general") => Symmetry::General,
+		Some((_, word)) if word.eq_ignore_ascii_case("symmetric") => Symmetry::Symmetric,
+		Some((_, word)) if word.eq_ignore_ascii_case("skew-symmetric") => Symmetry::SkewSymmetric,
+		Some((column, _)) => return lines.error(column, "unsupported symmetry"),
+		None => return lines.error(lines.text.len(), "missing symmetry"),
+	};
+	drop(banner);
+	if format == Format::Array && field == Field::Pattern
+		{return lines.error(1, "pattern field is only allowed for coordinate format")}
+
+	if !lines.next()?
+		{return lines.error(1, "missing size line")}
+	let mut numbers = [0usize; 3];
+	let expected = if format == Format::Coordinate {3} else {2};
+	let mut size = words(&lines.text);
+	for number in &mut numbers[.. expected] {
+		let Some((column, word)) = size.next()
+			else {return lines.error(lines.text.trim_end().len()+1, "missing size")};
+		*number = match word.parse() {
+			Ok(number) => number,
+			Err(_) => return lines.error(column, format!("invalid size {:?}", word)),
+		};
+	}
+	let shape = [numbers[0], numbers[1]];
+	if symmetry != Symmetry::General && shape[0] != shape[1]
+		{return lines.error(1, "symmetric matrices must be square")}
+	// the size line is untrusted, so it is checked before anything is allocated from it
+	let entries = match (format, symmetry) {
+		(Format::Coordinate, _) => Some(numbers[2]),
+		(Format::Array, Symmetry::General) => shape[0].checked_mul(shape[1]),
+		(Format::Array, Symmetry::Symmetric) => shape[0].checked_add(1).and_then(|next|  shape[0].checked_mul(next)).map(|n|  n/2),
+		(Format::Array, Symmetry::SkewSymmetric) => shape[0].checked_mul(shape[0].saturating_sub(1)).map(|n|  n/2),
+	};
+	let Some(entries) = entries
+		else {return lines.error(1, "matrix size overflows")};
+	Ok(Header {format, field, symmetry, shape, entries})
+}
+
+/**
+	read the header and call `entry(row, column, value)` for every entry in the file, with 0-based indices.
+
+	Symmetric entries are not mirrored, and entries above the diagonal of symmetric files are rejected as the upper triangle is implied by the lower one
+*/
+pub fn read_entries<T, R, F>(reader: R, entry: F) -> Result<Header, ParseError>
+where
+	T: Scalar + FromStr,
+	R: BufRead,
+	F: FnMut(usize, usize, T),
+{
+	let mut lines = Lines {reader, line: 0, text: String::new()};
+	let header = header(&mut lines)?;
+	entries(&mut lines, &header, entry)?;
+	Ok(header)
+}
+
+/// call `entry(row, column, value)` for every entry following the header
+fn entries<T, R, F>(lines: &mut Lines<R>, header: &Header, mut entry: F) -> Result<(), ParseError>
+where
+	T: Scalar + FromStr,
+	R: BufRead,
+	F: FnMut(usize, usize, T),
+{
+	let [rows, columns] = header.shape;
+	// position of the next array entry
+	let [mut i, mut j] = [0, 0];
+	if header.symmetry == Symmetry::SkewSymmetric  {i = 1}
+
+	for _ in 0 .. header.entries {
+		if !lines.next()?
+			{return lines.error(1, format!("expected {} entries", header.entries))}
+		let mut text = words(&lines.text);
+		let end = lines.text.trim_end().len() + 1;
+		if header.format == Format::Coordinate {
+			let mut index = [0; 2];
+			let mut start = end;
+			for (k, shape) in [rows, columns].into_iter().enumerate() {
+				let Some((column, word)) = text.next()
+					else {return lines.error(end, "missing index")};
+				if k == 0  {start = column}
+				index[k] = match word.parse::<usize>() {
+					Ok(position) if position >= 1 && position <= shape => position - 1,
+					_ => return lines.error(column, format!("invalid index {:?}", word)),
+				};
+			}
+			[i, j] = index;
+			// skew-symmetric matrices have a zero diagonal, so it is not stored either
+			match header.symmetry {
+				Symmetry::Symmetric if i < j => return lines.error(start, "symmetric entries must be in the lower triangle"),
+				Symmetry::SkewSymmetric if i <= j => return lines.error(start, "skew-symmetric entries must be strictly below the diagonal"),
+				_ => {},
+			}
+		}
+		let value = if header.field == Field::Pattern {T::one()} 
+			else {
+				let Some((column, word)) = text.next()
+					else {return lines.error(end, "missing value")};
+				match word.parse() {
+					Ok(value) => value,
+					Err(_) => return lines.error(column, format!("invalid value {:?}", word)),
+				}
+			};
+		if let Some((column, _)) = text.next()
+			{return lines.error(column, "unexpected text after entry")}
+		entry(i, j, value);
+
+		if header.format == Format::Array {
+			i += 1;
+			if i >= rows {
+				j += 1;
+				// only the lower triangle is stored for symmetric matrices
+				i = match header.symmetry {
+					Symmetry::General => 0,
+					Symmetry::Symmetric => j,
+					Symmetry::SkewSymmetric => j+1,
+				};
+			}
+		}
+	}
+	Ok(())
+}
+
+/// read a dense matrix from a matrix market file, symmetric entries are mirrored
+pub fn read<T, R>(reader: R) -> Result<DMatrix<T>, ParseError>
+where
+	T: Scalar + FromStr,
+	R: BufRead,
+{
+	let mut lines = Lines {reader, line: 0, text: String::new()};
+	let header = header(&mut lines)?;
+	// coordinate files can declare a shape too big for a dense matrix
+	if header.shape[0].checked_mul(header.shape[1]).is_none_or(|len|  len > isize::MAX as usize / size_of::<T>().max(1))
+		{return lines.error(1, "matrix size overflows")}
+	let mut values = Vec::new();
+	entries(&mut lines, &header, |i, j, value: T|  values.push((i, j, value)))?;
+	let mut matrix = DMatrix::<T>::zeros(header.shape);
+	for (i, j, value) in values {
+		match header.symmetry {
+			Symmetry::General => {},
+			Symmetry::Symmetric => matrix[[j,i]] = value.clone(),
+			Symmetry::SkewSymmetric => matrix[[j,i]] = T::zero() - value.clone(),
+		}
+		matrix[[i,j]] = value;
+	}
+	Ok(matrix)
+}
+
+/// read a sparse matrix from a matrix market file, symmetric entries are mirrored
+pub fn read_sparse<T, R>(reader: R) -> Result<Coo<T>, ParseError>
+where
+	T: Scalar + FromStr,
+	R: BufRead,
+{
+	let mut entries = Vec::new();
+	let header = read_entries(reader, |i, j, value: T|  entries.push((i, j, value)))?;
+	let mut matrix = Coo::with_capacity(header.shape, entries.len());
+	for (i, j, value) in entries {
+		if i != j {
+			match header.symmetry {
+				Symmetry::General => {},
+				Symmetry::Symmetric => {matrix.push([j,i], value.clone());},
+				Symmetry::SkewSymmetric => {matrix.push([j,i], T::zero() - value.clone());},
+			}
+		}
+		matrix.push([i,j], value);
+	}
+	Ok(matrix)
+}
+
+/// write a matrix in the `array` format, listing all its elements
+pub fn write<A, W>(matrix: &Matrix<A>, mut writer: W) -> io::Result<()>
+where
+	A: Array,
+	A::Element: MarketElement,
+	W: Write,
+{
+	let [rows, columns] = matrix.shape();
+	writeln!(writer, "%%MatrixMarket matrix array {} general", A::Element::FIELD)?;
+	writeln!(writer, "{} {}", rows, columns)?;
+	for value in matrix.iter() {
+		writeln!(writer, "{}", value)?;
+	}
+	Ok(())
+}
+
+/// write a matrix in the `coordinate` format, listing only its nonzero elements
+pub fn write_coordinate<A, W>(matrix: &Matrix<A>, mut writer: W) -> io::Result<()>
+where
+	A: Array,
+	A::Element: MarketElement,
+	W: Write,
+{
+	let [rows, columns] = matrix.shape();
+	let nonzeros = matrix.iter().filter(|value| !value.is_zero()).count();
+	writeln!(writer, "%%MatrixMarket matrix coordinate {} general", A::Element::FIELD)?;
+	writeln!(writer, "{} {} {}", rows, columns, nonzeros)?;
+	for ([i,j], value) in matrix.iter().index() {
+		if !value.is_zero() {
+			writeln!(writer, "{} {} {}", i+1, j+1, value)?;
+		}
+	}
+	Ok(())
+}
+
+/// write a symmetric matrix in the `coordinate` format, listing only the nonzero elements of its lower triangle
+pub fn write_symmetric<A, W>(matrix: &Matrix<A>, mut writer: W) -> io::Result<()>
+where
+	A: Array,
+	A::Element: MarketElement,
+	W: Write,
+{
+	let [rows, columns] = matrix.shape();
+	if rows != columns
+		{return Err(io::Error::new(io::ErrorKind::InvalidInput, "symmetric matrices must be square"))}
+	let lower = || matrix.iter().index().filter(|&([i,j], value)|  i >= j && !value.is_zero());
+	writeln!(writer, "%%MatrixMarket matrix coordinate {} symmetric", A::Element::FIELD)?;
+	writeln!(writer, "{} {} {}", rows, columns, lower().count())?;
+	for ([i,j], value) in lower() {
+		writeln!(writer, "{} {} {}", i+1, j+1, value)?;
+	}
+	Ok(())
+}
+
+
+#[test]
+fn test_market() {
+	let text = "%%MatrixMarket matrix coordinate real symmetric
+% comment
+3 3 4
+1 1 2.5
+2 1 -1
+3 2 4e1
+
+3 3 1.0
+";
+	let m = read::<f64, _>(text.as_bytes()).unwrap();
+	assert_eq!(m.as_slice(), Some(&[2.5,-1.,0.,  -1.,0.,40.,  0.,40.,1.][..]));
+
+	let text = "%%MatrixMarket matrix coordinate pattern general\n2 3 2\n1 3\n2 1\n";
+	let m = read::<i32, _>(text.as_bytes()).unwrap();
+	assert_eq!(m.as_slice(), Some(&[0,1,  0,0,  1,0][..]));
+
+	let text = "%%MatrixMarket matrix array integer skew-symmetric\n3 3\n1\n2\n3\n";
+	let m = read::<i32, _>(text.as_bytes()).unwrap();
+	assert_eq!(m.as_slice(), Some(&[0,1,2,  -1,0,3,  -2,-3,0][..]));
+	assert_eq!(read_sparse::<i32, _>(text.as_bytes()).unwrap().to_dense().as_slice(), m.as_slice());
+
+	let text = "%%MatrixMarket matrix coordinate real general\n2 2 2\n1 1 1.0\n2 3 1.0\n";
+	match read::<f64, _>(text.as_bytes()) {
+		Err(ParseError::Syntax {line: 4, column: 3, ..}) => {},
+		other => panic!("unexpected {:?}", other.map(|m| m.shape())),
+	}
+	let text = "%%MatrixMarket matrix array real general\n2 2\n1.0\n2.x\n";
+	match read::<f64, _>(text.as_bytes()) {
+		Err(ParseError::Syntax {line: 4, column: 1, ..}) => {},
+		other => panic!("unexpected {:?}", other.map(|m| m.shape())),
+	}
+	// sizes overflowing the dense matrix are errors before anything is allocated
+	for text in [
+		"%%MatrixMarket matrix array real general\n4294967296 4294967296\n",
+		"%%MatrixMarket matrix array real symmetric\n18446744073709551615 18446744073709551615\n",
+		"%%MatrixMarket matrix coordinate real general\n4294967296 4294967296 0\n",
+		] {
+		match read::<f64, _>(text.as_bytes()) {
+			Err(ParseError::Syntax {line: 2, ..}) => {},
+			other => panic!("unexpected {:?}", other.map(|m| m.shape())),
+		}
+	}
+
+	let a = crate::SMatrix::from([[1,2,0], [0,5,6]]);
+	let mut text = Vec::new();
+	write(&a.transpose(), &mut text).unwrap();
+	assert_eq!(String::from_utf8(text.clone()).unwrap(), "%%MatrixMarket matrix array integer general\n2 3\n1\n0\n2\n5\n0\n6\n");
+	assert_eq!(read::<i32, _>(text.as_slice()).unwrap().as_slice(), a.transpose().owned().as_slice());
+	let mut text = Vec::new();
+	write_coordinate(&a, &mut text).unwrap();
+	assert_eq!(String::from_utf8(text.clone()).unwrap(), "%%MatrixMarket matrix coordinate integer general\n3 2 4\n1 1 1\n2 1 2\n2 2 5\n3 2 6\n");
+	assert_eq!(read::<i32, _>(text.as_slice()).unwrap().as_slice(), a.as_slice());
+
+	// symmetric files only store the lower triangle
+	let s = crate::SMatrix::from([[4,1,0], [1,5,2], [0,2,6]]);
+	let mut text = Vec::new();
+	write_symmetric(&s, &mut text).unwrap();
+	assert_eq!(String::from_utf8(text.clone()).unwrap(), "%%MatrixMarket matrix coordinate integer symmetric\n3 3 5\n1 1 4\n2 1 1\n2 2 5\n3 2 2\n3 3 6\n");
+	assert_eq!(read::<i32, _>(text.as_slice()).unwrap().as_slice(), s.as_slice());
+	assert!(write_symmetric(&a, &mut Vec::new()).is_err());
+	let text = "%%MatrixMarket matrix coordinate real symmetric\n2 2 2\n1 1 1.0\n1 2 3.0\n";
+	match read::<f64, _>(text.as_bytes()) {
+		Err(ParseError::Syntax {line: 4, column: 1, ..}) => {},
+		other => panic!("unexpected {:?}", other.map(|m| m.shape())),
+	}
+	let text = "%%MatrixMarket matrix coordinate integer skew-symmetric\n2 2 1\n2 2 1\n";
+	assert!(matches!(read_sparse::<i32, _>(text.as_bytes()), Err(ParseError::Syntax {line: 3, ..})));
+}
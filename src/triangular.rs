@@ -0,0 +1,364 @@
+/*!
+	Triangular matrices and triangular solves
+
+	[LowerTriangular] and [UpperTriangular] wrap a square [Matrix] of any storage and only consider one of its triangles, so the other half of the storage can hold garbage or another triangular matrix. They provide the triangular solves, products and inversion, only reading their meaningful half.
+
+	The free functions [solve_lower], [solve_upper], [solve_lower_transpose] and [solve_upper_transpose] do the same on bare matrices, and are convenient on views
+*/
+
+use crate::prelude::*;
+use crate::matrix::*;
+use crate::matrices::*;
+use crate::operators::*;
+use crate::workspace::{self, Workspace, ScratchMatrix, MulWithWorkspace, workspace_size};
+
+use core::ops::Mul;
+use num_traits::{Zero, One};
+
+
+/// whether the diagonal of a triangular matrix is stored, or made of ones not read from storage
+#[derive(Copy, Clone, Debug, Eq, PartialEq, Default)]
+pub enum Diagonal {
+	#[default]
+	Stored,
+	Unit,
+}
+
+/// solve `L x = b` in place of `b`, with `L` the lower triangle of the given square matrix
+pub fn solve_lower<L, B>(lower: &Matrix<L>, diagonal: Diagonal, b: &mut Matrix<B>)
+where
+	L: Array,
+	B: ArrayMut<Element=L::Element>,
+	L::Element: Scalar,
+{
+	let size = check_system(lower, b);
+	for c in 0 .. b.columns() {
+		for i in 0 .. size {
+			// safety: shapes have been checked above
+			unsafe {
+				let mut value = b.get_unchecked([i,c]).clone();
+				for k in 0 .. i {
+					value = value - lower.get_unchecked([i,k]).clone() * b.get_unchecked([k,c]).clone();
+				}
+				if diagonal == Diagonal::Stored  {value = value / lower.get_unchecked([i,i]).clone()}
+				*b.get_unchecked_mut([i,c]) = value;
+			}
+		}
+	}
+}
+/// solve `U x = b` in place of `b`, with `U` the upper triangle of the given square matrix
+pub fn solve_upper<U, B>(upper: &Matrix<U>, diagonal: Diagonal, b: &mut Matrix<B>)
+where
+	U: Array,
+	B: ArrayMut<Element=U::Element>,
+	U::Element: Scalar,
+{
+	let size = check_system(upper, b);
+	for c in 0 .. b.columns() {
+		for i in (0 .. size).rev() {
+			// safety: shapes have been checked above
+			unsafe {
+				let mut value = b.get_unchecked([i,c]).clone();
+				for k in i+1 .. size {
+					value = value - upper.get_unchecked([i,k]).clone() * b.get_unchecked([k,c]).clone();
+				}
+				if diagonal == Diagonal::Stored  {value = value / upper.get_unchecked([i,i]).clone()}
+				*b.get_unchecked_mut([i,c]) = value;
+			}
+		}
+	}
+}
+/// solve `Lᵀ x = b` in place of `b`, with `L` the lower triangle of the given square matrix
+pub fn solve_lower_transpose<L, B>(lower: &Matrix<L>, diagonal: Diagonal, b: &mut Matrix<B>)
+where
+	L: Array,
+	B: ArrayMut<Element=L::Element>,
+	L::Element: Scalar,
+{
+	solve_upper(&lower.transpose(), diagonal, b)
+}
+/// solve `Uᵀ x = b` in place of `b`, with `U` the upper triangle of the given square matrix
+pub fn solve_upper_transpose<U, B>(upper: &Matrix<U>, diagonal: Diagonal, b: &mut Matrix<B>)
+where
+	U: Array,
+	B: ArrayMut<Element=U::Element>,
+	U::Element: Scalar,
+{
+	solve_lower(&upper.transpose(), diagonal, b)
+}
+
+fn check_system<A: Array, B: Array>(matrix: &Matrix<A>, b: &Matrix<B>) -> usize {
+	assert_eq!(matrix.rows(), matrix.columns(), "triangular matrix must be square");
+	assert_eq!(b.rows(), matrix.rows(), "right hand side doesn't match the matrix");
+	matrix.rows()
+}
+
+
+macro_rules! triangular {
+	($name:ident, $transposed:ident, $side:literal, $inside:expr,
+	$solve:ident, $solve_transpose:ident, $invert:ident) => {
+		#[doc = concat!("square matrix with only its ", $side, " triangle meaningful, the other half of the storage is never read")]
+		#[derive(Clone)]
+		pub struct $name<A: Array> {
+			matrix: Matrix<A>,
+			diagonal: Diagonal,
+		}
+		impl<A: Array> $name<A> {
+			/// consider the triangle of the given square matrix, including its diagonal
+			pub fn new(matrix: Matrix<A>) -> Self  {Self::with_diagonal(matrix, Diagonal::Stored)}
+			/// consider the triangle of the given square matrix with ones on the diagonal, the stored diagonal is ignored
+			pub fn unit(matrix: Matrix<A>) -> Self  {Self::with_diagonal(matrix, Diagonal::Unit)}
+			/// consider the triangle of the given square matrix, with the given diagonal kind
+			pub fn with_diagonal(matrix: Matrix<A>, diagonal: Diagonal) -> Self {
+				assert_eq!(matrix.rows(), matrix.columns(), "triangular matrix must be square");
+				Self {matrix, diagonal}
+			}
+			/// number of rows and columns
+			pub fn size(&self) -> usize  {self.matrix.rows()}
+			/// kind of diagonal
+			pub fn diagonal(&self) -> Diagonal  {self.diagonal}
+			/// underlying storage, its other half is not meaningful
+			pub fn matrix(&self) -> &Matrix<A>  {&self.matrix}
+			/// underlying storage, modifying its other half has no effect on the triangular matrix
+			pub fn matrix_mut(&mut self) -> &mut Matrix<A>  {&mut self.matrix}
+			/// retreive the underlying storage
+			pub fn into_inner(self) -> Matrix<A>  {self.matrix}
+			/// transposed triangular matrix, viewing the same storage
+			pub fn transpose(&self) -> $transposed<View<'_, A::Element, A::C, A::R>> {
+				$transposed {matrix: self.matrix.transpose(), diagonal: self.diagonal}
+			}
+			/// true if the given index is in the meaningful triangle
+			pub fn contains(&self, [i, j]: [usize; 2]) -> bool  {$inside(i, j)}
+		}
+		impl<A: Array> $name<A>
+		where A::Element: Scalar
+		{
+			/**
+				element `[i, c]` of the product with `right`, only reading the meaningful triangle
+
+				# Safety
+				`right` must have [Self::size] rows and the index must be in the product shape
+			*/
+			unsafe fn product_element<R: Array<Element=A::Element>>(&self, right: &Matrix<R>, [i, c]: [usize; 2]) -> A::Element {
+				(0 .. self.size())
+					.filter(|&k|  $inside(i, k) && !(i == k && self.diagonal == Diagonal::Unit))
+					.fold(
+						if self.diagonal == Diagonal::Unit {right.get_unchecked([i,c]).clone()} else {A::Element::zero()},
+						|sum, k|  sum + self.matrix.get_unchecked([i,k]).clone() * right.get_unchecked([k,c]).clone())
+			}
+			/// element at the given index, with zeros outside the triangle
+			pub fn get(&self, index: [usize; 2]) -> A::Element {
+				let [i, j] = index;
+				if i == j && self.diagonal == Diagonal::Unit  {A::Element::one()}
+				else if $inside(i, j)  {self.matrix[index].clone()}
+				else  {A::Element::zero()}
+			}
+			/// solve `self x = b` in place of `b`
+			pub fn solve_inplace<B: ArrayMut<Element=A::Element>>(&self, b: &mut Matrix<B>) {
+				$solve(&self.matrix, self.diagonal, b)
+			}
+			/// solve `selfᵀ x = b` in place of `b`
+			pub fn solve_transpose_inplace<B: ArrayMut<Element=A::Element>>(&self, b: &mut Matrix<B>) {
+				$solve_transpose(&self.matrix, self.diagonal, b)
+			}
+			/// solve `self x = b`
+			pub fn solve<B, R: Dim, C: Dim>(&self, b: &Matrix<B>) -> Matrix<B::Owned>
+			where B: Array<Element=A::Element, R=R, C=C> + Compatible<R, C>
+			{
+				let mut x = b.owned();
+				self.solve_inplace(&mut x);
+				x
+			}
+			/// bytes of workspace drawn by [Self::solve_with_workspace] for a right-hand side of the given shape
+			pub fn workspace_size(&self, shape: [usize; 2]) -> usize  {workspace_size::<A::Element>(shape)}
+			/// same as [Self::solve] but drawing the result from the workspace
+			pub fn solve_with_workspace<'w, B>(&self, b: &Matrix<B>, workspace: &'w Workspace) -> ScratchMatrix<'w, A::Element, B::R, B::C>
+			where B: Array<Element=A::Element>
+			{
+				let mut x = b.owned_with_workspace(workspace);
+				self.solve_inplace(&mut x);
+				x
+			}
+		}
+		impl<A, R: Dim, C: Dim> $name<A>
+		where
+			A: Array<R=R, C=C> + Compatible<R, C>,
+			A::Element: Scalar,
+		{
+			/// dense copy of this matrix, with zeros outside the triangle
+			pub fn to_dense(&self) -> Matrix<A::Owned> {
+				Matrix::from_field(self.matrix.shape(), |index|  self.get(index))
+			}
+		}
+		impl<A: ArrayMut> $name<A>
+		where A::Element: Scalar
+		{
+			/**
+				invert the matrix in place, the result is triangular on the same side and keeps the diagonal kind.
+
+				Return `false` and leave the matrix unchanged if a diagonal element is zero
+			*/
+			pub fn invert(&mut self) -> bool {
+				let size = self.size();
+				if self.diagonal == Diagonal::Stored && (0 .. size).any(|i|  self.matrix[[i,i]].is_zero())
+					{return false}
+				$invert(&mut self.matrix, self.diagonal);
+				true
+			}
+		}
+
+		impl<A,R,O>
+			MulTo<Matrix<R>, Matrix<O>> for $name<A>
+		where
+			A: Array<Element=O::Element, R=O::R>,
+			R: Array<Element=O::Element, C=O::C>,
+			O: ArrayMut,
+			O::Element: Scalar,
+		{
+			/// triangular-dense product without dynamic allocation, only reading the meaningful triangle
+			fn mul_to<'o>(&self, right: &Matrix<R>, out: &'o mut Matrix<O>) -> &'o mut Matrix<O> {
+				let size = self.size();
+				assert_eq!(right.rows(), size);
+				assert_eq!(out.shape(), [size, right.columns()]);
+				// safety: shapes have been checked above
+				out.set_field(|index|  unsafe {self.product_element(right, index)})
+			}
+		}
+		impl<A,R,RO,CO>
+			Mul<&Matrix<R>> for &$name<A>
+		where
+			A: Array<R=RO> + Compatible<RO, CO>,
+			R: Array<Element=A::Element, C=CO>,
+			A::Element: Scalar,
+		{
+			type Output = Matrix<A::Owned>;
+			/// triangular-dense product
+			fn mul(self, right: &Matrix<R>) -> Self::Output {
+				let size = self.size();
+				assert_eq!(right.rows(), size);
+				// safety: shapes have been checked above
+				Matrix::from_field([size, right.columns()], |index|  unsafe {self.product_element(right, index)})
+			}
+		}
+		impl<'w,A,R>
+			MulWithWorkspace<'w, Matrix<R>> for $name<A>
+		where
+			A: Array,
+			R: Array<Element=A::Element>,
+			A::Element: Scalar,
+		{
+			type Output = ScratchMatrix<'w, A::Element, A::R, R::C>;
+			/// triangular-dense product
+			fn mul_with_workspace(&self, right: &Matrix<R>, workspace: &'w Workspace) -> Self::Output {
+				workspace::product(self, right, [self.size(), right.columns()], workspace)
+			}
+		}
+	};
+}
+triangular!(LowerTriangular, UpperTriangular, "lower", |i, j|  i >= j,
+	solve_lower, solve_lower_transpose, invert_lower);
+triangular!(UpperTriangular, LowerTriangular, "upper", |i, j|  i <= j,
+	solve_upper, solve_upper_transpose, invert_upper);
+
+/// in place inversion of the lower triangle, column by column from the last one
+fn invert_lower<A: ArrayMut>(matrix: &mut Matrix<A>, diagonal: Diagonal)
+where A::Element: Scalar
+{
+	let size = matrix.rows();
+	for j in (0 .. size).rev() {
+		let scale = match diagonal {
+			Diagonal::Stored => {
+				let inverse = A::Element::one() / matrix[[j,j]].clone();
+				matrix[[j,j]] = inverse.clone();
+				A::Element::zero() - inverse
+			},
+			Diagonal::Unit => A::Element::zero() - A::Element::one(),
+		};
+		// column below the diagonal becomes - inverse(L[j+1..,j+1..]) L[j+1..,j] / L[j,j]
+		for i in (j+1 .. size).rev() {
+			let mut value = if diagonal == Diagonal::Unit {matrix[[i,j]].clone()} else {A::Element::zero()};
+			for k in j+1 .. i+1 {
+				if k == i && diagonal == Diagonal::Unit  {continue}
+				value = value + matrix[[i,k]].clone() * matrix[[k,j]].clone();
+			}
+			matrix[[i,j]] = value * scale.clone();
+		}
+	}
+}
+/// in place inversion of the upper triangle, column by column from the first one
+fn invert_upper<A: ArrayMut>(matrix: &mut Matrix<A>, diagonal: Diagonal)
+where A::Element: Scalar
+{
+	let size = matrix.columns();
+	for j in 0 .. size {
+		let scale = match diagonal {
+			Diagonal::Stored => {
+				let inverse = A::Element::one() / matrix[[j,j]].clone();
+				matrix[[j,j]] = inverse.clone();
+				A::Element::zero() - inverse
+			},
+			Diagonal::Unit => A::Element::zero() - A::Element::one(),
+		};
+		// column above the diagonal becomes - inverse(U[..j,..j]) U[..j,j] / U[j,j]
+		for i in 0 .. j {
+			let mut value = if diagonal == Diagonal::Unit {matrix[[i,j]].clone()} else {A::Element::zero()};
+			for k in i .. j {
+				if k == i && diagonal == Diagonal::Unit  {continue}
+				value = value + matrix[[i,k]].clone() * matrix[[k,j]].clone();
+			}
+			matrix[[i,j]] = value * scale.clone();
+		}
+	}
+}
+
+
+#[test]
+fn test_triangular() {
+	// the upper half holds garbage that must never be read
+	let storage = SMatrix::<f64,3,3>::field(|[i,j]|  if i >= j {(1 + i + 2*j) as f64} else {f64::NAN});
+	let lower = LowerTriangular::new(storage.clone());
+	let dense = lower.to_dense();
+	assert_eq!(dense.as_slice(), Some(&[1.,2.,3.,  0.,4.,5.,  0.,0.,7.][..]));
+	assert_eq!(lower.get([0,2]), 0.);
+	assert!(lower.contains([2,0]) && !lower.contains([0,2]));
+
+	let b = SMatrix::<f64,3,2>::field(|[i,j]|  (i + 3*j) as f64 + 1.);
+	let x = lower.solve(&b);
+	assert_eq!((&dense * &x).as_slice(), b.as_slice());
+	assert_eq!((&lower * &x).as_slice(), b.as_slice());
+	let mut x = b.clone();
+	lower.solve_transpose_inplace(&mut x);
+	assert_eq!((&dense.transpose() * &x).as_slice(), b.as_slice());
+
+	let upper = lower.transpose();
+	let x = upper.solve(&b);
+	assert_eq!((&upper.to_dense() * &x).as_slice(), b.as_slice());
+	let mut x = b.clone();
+	solve_upper_transpose(upper.matrix(), Diagonal::Stored, &mut x);
+	assert_eq!((&dense * &x).as_slice(), b.as_slice());
+
+	let unit = LowerTriangular::unit(storage.clone());
+	assert_eq!(unit.to_dense().as_slice(), Some(&[1.,2.,3.,  0.,1.,5.,  0.,0.,1.][..]));
+	let x = unit.solve(&b);
+	assert_eq!((&unit * &x).as_slice(), b.as_slice());
+	let mut out = SMatrix::<f64,3,2>::zeros();
+	unit.mul_to(&x, &mut out);
+	assert_eq!(out.as_slice(), b.as_slice());
+
+	let identity = SMatrix::<f64,3,3>::identity();
+	for diagonal in [Diagonal::Stored, Diagonal::Unit] {
+		let mut inverse = LowerTriangular::with_diagonal(storage.clone(), diagonal);
+		assert!(inverse.invert());
+		let product = &LowerTriangular::with_diagonal(storage.clone(), diagonal).to_dense() * &inverse.to_dense();
+		assert!(product.as_slice().unwrap().iter().zip(identity.as_slice().unwrap()).all(|(a, b)|  (a - b).abs() < 1e-12));
+		// the garbage half is untouched
+		assert!(inverse.matrix()[[0,2]].is_nan());
+
+		let mut inverse = UpperTriangular::with_diagonal(storage.transpose().owned(), diagonal);
+		assert!(inverse.invert());
+		let product = &inverse.to_dense() * &UpperTriangular::with_diagonal(storage.transpose().owned(), diagonal).to_dense();
+		assert!(product.as_slice().unwrap().iter().zip(identity.as_slice().unwrap()).all(|(a, b)|  (a - b).abs() < 1e-12));
+	}
+	let mut singular = UpperTriangular::new(SMatrix::<f64,2,2>::zeros());
+	assert!(!singular.invert());
+}
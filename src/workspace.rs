@@ -0,0 +1,322 @@
+/*!
+	Caller-owned arena for allocation-free operations in real-time loops
+
+	A [Workspace] allocates its memory once, then operations suffixed `_with_workspace` draw their temporaries and results from it as [ScratchMatrix]es, [Dynamic] matrices with aligned and padded columns:
+
+	- [Matrix::owned_with_workspace] and [Matrix::map_with_workspace]
+	- [AddWithWorkspace], [SubWithWorkspace], [MulWithWorkspace] and [DivWithWorkspace] like the common operators, elementwise or with a scalar, and [MulWithWorkspace] for the products of dense, structured and sparse matrices
+	- `solve_with_workspace` methods on solvers, and the Krylov solvers like [cg_with_workspace](crate::krylov::cg_with_workspace)
+
+	The memory drawn by an operation is reported up front, so the workspace can be sized before entering the loop: [workspace_size] gives it for one matrix, as drawn by the operators, solvers have a `workspace_size` method and Krylov solvers a function like [cg_workspace_size](crate::krylov::cg_workspace_size).
+
+	Scratch matrices borrow the workspace, so its memory is reclaimed all at once by [Workspace::reset] when none of them remains. Factorizations own their buffers and are made allocation-free by `refactorize` methods reusing them for a new matrix with the same structure.
+
+	Operations left out still allocate, they are meant to run once before the loop or produce buffers a workspace cannot hold: the construction of factorizations and preconditioners, the residuals history of Krylov solvers, sums of sparse matrices whose pattern is only known once computed, and the operations of [Tensor](crate::tensor::Tensor)s and [Batch](crate::batch::Batch)es which own their buffers
+*/
+
+use crate::prelude::*;
+use crate::matrix::*;
+use crate::matrices::*;
+use crate::allocator::*;
+use crate::operators::*;
+
+use core::{
+	alloc::Layout,
+	cell::Cell,
+	ptr::NonNull,
+	};
+
+
+/// alignment of the workspace buffer, as scratch matrices
+const ALIGN: usize = ALIGNMENT;
+
+/// matrix with its buffer drawn from a [Workspace]
+pub type ScratchMatrix<'w, T, R=Dyn, C=Dyn> = Matrix<Dynamic<T, R, C, &'w Workspace>>;
+
+/// bytes of workspace drawn by a [ScratchMatrix] of the given shape
+pub fn workspace_size<T>(shape: [usize; 2]) -> usize {
+	let [rows, columns] = shape;
+	let size = size_of::<T>();
+	let leading = leading::<T>(rows, ALIGN, true);
+	let align = ALIGN.max(align_of::<T>());
+	// blocks with a larger alignment than the workspace buffer may need to skip some bytes
+	(leading * columns * size).next_multiple_of(align) + (align - ALIGN)
+}
+
+
+/**
+	arena of memory allocated once, from which scratch matrices are drawn without further allocation
+
+	Drawing a matrix bigger than the remaining memory panics
+*/
+pub struct Workspace {
+	data: NonNull<u8>,
+	capacity: usize,
+	/// bytes drawn since the last reset
+	used: Cell<usize>,
+}
+impl Workspace {
+	/// allocate a workspace of the given number of bytes
+	pub fn new(capacity: usize) -> Self {
+		let layout = Layout::from_size_align(capacity, ALIGN).expect("workspace too big to allocate");
+		let data = match Global.allocate(layout) {
+			Ok(data) => data.cast(),
+			Err(_) => std::alloc::handle_alloc_error(layout),
+		};
+		Self {data, capacity, used: Cell::new(0)}
+	}
+	/// total number of bytes
+	pub fn capacity(&self) -> usize  {self.capacity}
+	/// number of bytes currently drawn
+	pub fn used(&self) -> usize  {self.used.get()}
+	/// reclaim all the memory, no scratch matrix can remain since they borrow the workspace
+	pub fn reset(&mut self)  {self.used.set(0)}
+	/// draw a new matrix with every element given by the closure called in column-major order
+	pub fn field<T, R, C, F>(&self, shape: [usize; 2], field: F) -> ScratchMatrix<'_, T, R, C>
+	where
+		T: Element,
+		R: Dim,
+		C: Dim,
+		F: FnMut([usize; 2]) -> T,
+	{
+		Matrix::try_field_in(shape, ALIGN, true, self, field)
+			.unwrap_or_else(|_|  panic!("workspace exhausted, {} bytes used out of {}", self.used(), self.capacity))
+	}
+	/// draw a new matrix of zeros
+	pub fn zeros<T: Scalar, R: Dim, C: Dim>(&self, shape: [usize; 2]) -> ScratchMatrix<'_, T, R, C> {
+		self.field(shape, |_|  T::zero())
+	}
+	/// draw a slice of `len` copies of the value, drawing as much memory as a [ScratchMatrix] of shape `[len, 1]`
+	// the workspace hands out disjoint blocks, so mutable slices drawn from a shared reference cannot alias
+	#[allow(clippy::mut_from_ref)]
+	pub fn slice<T: Copy>(&self, len: usize, value: T) -> &mut [T] {
+		let layout = Layout::array::<T>(len)
+			.and_then(|layout|  layout.align_to(ALIGN))
+			.expect("slice too big");
+		let data = self.allocate(layout)
+			.unwrap_or_else(|_|  panic!("workspace exhausted, {} bytes used out of {}", self.used(), self.capacity))
+			.cast::<T>().as_ptr();
+		// safety: the block is fresh and fits `len` elements, it is only reclaimed by a reset which cannot happen while the slice borrows the workspace
+		unsafe {
+			for i in 0 .. len  {data.add(i).write(value)}
+			core::slice::from_raw_parts_mut(data, len)
+		}
+	}
+}
+// safety: the buffer is owned, and can only be shared between scratch matrices of the same thread
+unsafe impl Send for Workspace {}
+impl Drop for Workspace {
+	fn drop(&mut self) {
+		// safety: the buffer was allocated with this layout
+		unsafe {Global.deallocate(self.data, Layout::from_size_align_unchecked(self.capacity, ALIGN))}
+	}
+}
+// safety: blocks are owned by the workspace buffer which is never moved nor freed before the workspace is dropped
+unsafe impl Allocator for Workspace {
+	fn allocate(&self, layout: Layout) -> Result<NonNull<[u8]>, AllocError> {
+		let base = self.data.as_ptr() as usize;
+		let start = (base + self.used.get()).next_multiple_of(layout.align()) - base;
+		let end = start.checked_add(layout.size())
+			.filter(|&end|  end <= self.capacity)
+			.ok_or(AllocError)?;
+		self.used.set(end);
+		// safety: the block is inside the buffer
+		let ptr = unsafe {self.data.add(start)};
+		Ok(NonNull::slice_from_raw_parts(ptr, layout.size()))
+	}
+	/// memory is only reclaimed by [Workspace::reset]
+	unsafe fn deallocate(&self, _: NonNull<u8>, _: Layout) {}
+}
+impl core::fmt::Debug for Workspace {
+	fn fmt(&self, f: &mut core::fmt::Formatter) -> core::fmt::Result {
+		f.debug_struct("Workspace")
+			.field("capacity", &self.capacity)
+			.field("used", &self.used.get())
+			.finish()
+	}
+}
+
+
+/// sum like [Add](core::ops::Add) but drawing the result from a [Workspace]
+pub trait AddWithWorkspace<'w, T> {
+	type Output;
+	fn add_with_workspace(&self, right: &T, workspace: &'w Workspace) -> Self::Output;
+}
+/// difference like [Sub](core::ops::Sub) but drawing the result from a [Workspace]
+pub trait SubWithWorkspace<'w, T> {
+	type Output;
+	fn sub_with_workspace(&self, right: &T, workspace: &'w Workspace) -> Self::Output;
+}
+/// product like [Mul](core::ops::Mul) but drawing the result from a [Workspace], implemented for the same operands as [MulTo]
+pub trait MulWithWorkspace<'w, T> {
+	type Output;
+	fn mul_with_workspace(&self, right: &T, workspace: &'w Workspace) -> Self::Output;
+}
+/// division like [Div](core::ops::Div) but drawing the result from a [Workspace]
+pub trait DivWithWorkspace<'w, T> {
+	type Output;
+	fn div_with_workspace(&self, right: &T, workspace: &'w Workspace) -> Self::Output;
+}
+
+/// draw a matrix of zeros of the product shape and write the product in it
+pub(crate) fn product<'w, L, R, T, RO, CO>(left: &L, right: &R, shape: [usize; 2], workspace: &'w Workspace) -> ScratchMatrix<'w, T, RO, CO>
+where
+	L: MulTo<R, ScratchMatrix<'w, T, RO, CO>> + ?Sized,
+	T: Scalar,
+	RO: Dim,
+	CO: Dim,
+{
+	let mut out = workspace.zeros(shape);
+	left.mul_to(right, &mut out);
+	out
+}
+
+impl<A: Array> Matrix<A> {
+	/// same as [Matrix::owned] but drawing the copy from the workspace
+	pub fn owned_with_workspace<'w>(&self, workspace: &'w Workspace) -> ScratchMatrix<'w, A::Element, A::R, A::C> {
+		workspace.field(self.shape(), |index|  self[index].clone())
+	}
+	/// same as [Matrix::map] but drawing the result from the workspace
+	pub fn map_with_workspace<'w, T, F>(&self, mut f: F, workspace: &'w Workspace) -> ScratchMatrix<'w, T, A::R, A::C>
+	where
+		T: Element,
+		F: FnMut(&A::Element) -> T,
+	{
+		workspace.field(self.shape(), |index|  f(&self[index]))
+	}
+}
+impl<'w, L, R>
+	MulWithWorkspace<'w, Matrix<R>> for Matrix<L>
+where
+	L: Array,
+	R: Array<Element=L::Element, R=L::C>,
+	L::Element: Scalar,
+{
+	type Output = ScratchMatrix<'w, L::Element, L::R, R::C>;
+	/// matrix product
+	fn mul_with_workspace(&self, right: &Matrix<R>, workspace: &'w Workspace) -> Self::Output {
+		assert_eq!(self.shape()[1], right.shape()[0]);
+		// safety: shapes have been checked above
+		workspace.field([self.shape()[0], right.shape()[1]], |index|  unsafe {product_element(self, right, index)})
+	}
+}
+
+macro_rules! elementwise_workspace {
+	($trait:ident, $method:ident, $methodto:ident) => {
+		impl<'w, L, R>
+			$trait<'w, Matrix<R>> for Matrix<L>
+		where
+			L: Array<Element=R::Element, R=R::R, C=R::C>,
+			R: Array,
+			R::Element: Scalar,
+		{
+			type Output = ScratchMatrix<'w, R::Element, R::R, R::C>;
+			/// elementwise operation
+			fn $method(&self, right: &Matrix<R>, workspace: &'w Workspace) -> Self::Output {
+				let mut out = workspace.zeros(self.shape());
+				self.$methodto(right, &mut out);
+				out
+			}
+		}
+	};
+}
+elementwise_workspace!(AddWithWorkspace, add_with_workspace, add_to);
+elementwise_workspace!(SubWithWorkspace, sub_with_workspace, sub_to);
+
+macro_rules! scalar_workspace {
+	($trait:ident, $method:ident, $methodto:ident) => {
+		impl<'w, L, T>
+			$trait<'w, T> for Matrix<L>
+		where
+			L: Array<Element=T>,
+			T: Scalar,
+		{
+			type Output = ScratchMatrix<'w, T, L::R, L::C>;
+			/// scalar operation
+			fn $method(&self, right: &T, workspace: &'w Workspace) -> Self::Output {
+				let mut out = workspace.zeros(self.shape());
+				self.$methodto(right, &mut out);
+				out
+			}
+		}
+	};
+}
+scalar_workspace!(AddWithWorkspace, add_with_workspace, add_to);
+scalar_workspace!(SubWithWorkspace, sub_with_workspace, sub_to);
+scalar_workspace!(MulWithWorkspace, mul_with_workspace, mul_to);
+scalar_workspace!(DivWithWorkspace, div_with_workspace, div_to);
+
+
+#[test]
+fn test_workspace() {
+	use crate::matrices::*;
+	use crate::banded::*;
+	use crate::sparse::FactorizationError;
+
+	let a = DMatrix::<f64>::field([5, 3], |[i,j]|  (i + 5*j) as f64);
+	let b = DMatrix::<f64>::field([3, 4], |[i,j]|  (i as f64) - (j as f64));
+	let mut workspace = Workspace::new(workspace_size::<f64>([5, 3]) + workspace_size::<f64>([5, 4]));
+	for _ in 0 .. 3 {
+		// the same memory is reused at every iteration
+		let copy = a.owned_with_workspace(&workspace);
+		let product = copy.mul_with_workspace(&b, &workspace);
+		assert_eq!(product.strides(), [1, 8]);
+		assert_eq!(product.as_ptr() as usize % 64, 0);
+		assert_eq!(product[[4,3]], (&a * &b)[[4,3]]);
+		assert_eq!(workspace.used(), workspace.capacity());
+		drop((copy, product));
+		workspace.reset();
+	}
+	// elementwise, scalar and map operations
+	let workspace = Workspace::new(4 * workspace_size::<f64>([5, 3]));
+	let sum = a.add_with_workspace(&a, &workspace);
+	assert_eq!(sum[[4,2]], 28.);
+	let scaled = sum.div_with_workspace(&2., &workspace);
+	assert_eq!(scaled.sub_with_workspace(&a, &workspace).iter().sum::<f64>(), 0.);
+	assert_eq!(a.map_with_workspace(|v|  *v as usize, &workspace)[[1,1]], 6);
+
+	// structured and sparse products give the same as the dense ones
+	let dense = DMatrix::<f64>::field([3, 3], |[i,j]|  (1 + i*j) as f64);
+	let mut workspace = Workspace::new(workspace_size::<f64>([5, 4]));
+	fn check(product: ScratchMatrix<'_, f64>, expected: DMatrix<f64>) {
+		assert_eq!(product.shape(), expected.shape());
+		assert!(product.iter().zip(&expected).all(|(p, e)|  (p - e).abs() < 1e-12));
+	}
+	check(crate::symmetric::Symmetric::<f64, Dyn, Vec<f64>>::from_upper(&dense).mul_with_workspace(&b, &workspace), &dense * &b);
+	workspace.reset();
+	let upper = DMatrix::<f64>::field([3, 3], |[i,j]|  if i <= j {dense[[i,j]]} else {0.});
+	check(crate::triangular::UpperTriangular::new(dense.clone()).mul_with_workspace(&b, &workspace), &upper * &b);
+	workspace.reset();
+	check(Banded::from_dense(&dense, 2, 2).mul_with_workspace(&b, &workspace), &dense * &b);
+	workspace.reset();
+	check(crate::sparse::Csc::from_dense(&dense).mul_with_workspace(&b, &workspace), &dense * &b);
+	workspace.reset();
+	check(a.mul_with_workspace(&crate::sparse::Csr::from_dense(&dense), &workspace), &a * &dense);
+	workspace.reset();
+	let diagonal = crate::diagonal::DiagonalMatrix::new(DVector::<f64>::from(vec![1., 2., 3.]));
+	check(diagonal.mul_with_workspace(&b, &workspace), &diagonal * &b);
+	workspace.reset();
+	let permutation = crate::permutation::PermutationMatrix::try_from_indices(vec![2, 0, 1]).unwrap();
+	check(a.mul_with_workspace(&permutation, &workspace), &a * &permutation);
+	workspace.reset();
+
+	// an exhausted workspace panics
+	let small = Workspace::new(64);
+	assert!(std::panic::catch_unwind(std::panic::AssertUnwindSafe(||  {small.zeros::<f64, Dyn, Dyn>([9, 1]);})).is_err());
+
+	// solves draw their temporaries and results
+	let tridiagonal = Tridiagonal::try_from_diagonals(vec![-1.; 4], vec![2.; 5], vec![-1.; 4]).unwrap();
+	let mut lu = Banded::field([5, 5], 1, 1, |[i,j]|  (1 + i + j) as f64).lu().unwrap();
+	// factorizations reuse their memory for new matrices of the same structure
+	lu.refactorize(&tridiagonal.to_banded()).unwrap();
+	assert_eq!(lu.refactorize(&Banded::zeros([5, 5], 2, 1)).err(), Some(FactorizationError::PatternMismatch));
+	let rhs = DMatrix::<f64>::field([5, 2], |[i,j]|  (i*j) as f64);
+	let mut workspace = Workspace::new(tridiagonal.workspace_size([5, 2]).max(lu.workspace_size([5, 2])));
+	let x = tridiagonal.solve_with_workspace(&rhs, &workspace).unwrap();
+	let expected = tridiagonal.solve(&rhs).unwrap();
+	assert!(x.iter().zip(&expected).all(|(x, expected)|  (x - expected).abs() < 1e-12));
+	drop(x);
+	workspace.reset();
+	let y = lu.solve_with_workspace(&rhs, &workspace);
+	assert!(y.iter().zip(&expected).all(|(y, expected)|  (y - expected).abs() < 1e-12));
+}